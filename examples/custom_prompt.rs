@@ -66,7 +66,7 @@ fn main() -> io::Result<()> {
             Signal::Success(buffer) => {
                 println!("We processed: {buffer}");
             }
-            Signal::CtrlD | Signal::CtrlC => {
+            Signal::CtrlD | Signal::CtrlC | Signal::IdleTimeout => {
                 println!("\nAborted!");
                 break Ok(());
             }
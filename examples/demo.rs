@@ -76,6 +76,7 @@ fn main() -> reedline::Result<()> {
     let cursor_config = CursorConfig {
         vi_insert: Some(SetCursorStyle::BlinkingBar),
         vi_normal: Some(SetCursorStyle::SteadyBlock),
+        vi_visual_line: None,
         emacs: None,
     };
 
@@ -137,7 +138,7 @@ fn main() -> reedline::Result<()> {
         let sig = line_editor.read_line(&prompt);
 
         match sig {
-            Ok(Signal::CtrlD) => {
+            Ok(Signal::CtrlD) | Ok(Signal::IdleTimeout) => {
                 break;
             }
             Ok(Signal::Success(buffer)) => {
@@ -0,0 +1,36 @@
+// Ask questions with the confirm, select and multi-select widgets of reedline.
+// cargo run --example widgets
+//
+// Type to filter the items, move with the arrow keys, check items with [Space] and pick
+// with [Enter]. Esc, Ctrl-C or Ctrl-D cancel.
+
+use reedline::{DefaultPrompt, DefaultPromptSegment, Reedline};
+use std::io;
+
+fn prompt(question: &str) -> DefaultPrompt {
+    DefaultPrompt::new(
+        DefaultPromptSegment::Basic(question.to_string()),
+        DefaultPromptSegment::Empty,
+    )
+}
+
+fn main() -> io::Result<()> {
+    let mut line_editor = Reedline::create();
+    let fruits = ["apple", "banana", "cherry", "kiwi", "mango", "pear"];
+
+    match line_editor.select(&prompt("Favorite fruit"), &fruits)? {
+        Some(index) => println!("\nYou picked {}", fruits[index]),
+        None => println!("\nNo favorite then"),
+    }
+
+    if let Some(checked) = line_editor.multi_select(&prompt("Fruits to buy"), &fruits)? {
+        let basket: Vec<_> = checked.into_iter().map(|index| fruits[index]).collect();
+        println!("\nBuying {basket:?}");
+    }
+
+    if line_editor.confirm(&prompt("Done shopping"), Some(true))? == Some(true) {
+        println!("\nBye!");
+    }
+
+    Ok(())
+}
@@ -0,0 +1,198 @@
+#[cfg(feature = "async")]
+use super::InputEventStream;
+use super::{InputSource, OutputSink};
+use crossterm::{cursor, event, event::Event, terminal, tty::IsTty};
+#[cfg(feature = "async")]
+use futures_util::StreamExt;
+#[cfg(feature = "async")]
+use std::sync::Arc;
+#[cfg(unix)]
+use std::time::Instant;
+use std::{
+    io::{BufRead, BufWriter, Result, Stderr, Write},
+    time::Duration,
+};
+
+/// How long an answer of the terminal that missed its timeout is waited for, to drop it
+#[cfg(unix)]
+const LATE_REPLY_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Reads the events from the terminal reedline is running in
+///
+/// If `stdin` is not a terminal or `TERM` is set to `dumb`, lines are read from `stdin` as is.
+/// Answers of the terminal are read from `stdin` on unix platforms, except while an
+/// [`InputSource::event_stream()`] is alive, as crossterm reads `stdin` for it in the
+/// background.
+#[derive(Default)]
+pub struct DefaultInputSource {
+    // Shared with the event streams that are alive
+    #[cfg(feature = "async")]
+    event_streams: Arc<()>,
+}
+
+impl InputSource for DefaultInputSource {
+    fn poll(&mut self, timeout: Duration) -> Result<bool> {
+        event::poll(timeout)
+    }
+
+    fn read(&mut self) -> Result<Event> {
+        event::read()
+    }
+
+    fn enable_raw_mode(&mut self) -> Result<()> {
+        terminal::enable_raw_mode()
+    }
+
+    fn disable_raw_mode(&mut self) -> Result<()> {
+        terminal::disable_raw_mode()
+    }
+
+    fn is_terminal(&self) -> bool {
+        std::io::stdin().is_tty() && std::env::var_os("TERM").map_or(true, |term| term != "dumb")
+    }
+
+    fn read_plain_line(&mut self) -> Result<Option<String>> {
+        let mut line = String::new();
+        if std::io::stdin().lock().read_line(&mut line)? == 0 {
+            return Ok(None);
+        }
+        if line.ends_with('\n') {
+            line.pop();
+            if line.ends_with('\r') {
+                line.pop();
+            }
+        }
+        Ok(Some(line))
+    }
+
+    #[cfg(unix)]
+    fn read_terminal_reply(&mut self, timeout: Duration) -> Result<Option<Vec<u8>>> {
+        #[cfg(feature = "async")]
+        if Arc::strong_count(&self.event_streams) > 1 {
+            return Ok(None);
+        }
+
+        let stdin = std::io::stdin();
+        let mut reply = Vec::new();
+        if read_until_device_attributes(&stdin, &mut reply, timeout) {
+            Ok(Some(reply))
+        } else {
+            // Drop the late answer, so it doesn't reach the line editor as typed keys
+            read_until_device_attributes(&stdin, &mut reply, LATE_REPLY_TIMEOUT);
+            Ok(None)
+        }
+    }
+
+    #[cfg(feature = "async")]
+    fn event_stream(&mut self) -> InputEventStream {
+        let event_streams = Arc::clone(&self.event_streams);
+        Box::pin(event::EventStream::new().map(move |event| {
+            // Keeps the stream counted as alive until it is dropped
+            let _alive = &event_streams;
+            event
+        }))
+    }
+}
+
+/// Reads `stdin` into `reply` until it ends with the answer to the device attributes
+/// request, returns `false` if that took longer than `timeout`
+#[cfg(unix)]
+fn read_until_device_attributes(
+    stdin: &std::io::Stdin,
+    reply: &mut Vec<u8>,
+    timeout: Duration,
+) -> bool {
+    use rustix::event::{poll, PollFd, PollFlags};
+
+    let deadline = Instant::now() + timeout;
+    while !ends_with_device_attributes(reply) {
+        let remaining = match deadline.checked_duration_since(Instant::now()) {
+            Some(remaining) => remaining,
+            None => return false,
+        };
+        let mut fds = [PollFd::new(stdin, PollFlags::IN)];
+        match poll(&mut fds, remaining.as_millis().max(1) as i32) {
+            Ok(0) | Err(_) => return false,
+            Ok(_) => {}
+        }
+
+        // Byte by byte to leave the following input to the line editor
+        let mut byte = [0];
+        match rustix::io::read(stdin, &mut byte) {
+            Ok(1) => reply.push(byte[0]),
+            _ => return false,
+        }
+    }
+    true
+}
+
+/// Whether `reply` ends with the answer to the device attributes request,
+/// `ESC [ ? <attributes> c`
+#[cfg(unix)]
+fn ends_with_device_attributes(reply: &[u8]) -> bool {
+    let attributes = match reply.strip_suffix(b"c") {
+        Some(attributes) => attributes,
+        None => return false,
+    };
+    let start = match attributes
+        .windows(3)
+        .rposition(|window| window == b"\x1b[?")
+    {
+        Some(start) => start + 3,
+        None => return false,
+    };
+    attributes[start..]
+        .iter()
+        .all(|byte| byte.is_ascii_digit() || *byte == b';')
+}
+
+/// Buffered output to `stderr` of the terminal reedline is running in
+pub struct DefaultOutputSink {
+    stderr: BufWriter<Stderr>,
+}
+
+impl Default for DefaultOutputSink {
+    fn default() -> Self {
+        DefaultOutputSink {
+            stderr: BufWriter::new(std::io::stderr()),
+        }
+    }
+}
+
+impl Write for DefaultOutputSink {
+    fn write(&mut self, buf: &[u8]) -> Result<usize> {
+        self.stderr.write(buf)
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        self.stderr.flush()
+    }
+}
+
+impl OutputSink for DefaultOutputSink {
+    fn size(&self) -> Result<(u16, u16)> {
+        terminal::size()
+    }
+
+    fn cursor_position(&mut self) -> Result<(u16, u16)> {
+        cursor::position()
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case(b"\x1b[?62;22c", true)]
+    #[case(b"\x1b]52;c;Zm9v\x07\x1b[?1;2c", true)]
+    #[case(b"\x1b]52;c;Zm9v\x07", false)]
+    #[case(b"\x1b[?62;22", false)]
+    // A clipboard payload ending with `c` is no answer to the request
+    #[case(b"\x1b]52;c;YWJj", false)]
+    fn replies_end_with_the_device_attributes(#[case] reply: &[u8], #[case] expected: bool) {
+        assert_eq!(ends_with_device_attributes(reply), expected);
+    }
+}
@@ -0,0 +1,91 @@
+mod default;
+#[cfg(test)]
+pub(crate) mod test_fixtures;
+mod virtual_terminal;
+
+pub use default::{DefaultInputSource, DefaultOutputSink};
+pub use virtual_terminal::VirtualTerminal;
+
+use crossterm::event::Event;
+#[cfg(feature = "async")]
+use futures_util::Stream;
+#[cfg(feature = "async")]
+use std::pin::Pin;
+use std::{
+    io::{Result, Write},
+    time::Duration,
+};
+
+/// Source of the terminal events driving [`crate::Reedline::read_line()`]
+///
+/// The default implementation [`DefaultInputSource`] reads from the real terminal through
+/// crossterm. Replace it (e.g. with a [`VirtualTerminal`]) to drive the engine without a TTY.
+pub trait InputSource: Send {
+    /// Wait up to `timeout` for an event to become available
+    ///
+    /// Returns `Ok(true)` if [`InputSource::read()`] will not block.
+    fn poll(&mut self, timeout: Duration) -> Result<bool>;
+
+    /// Read the next event, blocking until one is available
+    fn read(&mut self) -> Result<Event>;
+
+    /// Put the terminal into raw mode before the line editor starts reading
+    fn enable_raw_mode(&mut self) -> Result<()>;
+
+    /// Restore the terminal from raw mode after the line editor has finished reading
+    fn disable_raw_mode(&mut self) -> Result<()>;
+
+    /// Whether the input comes from an interactive terminal the line editor can drive
+    ///
+    /// Without one, [`crate::Reedline::read_line()`] reads plain lines with
+    /// [`InputSource::read_plain_line()`] instead of enabling raw mode. The default assumes a
+    /// terminal.
+    fn is_terminal(&self) -> bool {
+        true
+    }
+
+    /// Read the next line of input as is, without the line ending
+    ///
+    /// Only used when [`InputSource::is_terminal()`] is `false`. Returns `Ok(None)` at the end
+    /// of the input.
+    fn read_plain_line(&mut self) -> Result<Option<String>> {
+        Ok(None)
+    }
+
+    /// Read the answer of the terminal to a request the line editor wrote to its output
+    ///
+    /// The request ends with the device attributes request `ESC [ c`, so the answer is read up
+    /// to the answer to it, `ESC [ ? <attributes> c`. Returns `Ok(None)` if it didn't arrive
+    /// within `timeout`. Used to paste from the [`crate::Osc52Clipboard`]. The default has no
+    /// terminal to answer.
+    fn read_terminal_reply(&mut self, _timeout: Duration) -> Result<Option<Vec<u8>>> {
+        Ok(None)
+    }
+
+    /// Stream of the events awaited by [`crate::Reedline::read_line_async()`]
+    ///
+    /// Events that are already queued are still drained with [`InputSource::poll()`] and
+    /// [`InputSource::read()`]. The default reads from the terminal through crossterm's
+    /// `EventStream`.
+    #[cfg(feature = "async")]
+    fn event_stream(&mut self) -> InputEventStream {
+        Box::pin(crossterm::event::EventStream::new())
+    }
+}
+
+/// Stream of terminal events returned by [`InputSource::event_stream()`]
+#[cfg(feature = "async")]
+pub type InputEventStream = Pin<Box<dyn Stream<Item = Result<Event>> + Send>>;
+
+/// Sink for everything the [`crate::Painter`] renders
+///
+/// Besides accepting the escape sequences written by the painter, the sink has to report the
+/// size of the screen and the current cursor position, as the painter uses them to place the
+/// prompt.
+pub trait OutputSink: Write + Send {
+    /// Size of the screen as `(columns, rows)`
+    fn size(&self) -> Result<(u16, u16)>;
+
+    /// Current 0 based position of the cursor as `(column, row)`
+    fn cursor_position(&mut self) -> Result<(u16, u16)>;
+}
@@ -0,0 +1,41 @@
+//! Helpers shared by the tests driving [`crate::Reedline`] through a [`VirtualTerminal`]
+
+use super::VirtualTerminal;
+use crate::{DefaultPrompt, DefaultPromptSegment, Reedline};
+use crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers};
+#[cfg(feature = "async")]
+use std::time::{Duration, Instant};
+
+/// A key press without modifiers
+pub(crate) fn key(code: KeyCode) -> Event {
+    Event::Key(KeyEvent::new(code, KeyModifiers::NONE))
+}
+
+/// A plain `> ` prompt
+pub(crate) fn prompt() -> DefaultPrompt {
+    DefaultPrompt::new(
+        DefaultPromptSegment::Basic("> ".to_string()),
+        DefaultPromptSegment::Empty,
+    )
+}
+
+/// A line editor with the default configuration reading from and painting to `terminal`
+pub(crate) fn line_editor(terminal: &VirtualTerminal) -> Reedline {
+    Reedline::create()
+        .with_input_source(Box::new(terminal.clone()))
+        .with_output_sink(Box::new(terminal.clone()))
+}
+
+/// Waits up to a few seconds for `condition` to hold, for tests watching a line editor that
+/// runs concurrently
+#[cfg(feature = "async")]
+pub(crate) fn eventually(condition: impl Fn() -> bool) -> bool {
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while !condition() {
+        if Instant::now() >= deadline {
+            return false;
+        }
+        std::thread::sleep(Duration::from_millis(10));
+    }
+    true
+}
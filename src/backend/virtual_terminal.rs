@@ -0,0 +1,832 @@
+#[cfg(feature = "async")]
+use super::InputEventStream;
+use super::{InputSource, OutputSink};
+use crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers};
+use std::{
+    collections::VecDeque,
+    io::{Error, ErrorKind, Result, Write},
+    sync::{Arc, Mutex, MutexGuard},
+    time::Duration,
+};
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
+/// In-memory terminal to drive [`crate::Reedline`] without a TTY
+///
+/// The virtual terminal plays back a scripted list of [`Event`]s as [`InputSource`] and
+/// interprets the escape sequences written to it as [`OutputSink`], keeping track of the
+/// rendered screen grid and the cursor. Clones share the same state, so one handle can be
+/// given to the engine while another one is used to inspect the screen.
+///
+/// Once all scripted events have been read, the first timed [`InputSource::poll()`] waits for
+/// the timeout and reports that no event is available. Every following blocking wait fails with
+/// [`ErrorKind::UnexpectedEof`], ending [`crate::Reedline::read_line()`]. The event stream
+/// used by `Reedline::read_line_async()` instead stays pending until more events are pushed.
+/// Answers to requests of the line editor, like pastes from the [`crate::Osc52Clipboard`], are
+/// scripted separately with [`VirtualTerminal::push_reply()`].
+///
+/// ## Example
+///
+/// ```rust
+/// use crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers};
+/// use reedline::{DefaultPrompt, DefaultPromptSegment, Reedline, Signal, VirtualTerminal};
+///
+/// let terminal = VirtualTerminal::new(40, 10);
+/// terminal.push_str("hello");
+/// terminal.push_event(Event::Key(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE)));
+///
+/// let mut line_editor = Reedline::create()
+///     .with_input_source(Box::new(terminal.clone()))
+///     .with_output_sink(Box::new(terminal.clone()));
+/// let prompt = DefaultPrompt::new(
+///     DefaultPromptSegment::Basic("test".to_string()),
+///     DefaultPromptSegment::Empty,
+/// );
+///
+/// let signal = line_editor.read_line(&prompt).unwrap();
+/// assert!(matches!(signal, Signal::Success(buffer) if buffer == "hello"));
+/// assert_eq!(terminal.screen()[0], "test〉hello");
+/// ```
+#[derive(Clone)]
+pub struct VirtualTerminal {
+    state: Arc<Mutex<TerminalState>>,
+}
+
+struct TerminalState {
+    events: VecDeque<Event>,
+    replies: VecDeque<Vec<u8>>,
+    // Set when a timed poll found no scripted event left
+    exhausted: bool,
+    #[cfg(feature = "async")]
+    waker: Option<std::task::Waker>,
+    raw_mode: bool,
+    screen: Screen,
+    parser: Parser,
+    output: Vec<u8>,
+}
+
+impl VirtualTerminal {
+    /// Create a blank terminal with the given number of columns and rows
+    pub fn new(width: u16, height: u16) -> Self {
+        VirtualTerminal {
+            state: Arc::new(Mutex::new(TerminalState {
+                events: VecDeque::new(),
+                replies: VecDeque::new(),
+                exhausted: false,
+                #[cfg(feature = "async")]
+                waker: None,
+                raw_mode: false,
+                screen: Screen::new(width, height),
+                parser: Parser::default(),
+                output: Vec::new(),
+            })),
+        }
+    }
+
+    /// A builder to script the events the terminal plays back
+    #[must_use]
+    pub fn with_events(self, events: impl IntoIterator<Item = Event>) -> Self {
+        self.push_events(events);
+        self
+    }
+
+    /// Append an event to the script
+    pub fn push_event(&self, event: Event) {
+        let mut state = self.state();
+        state.events.push_back(event);
+        state.exhausted = false;
+        #[cfg(feature = "async")]
+        if let Some(waker) = state.waker.take() {
+            waker.wake();
+        }
+    }
+
+    /// Append several events to the script
+    pub fn push_events(&self, events: impl IntoIterator<Item = Event>) {
+        for event in events {
+            self.push_event(event);
+        }
+    }
+
+    /// Append a key press for every character of `text` to the script
+    pub fn push_str(&self, text: &str) {
+        self.push_events(text.chars().map(|c| {
+            let modifiers = if c.is_uppercase() {
+                KeyModifiers::SHIFT
+            } else {
+                KeyModifiers::NONE
+            };
+            Event::Key(KeyEvent::new(KeyCode::Char(c), modifiers))
+        }));
+    }
+
+    /// Append an answer of the terminal, read by the next
+    /// [`InputSource::read_terminal_reply()`]
+    pub fn push_reply(&self, reply: &[u8]) {
+        self.state().replies.push_back(reply.to_vec());
+    }
+
+    /// Number of scripted events that have not been read yet
+    pub fn pending_events(&self) -> usize {
+        self.state().events.len()
+    }
+
+    /// Rows of the visible screen with trailing whitespace removed
+    pub fn screen(&self) -> Vec<String> {
+        self.state().screen.rows()
+    }
+
+    /// Rows that were scrolled off the top of the screen, oldest first
+    pub fn scrollback(&self) -> Vec<String> {
+        self.state().screen.scrollback.clone()
+    }
+
+    /// Current 0 based position of the cursor as `(column, row)`
+    pub fn cursor(&self) -> (u16, u16) {
+        let state = self.state();
+        (state.screen.column, state.screen.row)
+    }
+
+    /// Whether the last written sequences left the cursor visible
+    pub fn is_cursor_visible(&self) -> bool {
+        self.state().screen.cursor_visible
+    }
+
+    /// Whether the terminal is currently in raw mode
+    pub fn is_raw_mode_enabled(&self) -> bool {
+        self.state().raw_mode
+    }
+
+    /// Everything written to the terminal so far, including escape sequences
+    pub fn output(&self) -> String {
+        String::from_utf8_lossy(&self.state().output).into_owned()
+    }
+
+    /// Change the size of the screen
+    ///
+    /// Scripted [`Event::Resize`] events are applied automatically when they are read.
+    pub fn resize(&self, width: u16, height: u16) {
+        self.state().screen.resize(width, height);
+    }
+
+    fn state(&self) -> MutexGuard<'_, TerminalState> {
+        // A panic while holding the lock can only come from a test assertion,
+        // the state itself is still consistent.
+        self.state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl InputSource for VirtualTerminal {
+    fn poll(&mut self, timeout: Duration) -> Result<bool> {
+        let mut state = self.state();
+        if !state.events.is_empty() {
+            Ok(true)
+        } else if timeout.is_zero() {
+            Ok(false)
+        } else if !state.exhausted {
+            state.exhausted = true;
+            drop(state);
+            std::thread::sleep(timeout);
+            Ok(false)
+        } else {
+            Err(Error::new(
+                ErrorKind::UnexpectedEof,
+                "no scripted events left",
+            ))
+        }
+    }
+
+    fn read(&mut self) -> Result<Event> {
+        let mut state = self.state();
+        match state.events.pop_front() {
+            Some(event) => {
+                if let Event::Resize(width, height) = event {
+                    state.screen.resize(width, height);
+                }
+                Ok(event)
+            }
+            None => Err(Error::new(
+                ErrorKind::UnexpectedEof,
+                "no scripted events left",
+            )),
+        }
+    }
+
+    fn enable_raw_mode(&mut self) -> Result<()> {
+        self.state().raw_mode = true;
+        Ok(())
+    }
+
+    fn disable_raw_mode(&mut self) -> Result<()> {
+        self.state().raw_mode = false;
+        Ok(())
+    }
+
+    fn read_terminal_reply(&mut self, _timeout: Duration) -> Result<Option<Vec<u8>>> {
+        Ok(self.state().replies.pop_front())
+    }
+
+    #[cfg(feature = "async")]
+    fn event_stream(&mut self) -> InputEventStream {
+        let mut terminal = self.clone();
+        Box::pin(futures_util::stream::poll_fn(move |cx| {
+            if terminal.pending_events() > 0 {
+                std::task::Poll::Ready(Some(terminal.read()))
+            } else {
+                terminal.state().waker = Some(cx.waker().clone());
+                std::task::Poll::Pending
+            }
+        }))
+    }
+}
+
+impl Write for VirtualTerminal {
+    fn write(&mut self, buf: &[u8]) -> Result<usize> {
+        let mut state = self.state();
+        let TerminalState {
+            screen,
+            parser,
+            output,
+            ..
+        } = &mut *state;
+        output.extend_from_slice(buf);
+        parser.feed(buf, screen);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        Ok(())
+    }
+}
+
+impl OutputSink for VirtualTerminal {
+    fn size(&self) -> Result<(u16, u16)> {
+        let state = self.state();
+        Ok((state.screen.width, state.screen.height))
+    }
+
+    fn cursor_position(&mut self) -> Result<(u16, u16)> {
+        Ok(self.cursor())
+    }
+}
+
+/// Grid of cells with a cursor
+///
+/// Every cell holds one grapheme. A wide grapheme is followed by an empty cell.
+struct Screen {
+    width: u16,
+    height: u16,
+    cells: Vec<Vec<String>>,
+    scrollback: Vec<String>,
+    column: u16,
+    row: u16,
+    saved_position: (u16, u16),
+    // Terminals only wrap once the next grapheme is printed in the last column
+    pending_wrap: bool,
+    cursor_visible: bool,
+}
+
+impl Screen {
+    fn new(width: u16, height: u16) -> Self {
+        let width = width.max(1);
+        let height = height.max(1);
+        Screen {
+            width,
+            height,
+            cells: (0..height).map(|_| Self::blank_row(width)).collect(),
+            scrollback: Vec::new(),
+            column: 0,
+            row: 0,
+            saved_position: (0, 0),
+            pending_wrap: false,
+            cursor_visible: true,
+        }
+    }
+
+    fn blank_row(width: u16) -> Vec<String> {
+        vec![" ".to_string(); width as usize]
+    }
+
+    fn render_row(row: &[String]) -> String {
+        row.concat().trim_end().to_string()
+    }
+
+    fn rows(&self) -> Vec<String> {
+        self.cells.iter().map(|row| Self::render_row(row)).collect()
+    }
+
+    fn resize(&mut self, width: u16, height: u16) {
+        let width = width.max(1);
+        let height = height.max(1);
+        for row in self.cells.iter_mut() {
+            row.resize(width as usize, " ".to_string());
+        }
+        // Drop the rows below the cursor before pushing rows into the scrollback
+        while self.cells.len() > height as usize && self.cells.len() > self.row as usize + 1 {
+            self.cells.pop();
+        }
+        while self.cells.len() > height as usize {
+            let row = self.cells.remove(0);
+            self.scrollback.push(Self::render_row(&row));
+            self.row = self.row.saturating_sub(1);
+        }
+        while self.cells.len() < height as usize {
+            self.cells.push(Self::blank_row(width));
+        }
+        self.width = width;
+        self.height = height;
+        self.move_to(self.column, self.row);
+    }
+
+    fn move_to(&mut self, column: u16, row: u16) {
+        self.column = column.min(self.width - 1);
+        self.row = row.min(self.height - 1);
+        self.pending_wrap = false;
+    }
+
+    fn scroll_up(&mut self, lines: u16) {
+        for _ in 0..lines.min(self.height) {
+            let row = self.cells.remove(0);
+            self.scrollback.push(Self::render_row(&row));
+            self.cells.push(Self::blank_row(self.width));
+        }
+    }
+
+    fn scroll_down(&mut self, lines: u16) {
+        for _ in 0..lines.min(self.height) {
+            self.cells.pop();
+            self.cells.insert(0, Self::blank_row(self.width));
+        }
+    }
+
+    fn line_feed(&mut self) {
+        if self.row + 1 >= self.height {
+            self.scroll_up(1);
+        } else {
+            self.row += 1;
+        }
+        self.pending_wrap = false;
+    }
+
+    fn print(&mut self, text: &str) {
+        for grapheme in text.graphemes(true) {
+            match grapheme {
+                "\r" => self.move_to(0, self.row),
+                "\n" | "\r\n" => {
+                    if grapheme == "\r\n" {
+                        self.column = 0;
+                    }
+                    self.line_feed();
+                }
+                "\x08" => self.move_to(self.column.saturating_sub(1), self.row),
+                "\t" => {
+                    let next_stop = (self.column / 8 + 1) * 8;
+                    self.move_to(next_stop, self.row);
+                }
+                g if g.chars().all(char::is_control) => {}
+                g => self.print_grapheme(g),
+            }
+        }
+    }
+
+    fn print_grapheme(&mut self, grapheme: &str) {
+        let width = grapheme.width() as u16;
+        if width == 0 {
+            // Combining characters attach to the previous cell
+            let column = if self.pending_wrap {
+                self.column
+            } else {
+                self.column.saturating_sub(1)
+            };
+            self.cells[self.row as usize][column as usize].push_str(grapheme);
+            return;
+        }
+
+        if self.pending_wrap || self.column + width > self.width {
+            self.column = 0;
+            self.line_feed();
+        }
+
+        let row = &mut self.cells[self.row as usize];
+        row[self.column as usize] = grapheme.to_string();
+        for offset in 1..width.min(self.width - self.column) {
+            row[(self.column + offset) as usize] = String::new();
+        }
+
+        if self.column + width >= self.width {
+            self.column = self.width - 1;
+            self.pending_wrap = true;
+        } else {
+            self.column += width;
+        }
+    }
+
+    fn clear_cells(&mut self, row: u16, columns: std::ops::Range<u16>) {
+        let row = &mut self.cells[row as usize];
+        for column in columns {
+            row[column as usize] = " ".to_string();
+        }
+    }
+
+    fn erase_in_display(&mut self, mode: u16) {
+        match mode {
+            0 => {
+                self.clear_cells(self.row, self.column..self.width);
+                for row in self.row + 1..self.height {
+                    self.clear_cells(row, 0..self.width);
+                }
+            }
+            1 => {
+                for row in 0..self.row {
+                    self.clear_cells(row, 0..self.width);
+                }
+                self.clear_cells(self.row, 0..self.column + 1);
+            }
+            2 => {
+                for row in 0..self.height {
+                    self.clear_cells(row, 0..self.width);
+                }
+            }
+            3 => self.scrollback.clear(),
+            _ => {}
+        }
+    }
+
+    fn erase_in_line(&mut self, mode: u16) {
+        match mode {
+            0 => self.clear_cells(self.row, self.column..self.width),
+            1 => self.clear_cells(self.row, 0..self.column + 1),
+            2 => self.clear_cells(self.row, 0..self.width),
+            _ => {}
+        }
+    }
+
+    fn control_sequence(&mut self, private: bool, params: &[u16], action: char) {
+        let first = params.first().copied().unwrap_or(0);
+        // Movements treat a missing or zero parameter as one
+        let count = first.max(1);
+        match (private, action) {
+            (false, 'H' | 'f') => {
+                let row = first.max(1) - 1;
+                let column = params.get(1).copied().unwrap_or(0).max(1) - 1;
+                self.move_to(column, row);
+            }
+            (false, 'A') => self.move_to(self.column, self.row.saturating_sub(count)),
+            (false, 'B') => self.move_to(self.column, self.row.saturating_add(count)),
+            (false, 'C') => self.move_to(self.column.saturating_add(count), self.row),
+            (false, 'D') => self.move_to(self.column.saturating_sub(count), self.row),
+            (false, 'E') => self.move_to(0, self.row.saturating_add(count)),
+            (false, 'F') => self.move_to(0, self.row.saturating_sub(count)),
+            (false, 'G') => self.move_to(count - 1, self.row),
+            (false, 'd') => self.move_to(self.column, count - 1),
+            (false, 'J') => self.erase_in_display(first),
+            (false, 'K') => self.erase_in_line(first),
+            (false, 'S') => self.scroll_up(count),
+            (false, 'T') => self.scroll_down(count),
+            (false, 's') => self.saved_position = (self.column, self.row),
+            (false, 'u') => self.move_to(self.saved_position.0, self.saved_position.1),
+            (true, 'h') if params.contains(&25) => self.cursor_visible = true,
+            (true, 'l') if params.contains(&25) => self.cursor_visible = false,
+            // Styling, cursor shapes and mode switches don't affect the grid
+            _ => {}
+        }
+    }
+
+    fn escape(&mut self, action: char) {
+        match action {
+            '7' => self.saved_position = (self.column, self.row),
+            '8' => self.move_to(self.saved_position.0, self.saved_position.1),
+            _ => {}
+        }
+    }
+}
+
+/// Incremental parser for the output, sequences may be split across writes
+#[derive(Default)]
+struct Parser {
+    pending: Vec<u8>,
+}
+
+impl Parser {
+    fn feed(&mut self, bytes: &[u8], screen: &mut Screen) {
+        self.pending.extend_from_slice(bytes);
+        let mut position = 0;
+        loop {
+            let rest = &self.pending[position..];
+            if rest.is_empty() {
+                break;
+            }
+            if rest[0] == 0x1b {
+                match Self::escape_sequence(rest, screen) {
+                    Some(consumed) => position += consumed,
+                    None => break,
+                }
+            } else {
+                let text_end = rest.iter().position(|&b| b == 0x1b).unwrap_or(rest.len());
+                let text = &rest[..text_end];
+                let valid = match std::str::from_utf8(text) {
+                    Ok(text) => text,
+                    Err(err) => {
+                        let valid_up_to = err.valid_up_to();
+                        if err.error_len().is_some() {
+                            // Skip invalid bytes instead of getting stuck on them
+                            let invalid = valid_up_to + err.error_len().unwrap_or(1);
+                            screen.print(&String::from_utf8_lossy(&text[..invalid]));
+                            position += invalid;
+                            continue;
+                        }
+                        // Incomplete character, wait for the rest of it
+                        std::str::from_utf8(&text[..valid_up_to]).unwrap_or_default()
+                    }
+                };
+                if valid.is_empty() {
+                    break;
+                }
+                screen.print(valid);
+                position += valid.len();
+            }
+        }
+        self.pending.drain(..position);
+    }
+
+    /// Interprets the escape sequence at the start of `bytes`
+    ///
+    /// Returns the number of consumed bytes or `None` if the sequence is incomplete
+    fn escape_sequence(bytes: &[u8], screen: &mut Screen) -> Option<usize> {
+        match bytes.get(1)? {
+            b'[' => {
+                let end = bytes[2..].iter().position(|b| (0x40..=0x7e).contains(b))? + 2;
+                let body = std::str::from_utf8(&bytes[2..end]).unwrap_or_default();
+                let private = body.starts_with('?');
+                let params: Vec<u16> = body
+                    .trim_start_matches(['?', '>', '='])
+                    .trim_end_matches(|c: char| !c.is_ascii_digit() && c != ';')
+                    .split(';')
+                    .map(|param| param.parse().unwrap_or(0))
+                    .collect();
+                screen.control_sequence(private, &params, bytes[end] as char);
+                Some(end + 1)
+            }
+            b']' => {
+                // Operating system commands end with BEL or ST
+                let body = &bytes[2..];
+                let bel = body.iter().position(|&b| b == 0x07);
+                let st = body.windows(2).position(|w| w == b"\x1b\\");
+                match (bel, st) {
+                    (Some(bel), Some(st)) if st < bel => Some(st + 4),
+                    (Some(bel), _) => Some(bel + 3),
+                    (None, Some(st)) => Some(st + 4),
+                    (None, None) => None,
+                }
+            }
+            &action => {
+                screen.escape(action as char);
+                Some(2)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::test_fixtures::{key, line_editor, prompt};
+    use crate::Signal;
+    use crossterm::{
+        cursor::{MoveTo, RestorePosition, SavePosition},
+        style::{Color, Print, SetForegroundColor},
+        terminal::{Clear, ClearType},
+        QueueableCommand,
+    };
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn prints_and_wraps_text() {
+        let mut terminal = VirtualTerminal::new(5, 3);
+        terminal.write_all(b"hello world").unwrap();
+
+        assert_eq!(terminal.screen(), vec!["hello", " worl", "d"]);
+        assert_eq!(terminal.cursor(), (1, 2));
+    }
+
+    #[test]
+    fn wide_graphemes_take_two_cells() {
+        let mut terminal = VirtualTerminal::new(4, 2);
+        terminal.write_all("a😀😀".as_bytes()).unwrap();
+
+        assert_eq!(terminal.screen(), vec!["a😀", "😀"]);
+        assert_eq!(terminal.cursor(), (2, 1));
+    }
+
+    #[test]
+    fn scrolls_at_the_bottom() {
+        let mut terminal = VirtualTerminal::new(10, 2);
+        terminal.write_all(b"one\r\ntwo\r\nthree").unwrap();
+
+        assert_eq!(terminal.screen(), vec!["two", "three"]);
+        assert_eq!(terminal.scrollback(), vec!["one"]);
+    }
+
+    #[test]
+    fn interprets_crossterm_commands() {
+        let mut terminal = VirtualTerminal::new(10, 3);
+        terminal
+            .queue(Print("abcdef\r\nghijkl"))
+            .unwrap()
+            .queue(SetForegroundColor(Color::Red))
+            .unwrap()
+            .queue(MoveTo(2, 0))
+            .unwrap()
+            .queue(SavePosition)
+            .unwrap()
+            .queue(Clear(ClearType::FromCursorDown))
+            .unwrap()
+            .queue(Print("X"))
+            .unwrap()
+            .queue(RestorePosition)
+            .unwrap();
+
+        assert_eq!(terminal.screen(), vec!["abX", "", ""]);
+        assert_eq!(terminal.cursor(), (2, 0));
+    }
+
+    #[test]
+    fn sequences_split_across_writes() {
+        let mut terminal = VirtualTerminal::new(10, 3);
+        terminal.write_all(b"ab\x1b[2").unwrap();
+        terminal.write_all(b";3Hc\xf0\x9f").unwrap();
+        terminal.write_all(b"\x98\x80").unwrap();
+
+        assert_eq!(terminal.screen(), vec!["ab", "  c😀", ""]);
+    }
+
+    #[test]
+    fn read_line_renders_prompt_and_buffer() {
+        let terminal = VirtualTerminal::new(20, 5);
+        terminal.push_str("hello");
+        terminal.push_event(key(KeyCode::Enter));
+
+        let mut line_editor = line_editor(&terminal);
+        let signal = line_editor.read_line(&prompt()).unwrap();
+
+        assert!(matches!(signal, Signal::Success(buffer) if buffer == "hello"));
+        assert_eq!(terminal.screen()[0], "> 〉hello");
+        assert_eq!(terminal.cursor(), (0, 1));
+        assert!(!terminal.is_raw_mode_enabled());
+    }
+
+    #[test]
+    fn terminal_extensions_are_written_to_the_sink() {
+        let terminal = VirtualTerminal::new(20, 5);
+        terminal.push_event(key(KeyCode::Enter));
+
+        let mut line_editor = line_editor(&terminal).use_bracketed_paste(true);
+        line_editor.read_line(&prompt()).unwrap();
+
+        let output = terminal.output();
+        let enabled = output.find("\x1b[?2004h").expect("bracketed paste enabled");
+        let disabled = output
+            .find("\x1b[?2004l")
+            .expect("bracketed paste disabled");
+        assert!(enabled < disabled);
+    }
+
+    #[test]
+    fn mouse_capture_is_written_to_the_sink() {
+        let terminal = VirtualTerminal::new(20, 5);
+        terminal.push_event(key(KeyCode::Enter));
+
+        let mut line_editor = line_editor(&terminal).use_mouse_capture(true);
+        line_editor.read_line(&prompt()).unwrap();
+
+        let output = terminal.output();
+        let enabled = output.find("\x1b[?1000h").expect("mouse capture enabled");
+        let disabled = output.find("\x1b[?1000l").expect("mouse capture disabled");
+        assert!(enabled < disabled);
+    }
+
+    #[cfg(feature = "async")]
+    #[test]
+    fn read_line_async_renders_prompt_and_buffer() {
+        let terminal = VirtualTerminal::new(20, 5);
+        terminal.push_str("hello");
+        terminal.push_event(key(KeyCode::Enter));
+
+        let mut line_editor = line_editor(&terminal);
+        let signal = futures::executor::block_on(line_editor.read_line_async(&prompt())).unwrap();
+
+        assert!(matches!(signal, Signal::Success(buffer) if buffer == "hello"));
+        assert_eq!(terminal.screen()[0], "> 〉hello");
+        assert!(!terminal.is_raw_mode_enabled());
+    }
+
+    #[cfg(feature = "async")]
+    #[test]
+    fn dropping_read_line_async_restores_the_terminal() {
+        use futures::FutureExt;
+
+        let terminal = VirtualTerminal::new(20, 5);
+        terminal.push_str("abc");
+
+        let mut line_editor = line_editor(&terminal);
+        let prompt = prompt();
+        let mut read_line = Box::pin(line_editor.read_line_async(&prompt));
+        // Waiting for more input after the scripted events
+        assert!(read_line.as_mut().now_or_never().is_none());
+        assert!(terminal.is_raw_mode_enabled());
+        drop(read_line);
+
+        assert!(!terminal.is_raw_mode_enabled());
+        assert_eq!(terminal.screen()[0], "> 〉abc");
+        assert_eq!(terminal.cursor(), (0, 1));
+        assert_eq!(line_editor.current_buffer_contents(), "abc");
+    }
+
+    #[cfg(all(feature = "async", feature = "external_printer"))]
+    #[test]
+    fn read_line_async_prints_external_messages_without_input() {
+        use crate::{backend::test_fixtures::eventually, ExternalPrinter};
+
+        let terminal = VirtualTerminal::new(20, 5);
+        let printer = ExternalPrinter::default();
+        let mut line_editor = line_editor(&terminal).with_external_printer(printer.clone());
+
+        let watched = terminal.clone();
+        let watcher = std::thread::spawn(move || {
+            assert!(eventually(|| watched.is_raw_mode_enabled()));
+            printer.print("hello".to_string()).unwrap();
+            let printed = eventually(|| watched.screen()[0] == "hello");
+            watched.push_event(key(KeyCode::Enter));
+            printed
+        });
+        let signal = futures::executor::block_on(line_editor.read_line_async(&prompt())).unwrap();
+
+        assert!(watcher.join().unwrap());
+        assert!(matches!(signal, Signal::Success(buffer) if buffer.is_empty()));
+    }
+
+    #[test]
+    fn read_line_stops_when_the_script_ends() {
+        let terminal = VirtualTerminal::new(20, 5);
+        terminal.push_str("abc");
+        terminal.push_event(key(KeyCode::Left));
+
+        let mut line_editor = line_editor(&terminal);
+        let error = line_editor.read_line(&prompt()).unwrap_err();
+
+        assert_eq!(error.kind(), ErrorKind::UnexpectedEof);
+        assert_eq!(terminal.screen()[0], "> 〉abc");
+        assert_eq!(terminal.cursor(), (6, 0));
+        assert_eq!(line_editor.current_buffer_contents(), "abc");
+    }
+
+    #[test]
+    fn read_line_wraps_long_buffers() {
+        let terminal = VirtualTerminal::new(10, 5);
+        terminal.push_str("0123456789abc");
+
+        let mut line_editor = line_editor(&terminal);
+        let _ = line_editor.read_line(&prompt());
+
+        assert_eq!(terminal.screen()[..3], ["> 〉012345", "6789abc", ""]);
+        assert_eq!(terminal.cursor(), (7, 1));
+    }
+
+    #[test]
+    fn resize_events_resize_the_screen() {
+        let mut terminal = VirtualTerminal::new(10, 3).with_events([Event::Resize(4, 2)]);
+        terminal.write_all(b"abcdefgh").unwrap();
+
+        assert!(matches!(terminal.read(), Ok(Event::Resize(4, 2))));
+        assert_eq!(terminal.size().unwrap(), (4, 2));
+        assert_eq!(terminal.screen(), vec!["abcd", ""]);
+        assert_eq!(terminal.cursor(), (3, 0));
+    }
+
+    #[test]
+    fn read_line_uses_the_screen_size() {
+        let terminal = VirtualTerminal::new(20, 5);
+        terminal.resize(8, 5);
+        terminal.push_str("0123456789x");
+
+        let mut line_editor = line_editor(&terminal);
+        let _ = line_editor.read_line(&prompt());
+
+        assert_eq!(terminal.screen()[..3], ["> 〉0123", "456789x", ""]);
+    }
+
+    #[test]
+    fn script_end_is_reported_once_polling_blocks() {
+        let mut terminal = VirtualTerminal::new(10, 3);
+        terminal.push_str("a");
+
+        assert!(terminal.poll(Duration::from_millis(100)).unwrap());
+        assert!(terminal.read().is_ok());
+        assert!(!terminal.poll(Duration::ZERO).unwrap());
+        assert!(!terminal.poll(Duration::from_millis(100)).unwrap());
+        assert!(terminal.poll(Duration::from_millis(100)).is_err());
+        assert!(terminal.read().is_err());
+    }
+}
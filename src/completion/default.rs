@@ -26,6 +26,7 @@ use std::{
 pub struct DefaultCompleter {
     root: CompletionNode,
     min_word_len: usize,
+    ignore_case: bool,
 }
 
 impl Default for DefaultCompleter {
@@ -34,6 +35,7 @@ impl Default for DefaultCompleter {
         Self {
             root: CompletionNode::new(inclusions),
             min_word_len: 2,
+            ignore_case: false,
         }
     }
 }
@@ -92,19 +94,21 @@ impl Completer for DefaultCompleter {
                     } else {
                         span_line = format!("{s} {span_line}");
                     }
-                    if let Some(mut extensions) = self.root.complete(span_line.chars()) {
-                        extensions.sort();
+                    if let Some(mut words) =
+                        self.root.complete(span_line.chars(), "", self.ignore_case)
+                    {
+                        words.sort();
                         completions.extend(
-                            extensions
-                                .iter()
-                                .map(|ext| {
+                            words
+                                .into_iter()
+                                .map(|value| {
                                     let span = Span::new(
                                         pos - span_line.len() - span_line_whitespaces,
                                         pos,
                                     );
 
                                     Suggestion {
-                                        value: format!("{span_line}{ext}"),
+                                        value,
                                         description: None,
                                         style: None,
                                         extra: None,
@@ -276,6 +280,23 @@ impl DefaultCompleter {
         self.min_word_len = len;
         self
     }
+
+    /// Sets whether the typed text matches the words regardless of their case, like
+    /// `set completion-ignore-case on` in an inputrc file (see [`crate::Inputrc`]). The
+    /// suggestions keep the case of the inserted words.
+    /// # Example
+    /// ```
+    /// use reedline::{DefaultCompleter,Completer};
+    ///
+    /// let mut completions = DefaultCompleter::default().set_ignore_case(true);
+    /// completions.insert(vec!["Batman","robin"].iter().map(|s| s.to_string()).collect());
+    /// assert_eq!(completions.complete("bat",3)[0].value, "Batman");
+    /// ```
+    #[must_use]
+    pub fn set_ignore_case(mut self, ignore_case: bool) -> Self {
+        self.ignore_case = ignore_case;
+        self
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -331,15 +352,27 @@ impl CompletionNode {
         }
     }
 
-    fn complete(&self, mut iter: Chars) -> Option<Vec<String>> {
+    fn complete(&self, mut iter: Chars, partial: &str, ignore_case: bool) -> Option<Vec<String>> {
         if let Some(c) = iter.next() {
-            if let Some(subnode) = self.subnodes.get(&c) {
-                subnode.complete(iter)
+            let subnodes: Vec<_> = if ignore_case {
+                self.subnodes
+                    .iter()
+                    .filter(|(sub_c, _)| sub_c.to_lowercase().eq(c.to_lowercase()))
+                    .collect()
             } else {
-                None
+                self.subnodes.get_key_value(&c).into_iter().collect()
+            };
+            let mut completions: Option<Vec<String>> = None;
+            for (sub_c, subnode) in subnodes {
+                let mut partial = partial.to_string();
+                partial.push(*sub_c);
+                if let Some(mut words) = subnode.complete(iter.clone(), &partial, ignore_case) {
+                    completions.get_or_insert_with(Vec::new).append(&mut words);
+                }
             }
+            completions
         } else {
-            Some(self.collect(""))
+            Some(self.collect(partial))
         }
     }
 
@@ -454,4 +487,29 @@ mod tests {
             [&buffer[ranges[0].clone()], &buffer[ranges[1].clone()]]
         );
     }
+
+    #[test]
+    fn default_completer_ignoring_case() {
+        let words = ["Batman", "batcave", "BATMOBILE", "robin"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect::<Vec<_>>();
+        let mut completions = DefaultCompleter::default();
+        completions.insert(words.clone());
+        let mut ignoring_case = DefaultCompleter::default().set_ignore_case(true);
+        ignoring_case.insert(words);
+
+        let values = |suggestions: Vec<Suggestion>| {
+            suggestions
+                .into_iter()
+                .map(|suggestion| suggestion.value)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(values(completions.complete("bat", 3)), ["batcave"]);
+        assert_eq!(
+            values(ignoring_case.complete("bat", 3)),
+            ["BATMOBILE", "Batman", "batcave"]
+        );
+        assert_eq!(values(ignoring_case.complete("ROB", 3)), ["robin"]);
+    }
 }
@@ -0,0 +1,235 @@
+use super::LineBuffer;
+
+/// Brackets and quotes closed automatically while typing in the [`super::Editor`]
+///
+/// Typing an opener inserts its closer after the cursor, typing the closer steps over it and
+/// backspace between an empty pair deletes both. Typing an opener of a single character while
+/// text is selected puts the pair around the selection.
+///
+/// The default pairs are `()`, `[]`, `{}`, `""`, `''` and ` `` `. Openers and closers may be
+/// longer than one character:
+///
+/// ```rust
+/// use reedline::AutoPairs;
+///
+/// let pairs = AutoPairs::default().with_pair("<<", ">>");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AutoPairs {
+    pairs: Vec<(String, String)>,
+}
+
+impl Default for AutoPairs {
+    fn default() -> Self {
+        AutoPairs::new()
+            .with_pair("(", ")")
+            .with_pair("[", "]")
+            .with_pair("{", "}")
+            .with_pair("\"", "\"")
+            .with_pair("'", "'")
+            .with_pair("`", "`")
+    }
+}
+
+impl AutoPairs {
+    /// A pair table without any pairs
+    pub fn new() -> Self {
+        AutoPairs { pairs: Vec::new() }
+    }
+
+    /// A builder that adds the pair of `open` and `close` to the table
+    ///
+    /// Empty openers or closers are ignored.
+    #[must_use]
+    pub fn with_pair(mut self, open: &str, close: &str) -> Self {
+        if !open.is_empty() && !close.is_empty() {
+            self.pairs.push((open.to_string(), close.to_string()));
+        }
+        self
+    }
+
+    fn is_closing_char(&self, c: char) -> bool {
+        self.pairs.iter().any(|(_, close)| close.contains(c))
+    }
+
+    /// Types `c` into `line_buffer`
+    ///
+    /// Returns `false`, without touching the buffer, when `c` doesn't open, close or wrap a pair
+    /// and has to be inserted as usual.
+    pub(crate) fn insert_char(
+        &self,
+        line_buffer: &mut LineBuffer,
+        selection: Option<(usize, usize)>,
+        c: char,
+    ) -> bool {
+        if let Some((start, end)) = selection {
+            return self.wrap_selection(line_buffer, start, end, c);
+        }
+
+        let insertion_point = line_buffer.insertion_point();
+        let (before, after) = line_buffer.get_buffer().split_at(insertion_point);
+
+        // Step over the rest of a closer, also when the cursor sits in the middle of it
+        let step_over = self.pairs.iter().any(|(_, close)| {
+            close.char_indices().any(|(index, _)| {
+                before.ends_with(&close[..index])
+                    && after.starts_with(&close[index..])
+                    && close[index..].starts_with(c)
+            })
+        });
+        if step_over {
+            line_buffer.set_insertion_point(insertion_point + c.len_utf8());
+            return true;
+        }
+
+        let closes_here = after.chars().next().map_or(true, |next| {
+            next.is_whitespace() || self.is_closing_char(next)
+        });
+        let typed = format!("{before}{c}");
+        let close = self
+            .pairs
+            .iter()
+            .filter(|(open, _)| typed.ends_with(open.as_str()))
+            .filter(|(open, close)| {
+                // Quotes right after a word are apostrophes or closing quotes
+                open != close
+                    || !typed[..typed.len() - open.len()]
+                        .chars()
+                        .next_back()
+                        .map_or(false, char::is_alphanumeric)
+            })
+            .max_by_key(|(open, _)| open.len())
+            .map(|(_, close)| close.clone());
+        match close {
+            Some(close) if closes_here => {
+                line_buffer.insert_char(c);
+                line_buffer.insert_str(&close);
+                line_buffer.set_insertion_point(insertion_point + c.len_utf8());
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn wrap_selection(
+        &self,
+        line_buffer: &mut LineBuffer,
+        start: usize,
+        end: usize,
+        c: char,
+    ) -> bool {
+        let pair = self
+            .pairs
+            .iter()
+            .find(|(open, _)| open.chars().eq(std::iter::once(c)));
+        match pair {
+            Some((open, close)) => {
+                line_buffer.replace_range(end..end, close);
+                line_buffer.replace_range(start..start, open);
+                line_buffer.set_insertion_point(end + open.len() + close.len());
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Deletes the pair the cursor sits in the middle of, when nothing is between its opener
+    /// and closer
+    ///
+    /// Returns `false`, without touching the buffer, when there is no such pair.
+    pub(crate) fn backspace(&self, line_buffer: &mut LineBuffer) -> bool {
+        let insertion_point = line_buffer.insertion_point();
+        let (before, after) = line_buffer.get_buffer().split_at(insertion_point);
+        let pair = self
+            .pairs
+            .iter()
+            .filter(|(open, close)| {
+                before.ends_with(open.as_str()) && after.starts_with(close.as_str())
+            })
+            .max_by_key(|(open, _)| open.len());
+        match pair {
+            Some((open, close)) => {
+                let start = insertion_point - open.len();
+                line_buffer.clear_range_safe(start, insertion_point + close.len());
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+
+    // The cursor is marked with `|`
+    fn line_buffer(text: &str) -> LineBuffer {
+        let mut line_buffer = LineBuffer::from(text.replace('|', "").as_str());
+        line_buffer.set_insertion_point(text.find('|').unwrap_or(text.len()));
+        line_buffer
+    }
+
+    fn with_cursor(line_buffer: &LineBuffer) -> String {
+        let mut text = line_buffer.get_buffer().to_string();
+        text.insert(line_buffer.insertion_point(), '|');
+        text
+    }
+
+    fn pairs() -> AutoPairs {
+        AutoPairs::default().with_pair("<<", ">>")
+    }
+
+    #[rstest]
+    #[case("ls |", '(', "ls (|)")]
+    #[case("f(|)", ')', "f()|")]
+    #[case("[a, b|]", ']', "[a, b]|")]
+    #[case("echo |", '"', "echo \"|\"")]
+    #[case("echo \"hi|\"", '"', "echo \"hi\"|")]
+    #[case("<|", '<', "<<|>>")]
+    #[case("<<a|>>", '>', "<<a>|>")]
+    #[case("<<a>|>", '>', "<<a>>|")]
+    #[case("{(|)}", '[', "{([|])}")]
+    fn pairs_are_opened_and_closed(#[case] text: &str, #[case] c: char, #[case] expected: &str) {
+        let mut line_buffer = line_buffer(text);
+
+        assert!(pairs().insert_char(&mut line_buffer, None, c));
+        assert_eq!(with_cursor(&line_buffer), expected);
+    }
+
+    #[rstest]
+    #[case("don|", '\'')]
+    #[case("|word", '(')]
+    #[case("a|", ')')]
+    #[case("a|>", '>')]
+    fn plain_characters_are_left_to_the_editor(#[case] text: &str, #[case] c: char) {
+        let mut line_buffer = line_buffer(text);
+
+        assert!(!pairs().insert_char(&mut line_buffer, None, c));
+        assert_eq!(with_cursor(&line_buffer), text);
+    }
+
+    #[rstest]
+    #[case("f(|)", "f|")]
+    #[case("<<|>>", "|")]
+    #[case("f(a|)", "f(|)")]
+    fn backspace_deletes_empty_pairs(#[case] text: &str, #[case] expected: &str) {
+        let mut line_buffer = line_buffer(text);
+
+        if !pairs().backspace(&mut line_buffer) {
+            line_buffer.delete_left_grapheme();
+        }
+
+        assert_eq!(with_cursor(&line_buffer), expected);
+    }
+
+    #[test]
+    fn selections_are_wrapped() {
+        let mut line_buffer = line_buffer("echo hello world|");
+
+        assert!(pairs().insert_char(&mut line_buffer, Some((5, 10)), '"'));
+        assert_eq!(with_cursor(&line_buffer), "echo \"hello\"| world");
+        assert!(!pairs().insert_char(&mut line_buffer, Some((0, 4)), 'x'));
+    }
+}
@@ -18,7 +18,7 @@ pub trait Clipboard: Send {
 }
 
 /// Determines how the content in the clipboard should be inserted
-#[derive(Copy, Clone, Debug, Default)]
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
 pub enum ClipboardMode {
     /// As direct content at the current cursor position
     #[default]
@@ -113,6 +113,343 @@ mod system_clipboard {
     }
 }
 
+#[cfg(feature = "system_clipboard")]
+pub use osc52_clipboard::{Osc52Clipboard, Osc52Passthrough};
+#[cfg(feature = "system_clipboard")]
+pub(crate) use osc52_clipboard::{Osc52Terminal, PASTE_REQUEST};
+
+#[cfg(feature = "system_clipboard")]
+mod osc52_clipboard {
+    use super::*;
+    use std::{
+        sync::{Arc, Mutex, MutexGuard},
+        time::Duration,
+    };
+
+    const BASE64_ALPHABET: &[u8; 64] =
+        b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+    /// Asks for the clipboard, followed by the device attributes request every terminal
+    /// answers. As the answers come in order, its answer marks the end of the clipboard
+    /// answer, if there is one.
+    pub(crate) const PASTE_REQUEST: &[u8] = b"\x1b]52;c;?\x1b\\\x1b[c";
+
+    /// Number of payload bytes per chunk passed through screen, which drops longer sequences
+    const SCREEN_CHUNK_SIZE: usize = 76;
+
+    /// Terminal multiplexer the copies of an [`Osc52Clipboard`] are passed through
+    #[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+    pub enum Osc52Passthrough {
+        /// Straight to the terminal, also for tmux with `set-clipboard on`
+        #[default]
+        None,
+        /// Wrapped for tmux with `allow-passthrough on`
+        Tmux,
+        /// Split into chunks wrapped for GNU screen
+        Screen,
+    }
+
+    /// Clipboard of the terminal, reached through the OSC 52 escape sequence
+    ///
+    /// Copies are sent to the terminal emulator, which puts them into the clipboard of the
+    /// machine it runs on. This works over SSH and inside tmux (with `set-clipboard on`) where
+    /// the default system clipboard can't reach the user's clipboard. Other multiplexers need
+    /// the copies wrapped with [`Osc52Clipboard::with_passthrough()`], e.g. split into chunks
+    /// short enough for GNU screen. Reading the clipboard back is
+    /// disabled by default, as most terminals refuse or ignore the request; without it pastes
+    /// use the last copy made in the line editor.
+    ///
+    /// Select it for [`crate::EditCommand::CopySelectionSystem`],
+    /// [`crate::EditCommand::CutSelectionSystem`] and [`crate::EditCommand::PasteSystem`] with
+    /// [`crate::Reedline::with_osc52_clipboard()`]. The line editor writes the escape sequences
+    /// to its [`crate::OutputSink`] and reads the answers of the terminal with
+    /// [`crate::InputSource::read_terminal_reply()`].
+    ///
+    /// Requires that the feature `system_clipboard` is enabled
+    pub struct Osc52Clipboard {
+        terminal: Osc52Terminal,
+        passthrough: Osc52Passthrough,
+        local_copy: String,
+        mode: ClipboardMode,
+    }
+
+    impl Default for Osc52Clipboard {
+        fn default() -> Self {
+            Osc52Clipboard {
+                terminal: Osc52Terminal {
+                    exchange: Arc::default(),
+                    paste_timeout: None,
+                },
+                passthrough: Osc52Passthrough::None,
+                local_copy: String::new(),
+                mode: ClipboardMode::Normal,
+            }
+        }
+    }
+
+    impl Osc52Clipboard {
+        /// Clipboard writing copies to the terminal without reading pastes from it
+        pub fn new() -> Self {
+            Self::default()
+        }
+
+        /// Read pastes from the terminal clipboard
+        ///
+        /// The terminal has to answer the request within `timeout`, otherwise the last copy
+        /// made in the line editor is pasted. A late answer is still read and dropped, so it
+        /// doesn't reach the line editor as typed keys.
+        #[must_use]
+        pub fn with_paste_from_terminal(mut self, enable: bool, timeout: Duration) -> Self {
+            self.terminal.paste_timeout = enable.then_some(timeout);
+            self
+        }
+
+        /// Pass the copies through a terminal multiplexer to the terminal around it
+        ///
+        /// Pastes are still requested from the terminal the line editor runs in.
+        #[must_use]
+        pub fn with_passthrough(mut self, passthrough: Osc52Passthrough) -> Self {
+            self.passthrough = passthrough;
+            self
+        }
+
+        /// The handle the line editor exchanges the escape sequences of the clipboard with
+        pub(crate) fn terminal(&self) -> Osc52Terminal {
+            self.terminal.clone()
+        }
+    }
+
+    impl Clipboard for Osc52Clipboard {
+        fn set(&mut self, content: &str, mode: ClipboardMode) {
+            content.clone_into(&mut self.local_copy);
+            let sequence = copy_sequence(&encode_base64(content.as_bytes()), self.passthrough);
+            self.terminal
+                .exchange()
+                .output
+                .extend_from_slice(sequence.as_bytes());
+            self.mode = mode;
+        }
+
+        fn get(&mut self) -> (String, ClipboardMode) {
+            match self.terminal.exchange().pasted.take() {
+                Some(content) if content != self.local_copy => (content, ClipboardMode::Normal),
+                // Either unchanged since the last yank or unknown
+                _ => (self.local_copy.clone(), self.mode),
+            }
+        }
+    }
+
+    /// What an [`Osc52Clipboard`] exchanges with the terminal
+    #[derive(Default)]
+    struct Exchange {
+        // Escape sequences of the copies not written yet
+        output: Vec<u8>,
+        // Clipboard content the terminal answered before the next paste
+        pasted: Option<String>,
+    }
+
+    /// Handle of the line editor to pass the escape sequences of an [`Osc52Clipboard`]
+    /// through its terminal backends
+    #[derive(Clone)]
+    pub(crate) struct Osc52Terminal {
+        exchange: Arc<Mutex<Exchange>>,
+        paste_timeout: Option<Duration>,
+    }
+
+    impl Osc52Terminal {
+        fn exchange(&self) -> MutexGuard<'_, Exchange> {
+            self.exchange
+                .lock()
+                .unwrap_or_else(std::sync::PoisonError::into_inner)
+        }
+
+        /// Takes the escape sequences of the copies made since the last call
+        pub(crate) fn take_output(&self) -> Vec<u8> {
+            std::mem::take(&mut self.exchange().output)
+        }
+
+        /// How long to wait for the answer to [`PASTE_REQUEST`] before a paste, `None` if
+        /// pastes don't read the terminal clipboard
+        pub(crate) fn paste_timeout(&self) -> Option<Duration> {
+            self.paste_timeout
+        }
+
+        /// Passes the answer of the terminal to [`PASTE_REQUEST`] to the next paste
+        pub(crate) fn set_reply(&self, reply: Option<&[u8]>) {
+            self.exchange().pasted = reply.and_then(parse_reply);
+        }
+    }
+
+    /// The escape sequence copying the base64 encoded `payload`, wrapped for `passthrough`
+    fn copy_sequence(payload: &str, passthrough: Osc52Passthrough) -> String {
+        match passthrough {
+            Osc52Passthrough::None => format!("\x1b]52;c;{payload}\x1b\\"),
+            // tmux passes on the contents of its DCS sequence with the doubled escape
+            // characters undoubled, the BEL ends the OSC sequence without ending the DCS one
+            Osc52Passthrough::Tmux => format!("\x1bPtmux;\x1b\x1b]52;c;{payload}\x07\x1b\\"),
+            // screen passes on every DCS sequence, the terminal joins the chunks
+            Osc52Passthrough::Screen => {
+                let mut sequence = String::from("\x1bP\x1b]52;c;");
+                for (i, chunk) in payload.as_bytes().chunks(SCREEN_CHUNK_SIZE).enumerate() {
+                    if i > 0 {
+                        sequence.push_str("\x1b\\\x1bP");
+                    }
+                    // The base64 alphabet is ASCII
+                    sequence.extend(chunk.iter().map(|byte| *byte as char));
+                }
+                sequence.push_str("\x07\x1b\\");
+                sequence
+            }
+        }
+    }
+
+    /// Extracts the clipboard content from `ESC ] 52 ; <selection> ; <base64> ST`
+    fn parse_reply(reply: &[u8]) -> Option<String> {
+        let reply = std::str::from_utf8(reply).ok()?;
+        let start = reply.find("\x1b]52;")? + 5;
+        let end = reply[start..].find(['\x07', '\x1b'])? + start;
+        let body = &reply[start..end];
+        let (_selection, payload) = body.split_once(';')?;
+        if payload.is_empty() || payload == "?" {
+            return None;
+        }
+        String::from_utf8(decode_base64(payload)?).ok()
+    }
+
+    fn encode_base64(bytes: &[u8]) -> String {
+        let mut encoded = String::with_capacity((bytes.len() + 2) / 3 * 4);
+        for chunk in bytes.chunks(3) {
+            let group = chunk.iter().enumerate().fold(0u32, |group, (i, byte)| {
+                group | (*byte as u32) << (16 - 8 * i)
+            });
+            for i in 0..4 {
+                if i <= chunk.len() {
+                    let index = (group >> (18 - 6 * i)) & 0x3f;
+                    encoded.push(BASE64_ALPHABET[index as usize] as char);
+                } else {
+                    encoded.push('=');
+                }
+            }
+        }
+        encoded
+    }
+
+    fn decode_base64(encoded: &str) -> Option<Vec<u8>> {
+        let encoded = encoded.trim_end_matches('=').as_bytes();
+        let mut decoded = Vec::with_capacity(encoded.len() * 3 / 4);
+        for chunk in encoded.chunks(4) {
+            if chunk.len() == 1 {
+                return None;
+            }
+            let mut group = 0u32;
+            for (i, symbol) in chunk.iter().enumerate() {
+                let value = BASE64_ALPHABET.iter().position(|c| c == symbol)? as u32;
+                group |= value << (18 - 6 * i);
+            }
+            for i in 0..chunk.len() - 1 {
+                decoded.push((group >> (16 - 8 * i)) as u8);
+            }
+        }
+        Some(decoded)
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use pretty_assertions::assert_eq;
+        use rstest::rstest;
+
+        #[rstest]
+        #[case("", "")]
+        #[case("f", "Zg==")]
+        #[case("fo", "Zm8=")]
+        #[case("foo", "Zm9v")]
+        #[case("foobar", "Zm9vYmFy")]
+        #[case("ünï", "w7xuw68=")]
+        fn base64_round_trip(#[case] text: &str, #[case] encoded: &str) {
+            assert_eq!(encode_base64(text.as_bytes()), encoded);
+            assert_eq!(decode_base64(encoded), Some(text.as_bytes().to_vec()));
+        }
+
+        #[test]
+        fn copies_are_handed_to_the_terminal_once() {
+            let mut clipboard = Osc52Clipboard::new();
+            let terminal = clipboard.terminal();
+
+            clipboard.set("foobar", ClipboardMode::Normal);
+
+            assert_eq!(terminal.take_output(), b"\x1b]52;c;Zm9vYmFy\x1b\\");
+            assert!(terminal.take_output().is_empty());
+        }
+
+        #[rstest]
+        #[case(
+            Osc52Passthrough::Tmux,
+            "foo",
+            "\x1bPtmux;\x1b\x1b]52;c;Zm9v\x07\x1b\\"
+        )]
+        #[case(Osc52Passthrough::Screen, "foo", "\x1bP\x1b]52;c;Zm9v\x07\x1b\\")]
+        #[case(
+            Osc52Passthrough::Screen,
+            &"abc".repeat(20),
+            &format!("\x1bP\x1b]52;c;{}\x1b\\\x1bP{}\x07\x1b\\", "YWJj".repeat(19), "YWJj")
+        )]
+        fn copies_are_passed_through_multiplexers(
+            #[case] passthrough: Osc52Passthrough,
+            #[case] content: &str,
+            #[case] expected: &str,
+        ) {
+            let mut clipboard = Osc52Clipboard::new().with_passthrough(passthrough);
+
+            clipboard.set(content, ClipboardMode::Normal);
+
+            assert_eq!(
+                String::from_utf8(clipboard.terminal().take_output()).unwrap(),
+                expected
+            );
+        }
+
+        #[test]
+        fn pastes_the_last_copy_without_a_terminal_reply() {
+            let mut clipboard = Osc52Clipboard::new();
+            clipboard.set("line", ClipboardMode::Lines);
+            clipboard.terminal().set_reply(None);
+
+            assert!(
+                matches!(clipboard.get(), (content, ClipboardMode::Lines) if content == "line")
+            );
+        }
+
+        #[test]
+        fn pastes_the_terminal_reply_once() {
+            let mut clipboard =
+                Osc52Clipboard::new().with_paste_from_terminal(true, Duration::from_millis(10));
+            clipboard.set("line", ClipboardMode::Lines);
+            clipboard
+                .terminal()
+                .set_reply(Some(b"\x1b]52;c;Zm9vYmFy\x07\x1b[?62;22c"));
+
+            assert!(
+                matches!(clipboard.get(), (content, ClipboardMode::Normal) if content == "foobar")
+            );
+            assert!(
+                matches!(clipboard.get(), (content, ClipboardMode::Lines) if content == "line")
+            );
+        }
+
+        #[rstest]
+        #[case(b"\x1b]52;c;Zm9vYmFy\x07\x1b[?62;22c", Some("foobar"))]
+        #[case(b"\x1b]52;c;Zm9vYmFy\x1b\\\x1b[?62;22c", Some("foobar"))]
+        #[case(b"\x1b]52;p;Zm8=\x1b\\\x1b[?1;2c", Some("fo"))]
+        #[case(b"\x1b]52;c;\x07\x1b[?62c", None)]
+        #[case(b"\x1b[?62;22c", None)]
+        #[case(b"\x1b]11;rgb:0000/0000/0000\x07\x1b[?62c", None)]
+        fn terminal_replies_are_parsed(#[case] reply: &[u8], #[case] expected: Option<&str>) {
+            assert_eq!(parse_reply(reply).as_deref(), expected);
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     #[cfg(feature = "system_clipboard")]
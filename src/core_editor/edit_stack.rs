@@ -1,4 +1,6 @@
-#[derive(Debug, PartialEq, Eq)]
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
 pub struct EditStack<T> {
     internal_list: Vec<T>,
     index: usize,
@@ -59,6 +61,17 @@ where
     pub(super) fn current(&mut self) -> &T {
         &self.internal_list[self.index]
     }
+
+    /// Repair a stack read from a snapshot so the index points to an entry
+    ///
+    /// `repair_entry` gets to fix every entry of the stack.
+    pub(super) fn repair(&mut self, repair_entry: impl FnMut(&mut T)) {
+        if self.internal_list.is_empty() {
+            self.reset();
+        }
+        self.index = self.index.min(self.internal_list.len() - 1);
+        self.internal_list.iter_mut().for_each(repair_entry);
+    }
 }
 
 #[cfg(test)]
@@ -110,4 +123,14 @@ mod test {
         stack.insert(value_to_insert);
         assert_eq!(stack, expected_stack);
     }
+
+    #[rstest]
+    #[case(edit_stack(&[1, 2, 3][..], 5), edit_stack(&[2, 3, 4], 2))]
+    #[case(edit_stack::<isize>(&[], 0), edit_stack(&[1], 0))]
+    fn repair_works(#[case] old_stack: EditStack<isize>, #[case] expected_stack: EditStack<isize>) {
+        let mut stack = old_stack;
+
+        stack.repair(|value| *value += 1);
+        assert_eq!(stack, expected_stack);
+    }
 }
@@ -1,44 +1,142 @@
-use super::{edit_stack::EditStack, Clipboard, ClipboardMode, LineBuffer};
-#[cfg(feature = "system_clipboard")]
-use crate::core_editor::get_system_clipboard;
+use super::{
+    edit_stack::EditStack, registers::Registers, text_object::text_object_range, AutoPairs,
+    Clipboard, ClipboardMode, LineBuffer,
+};
 use crate::enums::{EditType, UndoBehavior};
-use crate::{core_editor::get_local_clipboard, EditCommand};
+use crate::{EditCommand, Indenter, TextObject};
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "system_clipboard")]
 use std::ops::DerefMut;
 
+/// Part of the [`Editor`] kept in a [`crate::SessionSnapshot`]
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+pub(crate) struct EditorState {
+    line_buffer: LineBuffer,
+    edit_stack: EditStack<LineBuffer>,
+    selection_anchor: Option<usize>,
+    #[serde(default)]
+    line_selection: bool,
+}
+
+// Moves the insertion point of a buffer read from a snapshot back onto a grapheme
+fn repair_line_buffer(line_buffer: &mut LineBuffer) {
+    if !line_buffer.is_valid() {
+        line_buffer.move_to_end();
+    }
+}
+
+// Indentation added by `EditCommand::IndentLines` without an `Indenter`
+const DEFAULT_INDENT_UNIT: &str = "    ";
+
+fn is_indentation(c: char) -> bool {
+    c == ' ' || c == '\t'
+}
+
+// Replaces the indentation of the line starting at `line_start`, the insertion point stays on
+// the same character
+fn set_indentation(line_buffer: &mut LineBuffer, line_start: usize, indentation: &str) {
+    let line = &line_buffer.get_buffer()[line_start..];
+    let current = line.len() - line.trim_start_matches(is_indentation).len();
+    line_buffer.replace_range(line_start..line_start + current, indentation);
+    let insertion_point = line_buffer.insertion_point();
+    if insertion_point >= line_start + current {
+        line_buffer.set_insertion_point(insertion_point - current + indentation.len());
+    } else if insertion_point > line_start {
+        line_buffer.set_insertion_point(line_start + indentation.len());
+    }
+}
+
+// Start of the line at `start` and end of the line at `end`, before its line ending
+fn line_bounds(buffer: &str, start: usize, end: usize) -> (usize, usize) {
+    let line_start = buffer[..start].rfind('\n').map_or(0, |index| index + 1);
+    let line_end = buffer[end..]
+        .find('\n')
+        .map_or(buffer.len(), |index| end + index);
+    let line_end = if buffer[..line_end].ends_with('\r') {
+        line_end - 1
+    } else {
+        line_end
+    };
+    (line_start, line_end.max(line_start))
+}
+
+// Position of the character at `position` after `inserted` bytes replaced the `replaced` range,
+// positions inside the range move to its start
+fn shift_position(position: usize, replaced: &std::ops::Range<usize>, inserted: usize) -> usize {
+    if position >= replaced.end {
+        position - replaced.len() + inserted
+    } else {
+        position.min(replaced.start)
+    }
+}
+
 /// Stateful editor executing changes to the underlying [`LineBuffer`]
 ///
 /// In comparison to the state-less [`LineBuffer`] the [`Editor`] keeps track of
 /// the undo/redo history and has facilities for cut/copy/yank/paste
 pub struct Editor {
     line_buffer: LineBuffer,
-    cut_buffer: Box<dyn Clipboard>,
-    #[cfg(feature = "system_clipboard")]
-    system_clipboard: Box<dyn Clipboard>,
+    cut_buffer: Registers,
     edit_stack: EditStack<LineBuffer>,
     last_undo_behavior: UndoBehavior,
     selection_anchor: Option<usize>,
+    // Whether the selection covers whole lines, like the vi visual-line mode
+    line_selection: bool,
+    auto_pairs: Option<AutoPairs>,
+    indenter: Option<Box<dyn Indenter>>,
 }
 
 impl Default for Editor {
     fn default() -> Self {
         Editor {
             line_buffer: LineBuffer::new(),
-            cut_buffer: get_local_clipboard(),
-            #[cfg(feature = "system_clipboard")]
-            system_clipboard: get_system_clipboard(),
+            cut_buffer: Registers::new(),
             edit_stack: EditStack::new(),
             last_undo_behavior: UndoBehavior::CreateUndoPoint,
             selection_anchor: None,
+            line_selection: false,
+            auto_pairs: None,
+            indenter: None,
         }
     }
 }
 
 impl Editor {
+    /// Editor whose clipboards are its own, nothing cut or copied leaves it
+    pub(crate) fn with_local_clipboards() -> Self {
+        Editor {
+            line_buffer: LineBuffer::new(),
+            cut_buffer: Registers::local(),
+            edit_stack: EditStack::new(),
+            last_undo_behavior: UndoBehavior::CreateUndoPoint,
+            selection_anchor: None,
+            line_selection: false,
+            auto_pairs: None,
+            indenter: None,
+        }
+    }
+
     /// Get the current [`LineBuffer`]
     pub const fn line_buffer(&self) -> &LineBuffer {
         &self.line_buffer
     }
 
+    /// Replace the clipboard used by the commands working on the system clipboard
+    #[cfg(feature = "system_clipboard")]
+    pub(crate) fn set_system_clipboard(&mut self, clipboard: Box<dyn Clipboard>) {
+        self.cut_buffer.system = clipboard;
+    }
+
+    /// Close the brackets and quotes of `auto_pairs` while typing, `None` turns it off
+    pub(crate) fn set_auto_pairs(&mut self, auto_pairs: Option<AutoPairs>) {
+        self.auto_pairs = auto_pairs;
+    }
+
+    /// Indent new lines with `indenter`, `None` leaves them at column 0
+    pub(crate) fn set_indenter(&mut self, indenter: Option<Box<dyn Indenter>>) {
+        self.indenter = indenter;
+    }
+
     /// Set the current [`LineBuffer`].
     /// [`UndoBehavior`] specifies how this change should be reflected on the undo stack.
     pub(crate) fn set_line_buffer(&mut self, line_buffer: LineBuffer, undo_behavior: UndoBehavior) {
@@ -47,6 +145,9 @@ impl Editor {
     }
 
     pub(crate) fn run_edit_command(&mut self, command: &EditCommand) {
+        // Only copies leave the text alone, their contents go to the register `0`
+        self.cut_buffer
+            .set_copying(command.edit_type() == EditType::NoOp);
         match command {
             EditCommand::MoveToStart { select } => self.move_to_start(*select),
             EditCommand::MoveToLineStart { select } => self.move_to_line_start(*select),
@@ -55,6 +156,14 @@ impl Editor {
             EditCommand::MoveToPosition { position, select } => {
                 self.move_to_position(*position, *select)
             }
+            EditCommand::MoveLineUp { select } => {
+                self.update_selection_anchor(*select);
+                self.line_buffer.move_line_up();
+            }
+            EditCommand::MoveLineDown { select } => {
+                self.update_selection_anchor(*select);
+                self.line_buffer.move_line_down();
+            }
             EditCommand::MoveLeft { select } => self.move_left(*select),
             EditCommand::MoveRight { select } => self.move_right(*select),
             EditCommand::MoveWordLeft { select } => self.move_word_left(*select),
@@ -70,6 +179,9 @@ impl Editor {
             EditCommand::Complete => {}
             EditCommand::InsertString(str) => self.insert_str(str),
             EditCommand::InsertNewline => self.insert_newline(),
+            EditCommand::IndentLines => self.indent_lines(false),
+            EditCommand::DedentLines => self.indent_lines(true),
+            EditCommand::JoinLines => self.join_lines(),
             EditCommand::ReplaceChar(chr) => self.replace_char(*chr),
             EditCommand::ReplaceChars(n_chars, str) => self.replace_chars(*n_chars, str),
             EditCommand::Backspace => self.backspace(),
@@ -117,8 +229,39 @@ impl Editor {
                 self.move_left_until_char(*c, true, true, *select)
             }
             EditCommand::SelectAll => self.select_all(),
+            EditCommand::SelectLines => self.select_lines(),
+            EditCommand::CutTextObject { text_object } => self.cut_text_object(*text_object),
+            EditCommand::SelectTextObject { text_object } => self.select_text_object(*text_object),
             EditCommand::CutSelection => self.cut_selection_to_cut_buffer(),
+            EditCommand::ChangeSelection => self.change_selection(),
             EditCommand::CopySelection => self.copy_selection_to_cut_buffer(),
+            EditCommand::CopyCurrentLine => self.copy_current_line(),
+            EditCommand::CopyFromLineStart => self.copy_with(Self::cut_from_line_start),
+            EditCommand::CopyToLineEnd => self.copy_with(Self::cut_to_line_end),
+            EditCommand::CopyWordLeft => self.copy_with(Self::cut_word_left),
+            EditCommand::CopyBigWordLeft => self.copy_with(Self::cut_big_word_left),
+            EditCommand::CopyWordRight => self.copy_with(Self::cut_word_right),
+            EditCommand::CopyBigWordRight => self.copy_with(Self::cut_big_word_right),
+            EditCommand::CopyWordRightToNext => self.copy_with(Self::cut_word_right_to_next),
+            EditCommand::CopyBigWordRightToNext => self.copy_with(Self::cut_big_word_right_to_next),
+            EditCommand::CopyLeft => self.copy_left(),
+            EditCommand::CopyRight => self.copy_with(Self::cut_char),
+            EditCommand::CopyRightUntil(c) => {
+                self.copy_with(|editor| editor.cut_right_until_char(*c, false, true))
+            }
+            EditCommand::CopyRightBefore(c) => {
+                self.copy_with(|editor| editor.cut_right_until_char(*c, true, true))
+            }
+            EditCommand::CopyLeftUntil(c) => {
+                self.copy_with(|editor| editor.cut_left_until_char(*c, false, true))
+            }
+            EditCommand::CopyLeftBefore(c) => {
+                self.copy_with(|editor| editor.cut_left_until_char(*c, true, true))
+            }
+            EditCommand::CopyTextObject { text_object } => {
+                self.copy_with(|editor| editor.cut_text_object(*text_object))
+            }
+            EditCommand::UseRegister(register) => self.cut_buffer.select(*register),
             EditCommand::Paste => self.paste_cut_buffer(),
             #[cfg(feature = "system_clipboard")]
             EditCommand::CutSelectionSystem => self.cut_selection_to_system(),
@@ -127,13 +270,27 @@ impl Editor {
             #[cfg(feature = "system_clipboard")]
             EditCommand::PasteSystem => self.paste_from_system(),
         }
-        if !matches!(command.edit_type(), EditType::MoveCursor { select: true }) {
+        // Lines stay selected to be indented further or to be cut into a register
+        if !matches!(command.edit_type(), EditType::MoveCursor { select: true })
+            && !matches!(
+                command,
+                EditCommand::IndentLines | EditCommand::DedentLines | EditCommand::UseRegister(_)
+            )
+        {
             self.selection_anchor = None;
         }
+        if self.selection_anchor.is_none() {
+            self.line_selection = false;
+        }
         if let EditType::MoveCursor { select: true } = command.edit_type() {}
+        // The register is picked for the command after `UseRegister` only
+        if !matches!(command, EditCommand::UseRegister(_)) {
+            self.cut_buffer.reset();
+        }
 
         let new_undo_behavior = match (command, command.edit_type()) {
-            (_, EditType::MoveCursor { .. }) => UndoBehavior::MoveCursor,
+            // Copies move the cursor at most
+            (_, EditType::MoveCursor { .. } | EditType::NoOp) => UndoBehavior::MoveCursor,
             (EditCommand::InsertChar(c), EditType::EditText) => UndoBehavior::InsertCharacter(*c),
             (EditCommand::Delete, EditType::EditText) => {
                 let deleted_char = self.edit_stack.current().grapheme_right().chars().next();
@@ -150,12 +307,13 @@ impl Editor {
         self.update_undo_state(new_undo_behavior);
     }
     fn update_selection_anchor(&mut self, select: bool) {
-        self.selection_anchor = if select {
-            self.selection_anchor
-                .or_else(|| Some(self.insertion_point()))
-        } else {
-            None
-        };
+        if !select {
+            self.selection_anchor = None;
+        } else if self.selection_anchor.is_none() {
+            // New selections are character-wise until `EditCommand::SelectLines`
+            self.selection_anchor = Some(self.insertion_point());
+            self.line_selection = false;
+        }
     }
     fn move_to_position(&mut self, position: usize, select: bool) {
         self.update_selection_anchor(select);
@@ -217,6 +375,31 @@ impl Editor {
         self.edit_stack.reset();
     }
 
+    /// Copy of the buffer, the undo history and the selection
+    pub(crate) fn state(&self) -> EditorState {
+        EditorState {
+            line_buffer: self.line_buffer.clone(),
+            edit_stack: self.edit_stack.clone(),
+            selection_anchor: self.selection_anchor,
+            line_selection: self.line_selection,
+        }
+    }
+
+    /// Restore the buffer, the undo history and the selection from [`Editor::state()`]
+    ///
+    /// Positions that don't fit the restored buffers are moved to their end or dropped.
+    pub(crate) fn restore_state(&mut self, state: EditorState) {
+        self.line_buffer = state.line_buffer;
+        repair_line_buffer(&mut self.line_buffer);
+        self.edit_stack = state.edit_stack;
+        self.edit_stack.repair(repair_line_buffer);
+        self.selection_anchor = state
+            .selection_anchor
+            .filter(|anchor| self.line_buffer.get_buffer().is_char_boundary(*anchor));
+        self.line_selection = state.line_selection && self.selection_anchor.is_some();
+        self.last_undo_behavior = UndoBehavior::CreateUndoPoint;
+    }
+
     pub(crate) fn move_to_start(&mut self, select: bool) {
         self.update_selection_anchor(select);
         self.line_buffer.move_to_start();
@@ -404,7 +587,7 @@ impl Editor {
 
     fn insert_cut_buffer_before(&mut self) {
         self.delete_selection();
-        insert_clipboard_content_before(&mut self.line_buffer, self.cut_buffer.deref_mut())
+        insert_clipboard_content_before(&mut self.line_buffer, &mut self.cut_buffer)
     }
 
     fn insert_cut_buffer_after(&mut self) {
@@ -526,21 +709,128 @@ impl Editor {
         self.line_buffer.move_to_end();
     }
 
+    fn select_lines(&mut self) {
+        self.selection_anchor = Some(self.insertion_point());
+        self.line_selection = true;
+    }
+
+    fn cut_text_object(&mut self, text_object: TextObject) {
+        if let Some(range) = text_object_range(
+            self.line_buffer.get_buffer(),
+            self.insertion_point(),
+            text_object,
+        ) {
+            let cut_slice = &self.line_buffer.get_buffer()[range.clone()];
+            if !cut_slice.is_empty() {
+                self.cut_buffer.set(cut_slice, ClipboardMode::Normal);
+                self.line_buffer.clear_range_safe(range.start, range.end);
+            }
+            self.line_buffer.set_insertion_point(range.start);
+        }
+    }
+
+    // Copies what `cut` would cut, leaving the cursor at the start of the copy like vi
+    fn copy_with(&mut self, cut: impl FnOnce(&mut Self)) {
+        let line_buffer = self.line_buffer.clone();
+        cut(self);
+        let start = self.line_buffer.insertion_point();
+        self.line_buffer = line_buffer;
+        self.line_buffer.set_insertion_point(start);
+    }
+
+    fn copy_current_line(&mut self) {
+        let range = self.line_buffer.current_line_range();
+        let copy_slice = &self.line_buffer.get_buffer()[range];
+        if !copy_slice.is_empty() {
+            self.cut_buffer.set(copy_slice, ClipboardMode::Lines);
+        }
+    }
+
+    fn copy_left(&mut self) {
+        let insertion_offset = self.line_buffer.insertion_point();
+        let left_index = self.line_buffer.grapheme_left_index();
+        if left_index < insertion_offset {
+            self.cut_buffer.set(
+                &self.line_buffer.get_buffer()[left_index..insertion_offset],
+                ClipboardMode::Normal,
+            );
+            self.line_buffer.set_insertion_point(left_index);
+        }
+    }
+
+    fn select_text_object(&mut self, text_object: TextObject) {
+        if let Some(range) = text_object_range(
+            self.line_buffer.get_buffer(),
+            self.insertion_point(),
+            text_object,
+        ) {
+            self.selection_anchor = Some(range.start);
+            self.line_buffer.set_insertion_point(range.end);
+        }
+    }
+
+    fn selection_mode(&self) -> ClipboardMode {
+        if self.line_selection {
+            ClipboardMode::Lines
+        } else {
+            ClipboardMode::Normal
+        }
+    }
+
+    // Removes the selection from `start` to `end` after a cut, selected lines take one of their
+    // line endings along
+    fn clear_cut_selection(&mut self, start: usize, end: usize) {
+        if self.line_selection {
+            let buffer = self.line_buffer.get_buffer();
+            let range = match buffer[end..].find('\n') {
+                Some(index) => start..end + index + 1,
+                None if start > 0 => {
+                    let newline = start - 1;
+                    if buffer[..newline].ends_with('\r') {
+                        newline - 1..end
+                    } else {
+                        newline..end
+                    }
+                }
+                None => start..end,
+            };
+            self.line_buffer.clear_range(range.clone());
+            self.line_buffer.set_insertion_point(range.start);
+            self.line_buffer.move_to_line_start();
+        } else {
+            self.line_buffer.clear_range_safe(start, end);
+        }
+    }
+
     #[cfg(feature = "system_clipboard")]
     fn cut_selection_to_system(&mut self) {
         if let Some((start, end)) = self.get_selection() {
+            let mode = self.selection_mode();
             let cut_slice = &self.line_buffer.get_buffer()[start..end];
-            self.system_clipboard.set(cut_slice, ClipboardMode::Normal);
-            self.line_buffer.clear_range_safe(start, end);
+            self.cut_buffer.system.set(cut_slice, mode);
+            self.clear_cut_selection(start, end);
             self.selection_anchor = None;
         }
     }
 
     fn cut_selection_to_cut_buffer(&mut self) {
         if let Some((start, end)) = self.get_selection() {
+            let mode = self.selection_mode();
             let cut_slice = &self.line_buffer.get_buffer()[start..end];
-            self.cut_buffer.set(cut_slice, ClipboardMode::Normal);
-            self.line_buffer.clear_range_safe(start, end);
+            self.cut_buffer.set(cut_slice, mode);
+            self.clear_cut_selection(start, end);
+            self.selection_anchor = None;
+        }
+    }
+
+    // Like a cut, but selected lines leave an empty line behind to type the replacement on
+    fn change_selection(&mut self) {
+        if let Some((start, end)) = self.get_selection() {
+            let mode = self.selection_mode();
+            let cut_slice = &self.line_buffer.get_buffer()[start..end];
+            self.cut_buffer.set(cut_slice, mode);
+            self.line_buffer.clear_range(start..end);
+            self.line_buffer.set_insertion_point(start);
             self.selection_anchor = None;
         }
     }
@@ -548,26 +838,36 @@ impl Editor {
     #[cfg(feature = "system_clipboard")]
     fn copy_selection_to_system(&mut self) {
         if let Some((start, end)) = self.get_selection() {
+            let mode = self.selection_mode();
             let cut_slice = &self.line_buffer.get_buffer()[start..end];
-            self.system_clipboard.set(cut_slice, ClipboardMode::Normal);
+            self.cut_buffer.system.set(cut_slice, mode);
         }
     }
 
     fn copy_selection_to_cut_buffer(&mut self) {
         if let Some((start, end)) = self.get_selection() {
+            let mode = self.selection_mode();
             let cut_slice = &self.line_buffer.get_buffer()[start..end];
-            self.cut_buffer.set(cut_slice, ClipboardMode::Normal);
+            self.cut_buffer.set(cut_slice, mode);
         }
     }
 
     /// If a selection is active returns the selected range, otherwise None.
     /// The range is guaranteed to be ascending.
+    ///
+    /// A selection of whole lines reaches from the start of its first line to the end of its
+    /// last line, without the line ending.
     pub fn get_selection(&self) -> Option<(usize, usize)> {
         self.selection_anchor.map(|selection_anchor| {
-            if self.insertion_point() > selection_anchor {
+            let (start, end) = if self.insertion_point() > selection_anchor {
                 (selection_anchor, self.insertion_point())
             } else {
                 (self.insertion_point(), selection_anchor)
+            };
+            if self.line_selection {
+                line_bounds(self.line_buffer.get_buffer(), start, end)
+            } else {
+                (start, end)
             }
         })
     }
@@ -582,6 +882,10 @@ impl Editor {
     fn backspace(&mut self) {
         if self.selection_anchor.is_some() {
             self.delete_selection();
+        } else if let Some(auto_pairs) = &self.auto_pairs {
+            if !auto_pairs.backspace(&mut self.line_buffer) {
+                self.line_buffer.delete_left_grapheme();
+            }
         } else {
             self.line_buffer.delete_left_grapheme();
         }
@@ -624,8 +928,36 @@ impl Editor {
     }
 
     fn insert_char(&mut self, c: char) {
+        if let Some(auto_pairs) = &self.auto_pairs {
+            let selection = self.get_selection();
+            if auto_pairs.insert_char(&mut self.line_buffer, selection, c) {
+                self.selection_anchor = None;
+                self.reindent_line(c);
+                return;
+            }
+        }
         self.delete_selection();
         self.line_buffer.insert_char(c);
+        self.reindent_line(c);
+    }
+
+    // Indents the line again when `c` was typed first on it
+    fn reindent_line(&mut self, c: char) {
+        if let Some(indenter) = &self.indenter {
+            let line_start = self.line_buffer.current_line_range().start;
+            let typed_at = self.insertion_point().saturating_sub(c.len_utf8());
+            let buffer = self.line_buffer.get_buffer();
+            // Leading whitespace of the first line is left to the user
+            if line_start > 0
+                && indenter.reindents_on(c)
+                && typed_at >= line_start
+                && buffer[line_start..typed_at].chars().all(is_indentation)
+                && buffer[typed_at..].starts_with(c)
+            {
+                let indentation = indenter.indentation(buffer, line_start);
+                set_indentation(&mut self.line_buffer, line_start, &indentation);
+            }
+        }
     }
 
     fn insert_str(&mut self, str: &str) {
@@ -636,17 +968,138 @@ impl Editor {
     fn insert_newline(&mut self) {
         self.delete_selection();
         self.line_buffer.insert_newline();
+        if let Some(indenter) = &self.indenter {
+            let line_start = self.insertion_point();
+            let moved = self.line_buffer.get_buffer()[line_start..]
+                .trim_start_matches(is_indentation)
+                .chars()
+                .next();
+            // A closer moved onto the new line gets a line of its own, leaving the cursor on an
+            // empty line between the brackets
+            if moved.map_or(false, |c| indenter.reindents_on(c)) {
+                self.line_buffer.insert_newline();
+                let closer_start = self.insertion_point();
+                self.line_buffer.set_insertion_point(line_start);
+                let buffer = self.line_buffer.get_buffer();
+                let indentation = indenter.indentation(buffer, line_start);
+                let closer_indentation = indenter.indentation(buffer, closer_start);
+                if closer_indentation.len() < indentation.len() {
+                    set_indentation(&mut self.line_buffer, closer_start, &closer_indentation);
+                    set_indentation(&mut self.line_buffer, line_start, &indentation);
+                    return;
+                }
+                self.line_buffer.clear_range_safe(line_start, closer_start);
+            }
+            let indentation = indenter.indentation(self.line_buffer.get_buffer(), line_start);
+            set_indentation(&mut self.line_buffer, line_start, &indentation);
+        }
+    }
+
+    // Adds or removes one level of indentation on the selected lines or the current line
+    fn indent_lines(&mut self, dedent: bool) {
+        let unit = self
+            .indenter
+            .as_ref()
+            .map_or(DEFAULT_INDENT_UNIT, |indenter| indenter.unit())
+            .to_string();
+        let insertion_point = self.insertion_point();
+        let (start, end) = self
+            .get_selection()
+            .unwrap_or((insertion_point, insertion_point));
+        let buffer = self.line_buffer.get_buffer();
+        let first_line = buffer[..start].rfind('\n').map_or(0, |index| index + 1);
+        // A selection ending at the start of a line leaves that line out
+        let line_starts: Vec<usize> = std::iter::once(first_line)
+            .chain(
+                buffer[first_line..end]
+                    .match_indices('\n')
+                    .map(|(index, _)| first_line + index + 1)
+                    .filter(|line_start| *line_start < end),
+            )
+            .collect();
+
+        let mut insertion_point = insertion_point;
+        let mut anchor = self.selection_anchor;
+        // From the last line up, the offsets of the lines above stay valid
+        for line_start in line_starts.into_iter().rev() {
+            let line = &self.line_buffer.get_buffer()[line_start..];
+            let (removed, inserted) = if dedent {
+                let removed = if line.starts_with(unit.as_str()) {
+                    unit.len()
+                } else if line.starts_with('\t') {
+                    1
+                } else {
+                    line.len() - line.trim_start_matches(' ').len()
+                };
+                (removed.min(unit.len().max(1)), "")
+            } else if line.starts_with(['\n', '\r']) || line.is_empty() {
+                // Empty lines aren't indented
+                (0, "")
+            } else {
+                (0, unit.as_str())
+            };
+            self.line_buffer
+                .replace_range(line_start..line_start + removed, inserted);
+            let replaced = line_start..line_start + removed;
+            insertion_point = shift_position(insertion_point, &replaced, inserted.len());
+            anchor = anchor.map(|anchor| shift_position(anchor, &replaced, inserted.len()));
+        }
+        self.line_buffer.set_insertion_point(insertion_point);
+        self.selection_anchor = anchor;
+    }
+
+    // Replaces the line endings between the selected lines, or after the current line, and the
+    // indentation following them with a space, the cursor ends up on the last join
+    fn join_lines(&mut self) {
+        let insertion_point = self.insertion_point();
+        let (start, end) = self
+            .get_selection()
+            .unwrap_or((insertion_point, insertion_point));
+        let joins = self.line_buffer.get_buffer()[start..end]
+            .matches('\n')
+            .count()
+            .max(1);
+        let mut position = start;
+        for _ in 0..joins {
+            let buffer = self.line_buffer.get_buffer();
+            let newline = match buffer[position..].find('\n') {
+                Some(index) => position + index,
+                None => break,
+            };
+            let line_end = if buffer[..newline].ends_with('\r') {
+                newline - 1
+            } else {
+                newline
+            };
+            let next_line = &buffer[newline + 1..];
+            let indentation = next_line.len() - next_line.trim_start_matches(is_indentation).len();
+            let next_line_empty = next_line[indentation..].starts_with(['\r', '\n'])
+                || next_line.len() == indentation;
+            let line_empty_or_spaced = buffer[..line_end]
+                .chars()
+                .last()
+                .map_or(true, |c| c == '\n' || is_indentation(c));
+            let separator = if next_line_empty || line_empty_or_spaced {
+                ""
+            } else {
+                " "
+            };
+            self.line_buffer
+                .replace_range(line_end..newline + 1 + indentation, separator);
+            position = line_end;
+        }
+        self.line_buffer.set_insertion_point(position);
     }
 
     #[cfg(feature = "system_clipboard")]
     fn paste_from_system(&mut self) {
         self.delete_selection();
-        insert_clipboard_content_before(&mut self.line_buffer, self.system_clipboard.deref_mut());
+        insert_clipboard_content_before(&mut self.line_buffer, self.cut_buffer.system.deref_mut());
     }
 
     fn paste_cut_buffer(&mut self) {
         self.delete_selection();
-        insert_clipboard_content_before(&mut self.line_buffer, self.cut_buffer.deref_mut());
+        insert_clipboard_content_before(&mut self.line_buffer, &mut self.cut_buffer);
     }
 }
 
@@ -671,6 +1124,7 @@ fn insert_clipboard_content_before(line_buffer: &mut LineBuffer, clipboard: &mut
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::{TextObjectScope, TextObjectType};
     use pretty_assertions::assert_eq;
     use rstest::rstest;
 
@@ -876,6 +1330,251 @@ mod test {
         editor.run_edit_command(&EditCommand::Undo);
         assert_eq!(editor.get_buffer(), "This \r\n is a test");
     }
+    #[test]
+    fn auto_pairs_are_undone_in_one_step() {
+        let mut editor = editor_with("echo ");
+        editor.set_auto_pairs(Some(AutoPairs::default()));
+        for cmd in str_to_edit_commands("(a)") {
+            editor.run_edit_command(&cmd);
+        }
+        assert_eq!(editor.get_buffer(), "echo (a)");
+        editor.run_edit_command(&EditCommand::Undo);
+        assert_eq!(editor.get_buffer(), "echo ");
+
+        editor.run_edit_command(&EditCommand::InsertChar('['));
+        editor.run_edit_command(&EditCommand::Backspace);
+        assert_eq!(editor.get_buffer(), "echo ");
+        editor.run_edit_command(&EditCommand::Undo);
+        assert_eq!(editor.get_buffer(), "echo []");
+    }
+
+    #[test]
+    fn auto_pairs_wrap_the_selection() {
+        let mut editor = editor_with("echo hello");
+        editor.set_auto_pairs(Some(AutoPairs::default()));
+        editor.run_edit_command(&EditCommand::MoveWordLeft { select: true });
+        editor.run_edit_command(&EditCommand::InsertChar('\''));
+
+        assert_eq!(editor.get_buffer(), "echo 'hello'");
+        assert_eq!(editor.get_selection(), None);
+        editor.run_edit_command(&EditCommand::Undo);
+        assert_eq!(editor.get_buffer(), "echo hello");
+    }
+
+    fn indenting_editor(buffer: &str) -> Editor {
+        let mut editor = editor_with(buffer);
+        editor.set_indenter(Some(Box::new(crate::DefaultIndenter::default())));
+        editor
+    }
+
+    #[test]
+    fn new_lines_follow_the_bracket_depth() {
+        let mut editor = indenting_editor("if $x {");
+        editor.run_edit_command(&EditCommand::InsertNewline);
+        for cmd in str_to_edit_commands("[1,") {
+            editor.run_edit_command(&cmd);
+        }
+        editor.run_edit_command(&EditCommand::InsertNewline);
+        for cmd in str_to_edit_commands("2]") {
+            editor.run_edit_command(&cmd);
+        }
+        editor.run_edit_command(&EditCommand::InsertNewline);
+        editor.run_edit_command(&EditCommand::InsertChar('}'));
+
+        assert_eq!(editor.get_buffer(), "if $x {\n    [1,\n        2]\n}");
+        editor.run_edit_command(&EditCommand::Undo);
+        assert_eq!(editor.get_buffer(), "if $x {\n    [1,\n        2]\n    ");
+    }
+
+    #[test]
+    fn newline_between_brackets_opens_an_empty_line() {
+        let mut editor = indenting_editor("do {}");
+        editor.line_buffer.set_insertion_point(4);
+        editor.run_edit_command(&EditCommand::InsertNewline);
+        editor.run_edit_command(&EditCommand::InsertChar('x'));
+
+        assert_eq!(editor.get_buffer(), "do {\n    x\n}");
+    }
+
+    #[rstest]
+    #[case(EditCommand::IndentLines, "a\n        b\n\n          c\nd", (8, 20))]
+    #[case(EditCommand::DedentLines, "a\nb\n\n  c\nd", (2, 5))]
+    fn selected_lines_are_indented(
+        #[case] command: EditCommand,
+        #[case] expected: &str,
+        #[case] selection: (usize, usize),
+    ) {
+        let mut editor = editor_with("a\n    b\n\n      c\nd");
+        editor.line_buffer.set_insertion_point(4);
+        editor.run_edit_command(&EditCommand::MoveToPosition {
+            position: 12,
+            select: true,
+        });
+        editor.run_edit_command(&command);
+
+        assert_eq!(editor.get_buffer(), expected);
+        assert_eq!(editor.get_selection(), Some(selection));
+    }
+
+    #[rstest]
+    #[case(EditCommand::CopyWordLeft, "def ", 4)]
+    #[case(EditCommand::CopyWordRightToNext, "ghi", 8)]
+    #[case(EditCommand::CopyFromLineStart, "abc def ", 0)]
+    #[case(EditCommand::CopyLeft, " ", 7)]
+    #[case(EditCommand::CopyRight, "g", 8)]
+    #[case(EditCommand::CopyLeftUntil('b'), "bc def ", 1)]
+    #[case(EditCommand::CopyCurrentLine, "abc def ghi", 8)]
+    fn copies_leave_the_buffer(
+        #[case] command: EditCommand,
+        #[case] expected_copy: &str,
+        #[case] expected_position: usize,
+    ) {
+        let mut editor = editor_with("abc def ghi");
+        editor.line_buffer.set_insertion_point(8);
+        editor.run_edit_command(&command);
+
+        assert_eq!(editor.get_buffer(), "abc def ghi");
+        assert_eq!(editor.insertion_point(), expected_position);
+        assert_eq!(editor.cut_buffer.get().0, expected_copy);
+    }
+
+    #[test]
+    fn registers_are_used_for_one_command() {
+        let mut editor = editor_with("ls -la");
+        editor.run_edit_command(&EditCommand::UseRegister('a'));
+        editor.run_edit_command(&EditCommand::CopyCurrentLine);
+        editor.run_edit_command(&EditCommand::CutWordLeft);
+        editor.run_edit_command(&EditCommand::UseRegister('a'));
+        editor.run_edit_command(&EditCommand::PasteCutBufferBefore);
+
+        assert_eq!(editor.get_buffer(), "ls -la\nls -");
+    }
+
+    #[test]
+    fn text_objects_are_cut_into_the_cut_buffer() {
+        let mut editor = editor_with("ls (a, (b), c) x");
+        editor.line_buffer.set_insertion_point(8);
+        editor.run_edit_command(&EditCommand::CutTextObject {
+            text_object: TextObject {
+                scope: TextObjectScope::Around,
+                object_type: TextObjectType::Bracket('('),
+            },
+        });
+
+        assert_eq!(editor.get_buffer(), "ls (a, , c) x");
+        assert_eq!(editor.insertion_point(), 7);
+
+        editor.run_edit_command(&EditCommand::PasteCutBufferBefore);
+        assert_eq!(editor.get_buffer(), "ls (a, (b), c) x");
+    }
+
+    #[test]
+    fn missing_text_objects_leave_the_buffer() {
+        let mut editor = editor_with("echo hi");
+        editor.line_buffer.set_insertion_point(5);
+        editor.run_edit_command(&EditCommand::CutTextObject {
+            text_object: TextObject {
+                scope: TextObjectScope::Inner,
+                object_type: TextObjectType::Quote('"'),
+            },
+        });
+
+        assert_eq!(editor.get_buffer(), "echo hi");
+        assert_eq!(editor.insertion_point(), 5);
+    }
+
+    #[test]
+    fn text_objects_are_selected() {
+        let mut editor = editor_with(
+            "if x {
+    a
+}",
+        );
+        editor.line_buffer.set_insertion_point(11);
+        editor.run_edit_command(&EditCommand::SelectTextObject {
+            text_object: TextObject {
+                scope: TextObjectScope::Inner,
+                object_type: TextObjectType::Bracket('{'),
+            },
+        });
+
+        assert_eq!(editor.get_selection(), Some((7, 13)));
+    }
+
+    #[rstest]
+    #[case(EditCommand::CutSelection, "a\nd", 2)]
+    #[case(EditCommand::ChangeSelection, "a\n\nd", 2)]
+    #[case(EditCommand::CopySelection, "a\n  b\nc\nd", 7)]
+    fn selected_lines_are_cut_changed_and_copied(
+        #[case] command: EditCommand,
+        #[case] expected: &str,
+        #[case] expected_position: usize,
+    ) {
+        let mut editor = editor_with("a\n  b\nc\nd");
+        editor.line_buffer.set_insertion_point(4);
+        editor.run_edit_command(&EditCommand::SelectLines);
+        editor.run_edit_command(&EditCommand::MoveLineDown { select: true });
+
+        assert_eq!(editor.get_selection(), Some((2, 7)));
+        editor.run_edit_command(&command);
+
+        assert_eq!(editor.get_buffer(), expected);
+        assert_eq!(editor.insertion_point(), expected_position);
+        assert_eq!(
+            editor.cut_buffer.get(),
+            ("  b\nc".to_string(), ClipboardMode::Lines)
+        );
+    }
+
+    #[test]
+    fn cutting_the_last_lines_removes_the_line_ending_before_them() {
+        let mut editor = editor_with("a\nb\nc");
+        editor.line_buffer.set_insertion_point(2);
+        editor.run_edit_command(&EditCommand::SelectLines);
+        editor.run_edit_command(&EditCommand::MoveToEnd { select: true });
+        editor.run_edit_command(&EditCommand::CutSelection);
+
+        assert_eq!(editor.get_buffer(), "a");
+        assert_eq!(editor.insertion_point(), 0);
+    }
+
+    #[test]
+    fn new_selections_are_character_wise() {
+        let mut editor = editor_with("ab\ncd");
+        editor.line_buffer.set_insertion_point(0);
+        editor.run_edit_command(&EditCommand::SelectLines);
+        editor.run_edit_command(&EditCommand::MoveToLineEnd { select: false });
+        editor.run_edit_command(&EditCommand::MoveLeft { select: true });
+
+        assert_eq!(editor.get_selection(), Some((1, 2)));
+    }
+
+    #[rstest]
+    #[case("ls\n    -la", None, "ls -la", 2)]
+    #[case("a\nb\nc\nd", Some(4), "a b c\nd", 3)]
+    #[case("a \n\nb", None, "a \nb", 2)]
+    fn lines_are_joined(
+        #[case] input: &str,
+        #[case] selected_to: Option<usize>,
+        #[case] expected: &str,
+        #[case] expected_position: usize,
+    ) {
+        let mut editor = editor_with(input);
+        editor.line_buffer.set_insertion_point(0);
+        if let Some(position) = selected_to {
+            editor.run_edit_command(&EditCommand::SelectLines);
+            editor.run_edit_command(&EditCommand::MoveToPosition {
+                position,
+                select: true,
+            });
+        }
+        editor.run_edit_command(&EditCommand::JoinLines);
+
+        assert_eq!(editor.get_buffer(), expected);
+        assert_eq!(editor.insertion_point(), expected_position);
+        assert_eq!(editor.get_selection(), None);
+    }
+
     #[cfg(feature = "system_clipboard")]
     mod without_system_clipboard {
         use super::*;
@@ -1,11 +1,12 @@
 use {
     itertools::Itertools,
+    serde::{Deserialize, Serialize},
     std::{convert::From, ops::Range},
     unicode_segmentation::UnicodeSegmentation,
 };
 
 /// In memory representation of the entered line(s) including a cursor position to facilitate cursor based editing.
-#[derive(Debug, PartialEq, Eq, Clone, Default)]
+#[derive(Debug, PartialEq, Eq, Clone, Default, Serialize, Deserialize)]
 pub struct LineBuffer {
     lines: String,
     insertion_point: usize,
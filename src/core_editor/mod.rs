@@ -1,10 +1,20 @@
+mod auto_pairs;
 mod clip_buffer;
 mod edit_stack;
 mod editor;
 mod line_buffer;
+mod registers;
+mod text_object;
 
+pub use auto_pairs::AutoPairs;
 #[cfg(feature = "system_clipboard")]
 pub(crate) use clip_buffer::get_system_clipboard;
 pub(crate) use clip_buffer::{get_local_clipboard, Clipboard, ClipboardMode};
+#[cfg(feature = "system_clipboard")]
+pub use clip_buffer::{Osc52Clipboard, Osc52Passthrough};
+#[cfg(feature = "system_clipboard")]
+pub(crate) use clip_buffer::{Osc52Terminal, PASTE_REQUEST};
 pub use editor::Editor;
+pub(crate) use editor::EditorState;
 pub use line_buffer::LineBuffer;
+pub(crate) use registers::is_register;
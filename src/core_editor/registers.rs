@@ -0,0 +1,222 @@
+#[cfg(feature = "system_clipboard")]
+use super::get_system_clipboard;
+use super::{get_local_clipboard, Clipboard, ClipboardMode};
+use std::collections::{HashMap, VecDeque};
+
+// Number of cuts kept in the registers `1` to `9`
+const NUMBERED_REGISTERS: usize = 9;
+
+/// The vi registers behind the cut buffer of the [`crate::Editor`]
+///
+/// Without a selected register the cut buffer is the unnamed register. Cuts and copies still
+/// land in it when a register is selected, so a following plain paste repeats them.
+pub(crate) struct Registers {
+    unnamed: Box<dyn Clipboard>,
+    named: HashMap<char, (String, ClipboardMode)>,
+    // Register `0`
+    copied: (String, ClipboardMode),
+    // Registers `1` to `9`, the last cut first
+    cut: VecDeque<(String, ClipboardMode)>,
+    #[cfg(feature = "system_clipboard")]
+    pub(crate) system: Box<dyn Clipboard>,
+    selected: Option<char>,
+    copying: bool,
+}
+
+impl Registers {
+    /// Registers with the system clipboard as the register `+`
+    pub(crate) fn new() -> Self {
+        Registers {
+            #[cfg(feature = "system_clipboard")]
+            system: get_system_clipboard(),
+            ..Self::local()
+        }
+    }
+
+    /// Registers with a local clipboard for the register `+`
+    pub(crate) fn local() -> Self {
+        Registers {
+            unnamed: get_local_clipboard(),
+            named: HashMap::new(),
+            copied: (String::new(), ClipboardMode::Normal),
+            cut: VecDeque::new(),
+            #[cfg(feature = "system_clipboard")]
+            system: get_local_clipboard(),
+            selected: None,
+            copying: false,
+        }
+    }
+
+    /// Selects the register of the next cut, copy or paste
+    pub(crate) fn select(&mut self, register: char) {
+        self.selected = is_register(register).then_some(register);
+    }
+
+    /// Makes the register selected for the next command the unnamed register again
+    pub(crate) fn reset(&mut self) {
+        self.selected = None;
+    }
+
+    /// Whether the following contents come from a copy, which are kept in register `0`
+    /// instead of the registers of cuts
+    pub(crate) fn set_copying(&mut self, copying: bool) {
+        self.copying = copying;
+    }
+}
+
+/// Whether `register` names one of the registers of [`crate::EditCommand::UseRegister`]
+pub(crate) fn is_register(register: char) -> bool {
+    register.is_ascii_alphanumeric() || register == '"' || register == '+'
+}
+
+// Appends to the contents of a register, keeping whole lines on lines of their own
+fn append(
+    (previous, previous_mode): (String, ClipboardMode),
+    content: &str,
+    mode: ClipboardMode,
+) -> (String, ClipboardMode) {
+    if previous.is_empty() {
+        return (content.to_string(), mode);
+    }
+    match (previous_mode, mode) {
+        (ClipboardMode::Normal, ClipboardMode::Normal) => {
+            (previous + content, ClipboardMode::Normal)
+        }
+        _ => {
+            let mut lines = previous;
+            if !lines.ends_with('\n') {
+                lines.push('\n');
+            }
+            lines.push_str(content);
+            (lines, ClipboardMode::Lines)
+        }
+    }
+}
+
+impl Clipboard for Registers {
+    fn set(&mut self, content: &str, mode: ClipboardMode) {
+        let (content, mode) = match self.selected {
+            Some(register @ 'a'..='z') => {
+                self.named.insert(register, (content.to_string(), mode));
+                (content.to_string(), mode)
+            }
+            Some(register @ 'A'..='Z') => {
+                let register = register.to_ascii_lowercase();
+                let previous = self.named.remove(&register).unwrap_or_default();
+                let appended = append(previous, content, mode);
+                self.named.insert(register, appended.clone());
+                appended
+            }
+            #[cfg(feature = "system_clipboard")]
+            Some('+') => {
+                self.system.set(content, mode);
+                (content.to_string(), mode)
+            }
+            #[cfg(not(feature = "system_clipboard"))]
+            Some('+') => {
+                self.named.insert('+', (content.to_string(), mode));
+                (content.to_string(), mode)
+            }
+            _ if self.copying => {
+                self.copied = (content.to_string(), mode);
+                (content.to_string(), mode)
+            }
+            _ => {
+                self.cut.push_front((content.to_string(), mode));
+                self.cut.truncate(NUMBERED_REGISTERS);
+                (content.to_string(), mode)
+            }
+        };
+        self.unnamed.set(&content, mode);
+    }
+
+    fn get(&mut self) -> (String, ClipboardMode) {
+        let empty = || (String::new(), ClipboardMode::Normal);
+        match self.selected {
+            Some(register @ ('a'..='z' | 'A'..='Z')) => self
+                .named
+                .get(&register.to_ascii_lowercase())
+                .cloned()
+                .unwrap_or_else(empty),
+            Some('0') => self.copied.clone(),
+            Some(register @ '1'..='9') => {
+                let index = register as usize - '1' as usize;
+                self.cut.get(index).cloned().unwrap_or_else(empty)
+            }
+            #[cfg(feature = "system_clipboard")]
+            Some('+') => self.system.get(),
+            #[cfg(not(feature = "system_clipboard"))]
+            Some('+') => self.named.get(&'+').cloned().unwrap_or_else(empty),
+            _ => self.unnamed.get(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn get(registers: &mut Registers, register: char) -> String {
+        registers.select(register);
+        let (content, _) = registers.get();
+        registers.reset();
+        content
+    }
+
+    #[test]
+    fn named_registers_are_set_and_appended() {
+        let mut registers = Registers::local();
+        registers.select('a');
+        registers.set("ls", ClipboardMode::Normal);
+        registers.select('A');
+        registers.set(" -la", ClipboardMode::Normal);
+        registers.reset();
+
+        assert_eq!(get(&mut registers, 'a'), "ls -la");
+        assert_eq!(get(&mut registers, 'b'), "");
+        assert_eq!(registers.get().0, "ls -la");
+    }
+
+    #[test]
+    fn lines_are_appended_on_lines_of_their_own() {
+        let mut registers = Registers::local();
+        registers.select('a');
+        registers.set("echo", ClipboardMode::Normal);
+        registers.select('A');
+        registers.set("ls\n", ClipboardMode::Lines);
+
+        assert_eq!(
+            registers.get(),
+            ("echo\nls\n".to_string(), ClipboardMode::Lines)
+        );
+    }
+
+    #[test]
+    fn copies_and_cuts_are_kept_apart() {
+        let mut registers = Registers::local();
+        registers.set_copying(true);
+        registers.set("copied", ClipboardMode::Normal);
+        registers.set_copying(false);
+        for cut in ["first", "second"] {
+            registers.set(cut, ClipboardMode::Normal);
+        }
+
+        assert_eq!(get(&mut registers, '0'), "copied");
+        assert_eq!(get(&mut registers, '1'), "second");
+        assert_eq!(get(&mut registers, '2'), "first");
+        assert_eq!(get(&mut registers, '3'), "");
+        assert_eq!(get(&mut registers, '"'), "second");
+    }
+
+    #[test]
+    fn only_nine_cuts_are_numbered() {
+        let mut registers = Registers::local();
+        for cut in 1..=10 {
+            registers.set(&cut.to_string(), ClipboardMode::Normal);
+        }
+
+        assert_eq!(get(&mut registers, '1'), "10");
+        assert_eq!(get(&mut registers, '9'), "2");
+    }
+}
@@ -0,0 +1,402 @@
+use crate::{TextObject, TextObjectScope, TextObjectType};
+use std::ops::Range;
+
+const BRACKETS: [(char, char); 4] = [('(', ')'), ('[', ']'), ('{', '}'), ('<', '>')];
+
+// Brackets nesting the arguments of a call or a list
+const ARGUMENT_BRACKETS: [(char, char); 3] = [('(', ')'), ('[', ']'), ('{', '}')];
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum CharClass {
+    Newline,
+    Blank,
+    Word,
+    Punctuation,
+}
+
+fn char_class(c: char, big_word: bool) -> CharClass {
+    if c == '\n' || c == '\r' {
+        CharClass::Newline
+    } else if c.is_whitespace() {
+        CharClass::Blank
+    } else if big_word || c.is_alphanumeric() || c == '_' {
+        CharClass::Word
+    } else {
+        CharClass::Punctuation
+    }
+}
+
+/// The range of `buffer` covered by `text_object` around the character at `cursor`
+///
+/// `None` when there is no such text, e.g. for brackets when the cursor isn't between any.
+pub(crate) fn text_object_range(
+    buffer: &str,
+    cursor: usize,
+    text_object: TextObject,
+) -> Option<Range<usize>> {
+    // The cursor at the end of the buffer stands on its last character, like in vi
+    let cursor = if cursor >= buffer.len() {
+        buffer.char_indices().next_back()?.0
+    } else {
+        cursor
+    };
+    let around = text_object.scope == TextObjectScope::Around;
+    match text_object.object_type {
+        TextObjectType::Word => word_range(buffer, cursor, false, around),
+        TextObjectType::BigWord => word_range(buffer, cursor, true, around),
+        TextObjectType::Quote(quote) => quote_range(buffer, cursor, quote, around),
+        TextObjectType::Bracket(bracket) => {
+            let pair = BRACKETS
+                .iter()
+                .copied()
+                .find(|(open, close)| bracket == *open || bracket == *close)?;
+            bracket_range(buffer, cursor, pair, around)
+        }
+        TextObjectType::Argument => argument_range(buffer, cursor, around),
+    }
+}
+
+// Extends `index` over the characters of `class`, towards the end of the buffer with `forward`
+fn extend(buffer: &str, index: usize, class: CharClass, big_word: bool, forward: bool) -> usize {
+    if forward {
+        buffer[index..]
+            .char_indices()
+            .find(|(_, c)| char_class(*c, big_word) != class)
+            .map_or(buffer.len(), |(offset, _)| index + offset)
+    } else {
+        buffer[..index]
+            .char_indices()
+            .rev()
+            .find(|(_, c)| char_class(*c, big_word) != class)
+            .map_or(0, |(offset, c)| offset + c.len_utf8())
+    }
+}
+
+fn class_at(buffer: &str, index: usize, big_word: bool) -> Option<CharClass> {
+    buffer[index..]
+        .chars()
+        .next()
+        .map(|c| char_class(c, big_word))
+}
+
+fn word_range(buffer: &str, cursor: usize, big_word: bool, around: bool) -> Option<Range<usize>> {
+    let class = class_at(buffer, cursor, big_word)?;
+    if class == CharClass::Newline {
+        return None;
+    }
+    let start = extend(buffer, cursor, class, big_word, false);
+    let end = extend(buffer, cursor, class, big_word, true);
+    if !around {
+        return Some(start..end);
+    }
+
+    if class == CharClass::Blank {
+        // The blanks and the word after them
+        let word_end = match class_at(buffer, end, big_word) {
+            Some(next) if next != CharClass::Newline => extend(buffer, end, next, big_word, true),
+            _ => end,
+        };
+        return Some(start..word_end);
+    }
+    // The word with the blanks after it or, at the end of a line, the blanks before it
+    let trailing_end = extend(buffer, end, CharClass::Blank, big_word, true);
+    if trailing_end > end {
+        Some(start..trailing_end)
+    } else {
+        Some(extend(buffer, start, CharClass::Blank, big_word, false)..end)
+    }
+}
+
+fn quote_range(buffer: &str, cursor: usize, quote: char, around: bool) -> Option<Range<usize>> {
+    let line_start = buffer[..cursor].rfind('\n').map_or(0, |index| index + 1);
+    let line_end = buffer[cursor..]
+        .find('\n')
+        .map_or(buffer.len(), |index| cursor + index);
+    let line = &buffer[line_start..line_end];
+
+    // Quotes escaped with a backslash don't delimit strings
+    let mut quotes = Vec::new();
+    let mut escaped = false;
+    for (index, c) in line.char_indices() {
+        if c == quote && !escaped {
+            quotes.push(line_start + index);
+        }
+        escaped = c == '\\' && !escaped;
+    }
+
+    let (open, close) = if quotes.contains(&cursor) {
+        // Strings are paired up from the start of the line
+        let position = quotes.iter().position(|index| *index == cursor)?;
+        let first = position - position % 2;
+        (*quotes.get(first)?, *quotes.get(first + 1)?)
+    } else {
+        let before = quotes.iter().rev().find(|index| **index < cursor);
+        let after = quotes.iter().find(|index| **index > cursor);
+        match (before, after) {
+            (Some(open), Some(close)) => (*open, *close),
+            // Without a quote before the cursor, the next string on the line
+            (None, Some(open)) => {
+                let close = quotes.iter().find(|index| *index > open)?;
+                (*open, *close)
+            }
+            _ => return None,
+        }
+    };
+
+    let quote_len = quote.len_utf8();
+    if !around {
+        return Some(open + quote_len..close);
+    }
+    let end = close + quote_len;
+    let trailing_end = extend(buffer, end, CharClass::Blank, false, true).min(line_end);
+    if trailing_end > end {
+        Some(open..trailing_end)
+    } else {
+        Some(extend(buffer, open, CharClass::Blank, false, false).max(line_start)..end)
+    }
+}
+
+// The opening and closing positions of the innermost pair of `pairs` around `cursor`
+fn enclosing_pair(
+    buffer: &str,
+    cursor: usize,
+    pairs: &[(char, char)],
+    on_open: bool,
+) -> Option<(usize, usize)> {
+    let is_open = |c: char| pairs.iter().any(|(open, _)| *open == c);
+    let is_close = |c: char| pairs.iter().any(|(_, close)| *close == c);
+
+    let at_cursor = buffer[cursor..].chars().next()?;
+    let open = if on_open && is_open(at_cursor) {
+        cursor
+    } else {
+        let mut depth = 0usize;
+        let mut open = None;
+        for (index, c) in buffer[..cursor].char_indices().rev() {
+            if is_close(c) {
+                depth += 1;
+            } else if is_open(c) {
+                if depth == 0 {
+                    open = Some(index);
+                    break;
+                }
+                depth -= 1;
+            }
+        }
+        open?
+    };
+
+    let open_char = buffer[open..].chars().next()?;
+    let mut depth = 0usize;
+    for (index, c) in buffer[open + open_char.len_utf8()..].char_indices() {
+        if is_open(c) {
+            depth += 1;
+        } else if is_close(c) {
+            if depth == 0 {
+                let close = open + open_char.len_utf8() + index;
+                return (close >= cursor).then_some((open, close));
+            }
+            depth -= 1;
+        }
+    }
+    None
+}
+
+fn bracket_range(
+    buffer: &str,
+    cursor: usize,
+    pair: (char, char),
+    around: bool,
+) -> Option<Range<usize>> {
+    let (open, close) = enclosing_pair(buffer, cursor, &[pair], true)?;
+    if around {
+        return Some(open..close + pair.1.len_utf8());
+    }
+
+    let mut start = open + pair.0.len_utf8();
+    let mut end = close;
+    // Brackets on lines of their own keep their lines, like a block in vi
+    let after_open = &buffer[start..end];
+    let first_line_break = after_open
+        .find('\n')
+        .filter(|index| after_open[..*index].trim().is_empty());
+    let last_line_start = after_open
+        .rfind('\n')
+        .filter(|index| after_open[index + 1..].trim().is_empty());
+    if let (Some(first), Some(last)) = (first_line_break, last_line_start) {
+        if first < last {
+            end = start + last + 1;
+            start += first + 1;
+        }
+    }
+    Some(start..end)
+}
+
+fn argument_range(buffer: &str, cursor: usize, around: bool) -> Option<Range<usize>> {
+    let (open, close) = enclosing_pair(buffer, cursor, &ARGUMENT_BRACKETS, false)?;
+
+    // Commas outside of nested brackets and strings separate the arguments
+    let content_start = open + 1;
+    let mut separators = vec![open];
+    let mut depth = 0usize;
+    let mut quote = None;
+    for (index, c) in buffer[content_start..close].char_indices() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), _) => {}
+            (None, '"' | '\'') => quote = Some(c),
+            (None, '(' | '[' | '{') => depth += 1,
+            (None, ')' | ']' | '}') => depth = depth.saturating_sub(1),
+            (None, ',') if depth == 0 => separators.push(content_start + index),
+            _ => {}
+        }
+    }
+    separators.push(close);
+
+    // A comma under the cursor belongs to the argument before it
+    let argument = separators
+        .windows(2)
+        .position(|window| cursor > window[0] && cursor <= window[1])
+        .unwrap_or(0);
+    let (before, after) = (separators[argument], separators[argument + 1]);
+    let segment = &buffer[before + 1..after];
+    let start = before + 1 + (segment.len() - segment.trim_start().len());
+    let end = (before + 1 + segment.trim_end().len()).max(start);
+    if !around {
+        return Some(start..end);
+    }
+
+    if after < close {
+        // The argument with the comma and the blanks up to the next argument
+        let next = &buffer[after + 1..close];
+        Some(start..after + 1 + (next.len() - next.trim_start().len()))
+    } else if before > open {
+        // The last argument with the comma before it
+        Some(before..end)
+    } else {
+        Some(start..end)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+
+    // The cursor is marked with `|`, the expected range with `[` and `]`
+    fn apply(text: &str, scope: TextObjectScope, object_type: TextObjectType) -> Option<String> {
+        let cursor = text.find('|').unwrap();
+        let buffer = text.replace('|', "");
+        let range = text_object_range(&buffer, cursor, TextObject { scope, object_type })?;
+        Some(format!(
+            "{}[{}]{}",
+            &buffer[..range.start],
+            &buffer[range.clone()],
+            &buffer[range.end..]
+        ))
+    }
+
+    #[rstest]
+    #[case("echo he|llo world", TextObjectScope::Inner, "echo [hello] world")]
+    #[case("echo he|llo world", TextObjectScope::Around, "echo [hello ]world")]
+    #[case("echo hello wor|ld", TextObjectScope::Around, "echo hello[ world]")]
+    #[case("echo |  hello", TextObjectScope::Inner, "echo[   ]hello")]
+    #[case("echo |  hello", TextObjectScope::Around, "echo[   hello]")]
+    #[case("a.b|ar(c)", TextObjectScope::Inner, "a.[bar](c)")]
+    #[case("ls -la|", TextObjectScope::Inner, "ls -[la]")]
+    fn words(#[case] text: &str, #[case] scope: TextObjectScope, #[case] expected: &str) {
+        assert_eq!(
+            apply(text, scope, TextObjectType::Word),
+            Some(expected.to_string())
+        );
+    }
+
+    #[rstest]
+    #[case("cd a.b|ar(c) x", TextObjectScope::Inner, "cd [a.bar(c)] x")]
+    #[case("cd a.b|ar(c) x", TextObjectScope::Around, "cd [a.bar(c) ]x")]
+    fn big_words(#[case] text: &str, #[case] scope: TextObjectScope, #[case] expected: &str) {
+        assert_eq!(
+            apply(text, scope, TextObjectType::BigWord),
+            Some(expected.to_string())
+        );
+    }
+
+    #[rstest]
+    #[case(
+        r#"echo "he|llo" x"#,
+        '"',
+        TextObjectScope::Inner,
+        r#"echo "[hello]" x"#
+    )]
+    #[case(
+        r#"echo "he|llo" x"#,
+        '"',
+        TextObjectScope::Around,
+        r#"echo ["hello" ]x"#
+    )]
+    #[case(r#"echo |x "a\"b""#, '"', TextObjectScope::Inner, r#"echo x "[a\"b]""#)]
+    #[case(r#"echo 'a' |'b'"#, '\'', TextObjectScope::Inner, r#"echo 'a' '[b]'"#)]
+    #[case("echo `da|te`", '`', TextObjectScope::Around, "echo[ `date`]")]
+    fn quotes(
+        #[case] text: &str,
+        #[case] quote: char,
+        #[case] scope: TextObjectScope,
+        #[case] expected: &str,
+    ) {
+        assert_eq!(
+            apply(text, scope, TextObjectType::Quote(quote)),
+            Some(expected.to_string())
+        );
+    }
+
+    #[rstest]
+    #[case("f(a, (b|), c)", '(', TextObjectScope::Inner, "f(a, ([b]), c)")]
+    #[case("f(a, (b), |c)", ')', TextObjectScope::Inner, "f([a, (b), c])")]
+    #[case("f|(a)", '(', TextObjectScope::Around, "f[(a)]")]
+    #[case("[1, {a: |2}]", '[', TextObjectScope::Around, "[[1, {a: 2}]]")]
+    #[case("if x {\n    a|\n}", '{', TextObjectScope::Inner, "if x {\n[    a\n]}")]
+    #[case(
+        "if x {\n  { a|\n  }\n}",
+        '{',
+        TextObjectScope::Inner,
+        "if x {\n  {[ a\n  ]}\n}"
+    )]
+    fn brackets(
+        #[case] text: &str,
+        #[case] bracket: char,
+        #[case] scope: TextObjectScope,
+        #[case] expected: &str,
+    ) {
+        assert_eq!(
+            apply(text, scope, TextObjectType::Bracket(bracket)),
+            Some(expected.to_string())
+        );
+    }
+
+    #[rstest]
+    #[case("f(a, b|b, c)", TextObjectScope::Inner, "f(a, [bb], c)")]
+    #[case("f(a, b|b, c)", TextObjectScope::Around, "f(a, [bb, ]c)")]
+    #[case("f(a, g(x, |y))", TextObjectScope::Around, "f(a, g(x[, y]))")]
+    #[case(
+        "f(a, [1, 2]|, \"x,y\")",
+        TextObjectScope::Inner,
+        "f(a, [[1, 2]], \"x,y\")"
+    )]
+    #[case("f(|only)", TextObjectScope::Around, "f([only])")]
+    fn arguments(#[case] text: &str, #[case] scope: TextObjectScope, #[case] expected: &str) {
+        assert_eq!(
+            apply(text, scope, TextObjectType::Argument),
+            Some(expected.to_string())
+        );
+    }
+
+    #[rstest]
+    #[case("echo |hi", TextObjectType::Bracket('('))]
+    #[case("echo |hi", TextObjectType::Quote('"'))]
+    #[case("a\n|\nb", TextObjectType::Word)]
+    #[case("|", TextObjectType::Word)]
+    fn missing_objects(#[case] text: &str, #[case] object_type: TextObjectType) {
+        assert_eq!(apply(text, TextObjectScope::Inner, object_type), None);
+    }
+}
@@ -2,6 +2,8 @@ use crate::{
     enums::{ReedlineEvent, ReedlineRawEvent},
     PromptEditMode,
 };
+use crossterm::event::{MouseButton, MouseEvent, MouseEventKind};
+use std::time::Duration;
 
 /// Define the style of parsing for the edit events
 /// Available default options:
@@ -13,4 +15,45 @@ pub trait EditMode: Send {
 
     /// What to display in the prompt indicator
     fn edit_mode(&self) -> PromptEditMode;
+
+    /// Name of the internal mode the edit mode is in, e.g. the vi normal or insert mode
+    ///
+    /// Kept in a [`crate::SessionSnapshot`] and handed back to
+    /// [`EditMode::restore_mode_state()`]. Edit modes without internal modes return `None`.
+    fn mode_state(&self) -> Option<String> {
+        None
+    }
+
+    /// Switch back to the internal mode returned by [`EditMode::mode_state()`]
+    ///
+    /// Unknown names are ignored.
+    fn restore_mode_state(&mut self, _state: &str) {}
+
+    /// How long to wait for the next key of a pending sequence of keys, e.g. after the
+    /// `Ctrl-x` of `Ctrl-x Ctrl-e`
+    ///
+    /// `None` while no sequence is pending. When the time passes without another key,
+    /// [`EditMode::resolve_pending_sequence()`] is called.
+    fn pending_sequence_timeout(&self) -> Option<Duration> {
+        None
+    }
+
+    /// Give up waiting for the rest of a pending sequence and handle the keys typed so far
+    fn resolve_pending_sequence(&mut self) -> ReedlineEvent {
+        ReedlineEvent::None
+    }
+}
+
+/// Translate the mouse events shared by all edit modes
+///
+/// Left clicks place the cursor or select a menu entry, the wheel scrolls the menu pages
+pub(crate) fn parse_mouse_event(event: MouseEvent) -> ReedlineEvent {
+    match event.kind {
+        MouseEventKind::Down(MouseButton::Left) => {
+            ReedlineEvent::MouseClick(event.column, event.row)
+        }
+        MouseEventKind::ScrollDown => ReedlineEvent::MenuPageNext,
+        MouseEventKind::ScrollUp => ReedlineEvent::MenuPagePrevious,
+        _ => ReedlineEvent::None,
+    }
 }
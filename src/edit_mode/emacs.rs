@@ -2,14 +2,15 @@ use crate::{
     edit_mode::{
         keybindings::{
             add_common_control_bindings, add_common_edit_bindings, add_common_navigation_bindings,
-            add_common_selection_bindings, edit_bind, Keybindings,
+            add_common_selection_bindings, edit_bind, KeyCombination, Keybindings,
         },
-        EditMode,
+        parse_mouse_event, EditMode,
     },
     enums::{EditCommand, ReedlineEvent, ReedlineRawEvent},
     PromptEditMode,
 };
 use crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers};
+use std::time::Duration;
 
 /// Returns the current default emacs keybindings
 pub fn default_emacs_keybindings() -> Keybindings {
@@ -99,19 +100,48 @@ pub fn default_emacs_keybindings() -> Keybindings {
     kb.add_binding(KM::ALT, KC::Char('l'), edit_bind(EC::LowercaseWord));
     kb.add_binding(KM::ALT, KC::Char('c'), edit_bind(EC::CapitalizeChar));
 
+    // *** SEQUENCES ***
+    let ctrl_x = KeyCombination {
+        modifier: KM::CONTROL,
+        key_code: KC::Char('x'),
+    };
+    kb.add_sequence_binding(
+        vec![
+            ctrl_x.clone(),
+            KeyCombination {
+                modifier: KM::CONTROL,
+                key_code: KC::Char('e'),
+            },
+        ],
+        ReedlineEvent::OpenEditor,
+    );
+    kb.add_sequence_binding(
+        vec![
+            ctrl_x,
+            KeyCombination {
+                modifier: KM::NONE,
+                key_code: KC::Char('u'),
+            },
+        ],
+        edit_bind(EC::Undo),
+    );
+
     kb
 }
 
+/// The default time to wait for the next key of a sequence like `Ctrl-x Ctrl-e`
+pub(crate) const DEFAULT_SEQUENCE_TIMEOUT: Duration = Duration::from_millis(1000);
+
 /// This parses the incoming Events like a emacs style-editor
 pub struct Emacs {
     keybindings: Keybindings,
+    pending_keys: Vec<KeyCombination>,
+    sequence_timeout: Duration,
 }
 
 impl Default for Emacs {
     fn default() -> Self {
-        Emacs {
-            keybindings: default_emacs_keybindings(),
-        }
+        Emacs::new(default_emacs_keybindings())
     }
 }
 
@@ -120,51 +150,13 @@ impl EditMode for Emacs {
         match event.into() {
             Event::Key(KeyEvent {
                 code, modifiers, ..
-            }) => match (modifiers, code) {
-                (modifier, KeyCode::Char(c)) => {
-                    // Note. The modifier can also be a combination of modifiers, for
-                    // example:
-                    //     KeyModifiers::CONTROL | KeyModifiers::ALT
-                    //     KeyModifiers::CONTROL | KeyModifiers::ALT | KeyModifiers::SHIFT
-                    //
-                    // Mixed modifiers are used by non american keyboards that have extra
-                    // keys like 'alt gr'. Keep this in mind if in the future there are
-                    // cases where an event is not being captured
-                    let c = match modifier {
-                        KeyModifiers::NONE => c,
-                        _ => c.to_ascii_lowercase(),
-                    };
-
-                    self.keybindings
-                        .find_binding(modifier, KeyCode::Char(c))
-                        .unwrap_or_else(|| {
-                            if modifier == KeyModifiers::NONE
-                                || modifier == KeyModifiers::SHIFT
-                                || modifier == KeyModifiers::CONTROL | KeyModifiers::ALT
-                                || modifier
-                                    == KeyModifiers::CONTROL
-                                        | KeyModifiers::ALT
-                                        | KeyModifiers::SHIFT
-                            {
-                                ReedlineEvent::Edit(vec![EditCommand::InsertChar(
-                                    if modifier == KeyModifiers::SHIFT {
-                                        c.to_ascii_uppercase()
-                                    } else {
-                                        c
-                                    },
-                                )])
-                            } else {
-                                ReedlineEvent::None
-                            }
-                        })
-                }
-                _ => self
-                    .keybindings
-                    .find_binding(modifiers, code)
-                    .unwrap_or(ReedlineEvent::None),
-            },
+            }) => {
+                let key_combo = KeyCombination::from_key_event(modifiers, code);
+                self.keybindings
+                    .parse_sequence_key(&mut self.pending_keys, key_combo)
+            }
 
-            Event::Mouse(_) => ReedlineEvent::Mouse,
+            Event::Mouse(event) => parse_mouse_event(event),
             Event::Resize(width, height) => ReedlineEvent::Resize(width, height),
             Event::FocusGained => ReedlineEvent::None,
             Event::FocusLost => ReedlineEvent::None,
@@ -177,12 +169,32 @@ impl EditMode for Emacs {
     fn edit_mode(&self) -> PromptEditMode {
         PromptEditMode::Emacs
     }
+
+    fn pending_sequence_timeout(&self) -> Option<Duration> {
+        (!self.pending_keys.is_empty()).then_some(self.sequence_timeout)
+    }
+
+    fn resolve_pending_sequence(&mut self) -> ReedlineEvent {
+        self.keybindings.resolve_sequence(&mut self.pending_keys)
+    }
 }
 
 impl Emacs {
     /// Emacs style input parsing constructor if you want to use custom keybindings
     pub const fn new(keybindings: Keybindings) -> Self {
-        Emacs { keybindings }
+        Emacs {
+            keybindings,
+            pending_keys: Vec::new(),
+            sequence_timeout: DEFAULT_SEQUENCE_TIMEOUT,
+        }
+    }
+
+    /// A builder that sets how long to wait for the next key of a sequence before the keys
+    /// typed so far are handled on their own, one second by default
+    #[must_use]
+    pub fn with_sequence_timeout(mut self, timeout: Duration) -> Self {
+        self.sequence_timeout = timeout;
+        self
     }
 }
 
@@ -288,4 +300,72 @@ mod test {
             ReedlineEvent::Edit(vec![EditCommand::InsertChar('😀')])
         );
     }
+
+    fn ctrl(c: char) -> ReedlineRawEvent {
+        ReedlineRawEvent::try_from(Event::Key(KeyEvent::new(
+            KeyCode::Char(c),
+            KeyModifiers::CONTROL,
+        )))
+        .unwrap()
+    }
+
+    fn plain(code: KeyCode) -> ReedlineRawEvent {
+        ReedlineRawEvent::try_from(Event::Key(KeyEvent::new(code, KeyModifiers::NONE))).unwrap()
+    }
+
+    #[test]
+    fn sequences_wait_for_their_last_key() {
+        let mut emacs = Emacs::default();
+
+        assert_eq!(emacs.parse_event(ctrl('x')), ReedlineEvent::None);
+        assert_eq!(
+            emacs.pending_sequence_timeout(),
+            Some(DEFAULT_SEQUENCE_TIMEOUT)
+        );
+        assert_eq!(emacs.parse_event(ctrl('e')), ReedlineEvent::OpenEditor);
+        assert_eq!(emacs.pending_sequence_timeout(), None);
+
+        emacs.parse_event(ctrl('x'));
+        assert_eq!(
+            emacs.parse_event(plain(KeyCode::Char('u'))),
+            ReedlineEvent::Edit(vec![EditCommand::Undo])
+        );
+    }
+
+    #[test]
+    fn pending_sequences_are_cancelled() {
+        let mut emacs = Emacs::default();
+
+        emacs.parse_event(ctrl('x'));
+        assert_eq!(emacs.parse_event(ctrl('g')), ReedlineEvent::None);
+        assert_eq!(emacs.pending_sequence_timeout(), None);
+
+        emacs.parse_event(ctrl('x'));
+        assert_eq!(emacs.parse_event(plain(KeyCode::Esc)), ReedlineEvent::None);
+        assert_eq!(
+            emacs.parse_event(plain(KeyCode::Char('u'))),
+            ReedlineEvent::Edit(vec![EditCommand::InsertChar('u')])
+        );
+    }
+
+    #[test]
+    fn keys_not_continuing_a_sequence_are_handled_on_their_own() {
+        let mut keybindings = default_emacs_keybindings();
+        keybindings.add_binding(
+            KeyModifiers::CONTROL,
+            KeyCode::Char('x'),
+            ReedlineEvent::Repaint,
+        );
+        let mut emacs = Emacs::new(keybindings);
+
+        emacs.parse_event(ctrl('x'));
+        assert_eq!(
+            emacs.parse_event(ctrl('l')),
+            ReedlineEvent::Multiple(vec![ReedlineEvent::Repaint, ReedlineEvent::ClearScreen])
+        );
+
+        emacs.parse_event(ctrl('x'));
+        assert_eq!(emacs.resolve_pending_sequence(), ReedlineEvent::Repaint);
+        assert_eq!(emacs.pending_sequence_timeout(), None);
+    }
 }
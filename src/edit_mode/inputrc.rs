@@ -0,0 +1,837 @@
+use crate::{
+    default_emacs_keybindings, default_vi_insert_keybindings, default_vi_normal_keybindings,
+    edit_mode::{
+        keybindings::{edit_bind, KeyCombination, Keybindings},
+        EditMode,
+    },
+    EditCommand, Emacs, ReedlineEvent, Vi,
+};
+use crossterm::event::{KeyCode, KeyModifiers};
+use std::{fmt::Display, path::Path};
+
+/// The edit mode chosen with `set editing-mode` in an inputrc file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InputrcEditingMode {
+    /// `set editing-mode emacs`, the default
+    Emacs,
+    /// `set editing-mode vi`
+    Vi,
+}
+
+/// A line of an inputrc file that couldn't be applied
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InputrcIssue {
+    /// Number of the line, starting at 1
+    pub line: usize,
+    /// Text of the line
+    pub text: String,
+    /// Why the line was left out
+    pub reason: String,
+}
+
+impl Display for InputrcIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "line {}: {}: {}", self.line, self.reason, self.text)
+    }
+}
+
+/// Settings and key bindings of a readline init file, like the `~/.inputrc` of bash
+///
+/// Key bindings in the `"\C-x\C-r": function-name` and `Control-u: function-name` forms are
+/// added on top of the default bindings of the keymap they are in. Readline function names
+/// are translated to the matching [`ReedlineEvent`] or [`EditCommand`], macros like
+/// `"\C-xl": "ls -la\n"` insert their text and end their lines with
+/// [`ReedlineEvent::Enter`]. `set editing-mode`, `set keymap`,
+/// `set completion-ignore-case` and the `$if`, `$else` and `$endif` conditionals on `mode`,
+/// `term` and the application name are understood. Every other line is reported in
+/// [`Inputrc::issues`].
+///
+/// ```rust
+/// use reedline::{Inputrc, InputrcEditingMode, Reedline};
+///
+/// let inputrc = Inputrc::parse(
+///     "set editing-mode vi\n\
+///      $if mode=vi\n\
+///      \"\\C-l\": clear-screen\n\
+///      $endif\n",
+///     "reedline",
+/// );
+/// assert_eq!(inputrc.editing_mode, InputrcEditingMode::Vi);
+/// assert!(inputrc.issues.is_empty());
+///
+/// let line_editor = Reedline::create().with_edit_mode(inputrc.edit_mode());
+/// ```
+#[derive(Debug, Clone)]
+pub struct Inputrc {
+    /// The edit mode chosen with `set editing-mode`
+    pub editing_mode: InputrcEditingMode,
+    /// Whether `set completion-ignore-case on` asks completers to ignore the case, pass it to
+    /// [`crate::DefaultCompleter::set_ignore_case`] or apply it in your own [`crate::Completer`]
+    pub completion_ignore_case: bool,
+    /// The default emacs keybindings with the bindings of the `emacs` keymaps
+    pub emacs_keybindings: Keybindings,
+    /// The default vi insert keybindings with the bindings of the `vi-insert` keymap
+    pub vi_insert_keybindings: Keybindings,
+    /// The default vi normal keybindings with the bindings of the `vi`, `vi-command` and
+    /// `vi-move` keymaps
+    pub vi_normal_keybindings: Keybindings,
+    /// The lines that were left out
+    pub issues: Vec<InputrcIssue>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Keymap {
+    Emacs,
+    EmacsMeta,
+    EmacsCtlx,
+    ViInsert,
+    ViNormal,
+}
+
+// The state of one `$if` block
+struct Conditional {
+    // Whether the lines of the current branch are applied
+    active: bool,
+    // Whether the lines around the whole block are applied
+    enclosing_active: bool,
+}
+
+impl Inputrc {
+    /// Reads the `contents` of an inputrc file
+    ///
+    /// `$if` conditionals on a name compare it to the `application` name, `term` is compared
+    /// to the `TERM` environment variable.
+    pub fn parse(contents: &str, application: &str) -> Self {
+        let term = std::env::var("TERM").unwrap_or_default();
+        Self::parse_with_term(contents, application, &term)
+    }
+
+    /// Reads the inputrc file at `path`, see [`Inputrc::parse()`]
+    pub fn from_file(path: impl AsRef<Path>, application: &str) -> std::io::Result<Self> {
+        let contents = std::fs::read_to_string(path)?;
+        Ok(Self::parse(&contents, application))
+    }
+
+    /// The [`Emacs`] or [`Vi`] edit mode chosen with `set editing-mode`, using the keybindings
+    /// of the file
+    pub fn edit_mode(&self) -> Box<dyn EditMode> {
+        match self.editing_mode {
+            InputrcEditingMode::Emacs => Box::new(Emacs::new(self.emacs_keybindings.clone())),
+            InputrcEditingMode::Vi => Box::new(Vi::new(
+                self.vi_insert_keybindings.clone(),
+                self.vi_normal_keybindings.clone(),
+            )),
+        }
+    }
+
+    fn parse_with_term(contents: &str, application: &str, term: &str) -> Self {
+        let mut inputrc = Inputrc {
+            editing_mode: InputrcEditingMode::Emacs,
+            completion_ignore_case: false,
+            emacs_keybindings: default_emacs_keybindings(),
+            vi_insert_keybindings: default_vi_insert_keybindings(),
+            vi_normal_keybindings: default_vi_normal_keybindings(),
+            issues: Vec::new(),
+        };
+        let mut keymap = Keymap::Emacs;
+        let mut conditionals: Vec<Conditional> = Vec::new();
+
+        for (index, text) in contents.lines().enumerate() {
+            let line = text.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let active = conditionals.last().map_or(true, |c| c.active);
+
+            let result = if let Some(directive) = line.strip_prefix('$') {
+                let (name, argument) = split_word(directive);
+                match name {
+                    "if" => {
+                        let matches = inputrc.evaluate_condition(argument, application, term);
+                        conditionals.push(Conditional {
+                            active: active && matches == Ok(true),
+                            enclosing_active: active,
+                        });
+                        if active {
+                            matches.map(|_| ())
+                        } else {
+                            Ok(())
+                        }
+                    }
+                    "else" => match conditionals.last_mut() {
+                        Some(conditional) => {
+                            conditional.active =
+                                conditional.enclosing_active && !conditional.active;
+                            Ok(())
+                        }
+                        None => Err("`$else` without `$if`".to_string()),
+                    },
+                    "endif" => match conditionals.pop() {
+                        Some(_) => Ok(()),
+                        None => Err("`$endif` without `$if`".to_string()),
+                    },
+                    _ if !active => Ok(()),
+                    _ => Err(format!("unsupported directive `${name}`")),
+                }
+            } else if !active {
+                Ok(())
+            } else if let Some(setting) = line.strip_prefix("set ") {
+                inputrc.set_variable(setting.trim(), &mut keymap)
+            } else {
+                inputrc.bind(line, keymap)
+            };
+
+            if let Err(reason) = result {
+                inputrc.issues.push(InputrcIssue {
+                    line: index + 1,
+                    text: text.to_string(),
+                    reason,
+                });
+            }
+        }
+
+        inputrc
+    }
+
+    // `Ok(false)` for conditions that don't hold, `Err` for the ones that can't be checked
+    fn evaluate_condition(
+        &self,
+        condition: &str,
+        application: &str,
+        term: &str,
+    ) -> Result<bool, String> {
+        match condition.split_once('=') {
+            Some(("mode", mode)) => match mode.trim() {
+                "emacs" => Ok(self.editing_mode == InputrcEditingMode::Emacs),
+                "vi" => Ok(self.editing_mode == InputrcEditingMode::Vi),
+                mode => Err(format!("unknown editing mode `{mode}`")),
+            },
+            Some(("term", name)) => {
+                let name = name.trim();
+                Ok(term == name || term.split('-').next() == Some(name))
+            }
+            Some(_) => Err("unsupported condition".to_string()),
+            None if condition.contains(char::is_whitespace) || condition.is_empty() => {
+                Err("unsupported condition".to_string())
+            }
+            None => Ok(condition.eq_ignore_ascii_case(application)),
+        }
+    }
+
+    fn set_variable(&mut self, setting: &str, keymap: &mut Keymap) -> Result<(), String> {
+        let (name, value) = split_word(setting);
+        match name.to_ascii_lowercase().as_str() {
+            "editing-mode" => {
+                (self.editing_mode, *keymap) = match value {
+                    "emacs" => (InputrcEditingMode::Emacs, Keymap::Emacs),
+                    "vi" => (InputrcEditingMode::Vi, Keymap::ViInsert),
+                    _ => return Err(format!("unknown editing mode `{value}`")),
+                };
+            }
+            "keymap" => {
+                *keymap = match value {
+                    "emacs" | "emacs-standard" => Keymap::Emacs,
+                    "emacs-meta" => Keymap::EmacsMeta,
+                    "emacs-ctlx" => Keymap::EmacsCtlx,
+                    "vi-insert" => Keymap::ViInsert,
+                    "vi" | "vi-command" | "vi-move" => Keymap::ViNormal,
+                    _ => return Err(format!("unknown keymap `{value}`")),
+                };
+            }
+            "completion-ignore-case" => {
+                self.completion_ignore_case =
+                    value.is_empty() || value.eq_ignore_ascii_case("on") || value == "1";
+            }
+            _ => return Err(format!("unsupported variable `{name}`")),
+        }
+        Ok(())
+    }
+
+    fn bind(&mut self, line: &str, keymap: Keymap) -> Result<(), String> {
+        let (keys, action) = split_binding(line)?;
+        let mut sequence = if let Some(quoted) = keys.strip_prefix('"') {
+            parse_key_sequence(quoted.strip_suffix('"').unwrap_or(quoted))?
+        } else {
+            vec![parse_key_name(keys)?]
+        };
+        match keymap {
+            Keymap::EmacsMeta => {
+                if let Some(first) = sequence.first_mut() {
+                    first.modifier |= KeyModifiers::ALT;
+                }
+            }
+            Keymap::EmacsCtlx => sequence.insert(
+                0,
+                KeyCombination {
+                    modifier: KeyModifiers::CONTROL,
+                    key_code: KeyCode::Char('x'),
+                },
+            ),
+            _ => {}
+        }
+        if sequence.is_empty() {
+            return Err("empty key sequence".to_string());
+        }
+
+        let event = if let Some(quoted) = action.strip_prefix(['"', '\'']) {
+            let quote = &action[..1];
+            macro_event(&unescape(quoted.strip_suffix(quote).unwrap_or(quoted))?)?
+        } else {
+            let name = action.split_whitespace().next().unwrap_or_default();
+            readline_function(&name.to_ascii_lowercase())
+                .ok_or_else(|| format!("unsupported function `{name}`"))?
+        };
+
+        let keybindings = match keymap {
+            Keymap::Emacs | Keymap::EmacsMeta | Keymap::EmacsCtlx => &mut self.emacs_keybindings,
+            Keymap::ViInsert => &mut self.vi_insert_keybindings,
+            Keymap::ViNormal if sequence.len() > 1 => {
+                return Err("key sequences are not supported in the vi command keymap".to_string())
+            }
+            Keymap::ViNormal => &mut self.vi_normal_keybindings,
+        };
+        keybindings.add_sequence_binding(sequence, event);
+        Ok(())
+    }
+}
+
+// Splits off the first word, returning the trimmed rest
+fn split_word(text: &str) -> (&str, &str) {
+    match text.split_once(char::is_whitespace) {
+        Some((word, rest)) => (word, rest.trim()),
+        None => (text, ""),
+    }
+}
+
+// Splits a binding at the colon after its keys, keeping the quotes around key sequences
+fn split_binding(line: &str) -> Result<(&str, &str), String> {
+    let keys_end = if line.starts_with('"') {
+        let mut escaped = false;
+        line.char_indices()
+            .skip(1)
+            .find(|(_, c)| {
+                let closes = *c == '"' && !escaped;
+                escaped = *c == '\\' && !escaped;
+                closes
+            })
+            .map(|(index, _)| index + 1)
+            .ok_or_else(|| "unterminated key sequence".to_string())?
+    } else {
+        // The first character may be a colon, e.g. in `Meta-:`
+        line.char_indices()
+            .skip(1)
+            .find(|(_, c)| *c == ':')
+            .map_or(line.len(), |(index, _)| index)
+    };
+    let (keys, rest) = line.split_at(keys_end);
+    match rest.trim_start().strip_prefix(':') {
+        Some(action) if !action.trim().is_empty() => Ok((keys.trim(), action.trim())),
+        _ => Err("missing function or macro".to_string()),
+    }
+}
+
+// A character of a key sequence with the modifiers of its `\C-` and `\M-` prefixes
+type EscapedChar = (KeyModifiers, char);
+
+// Resolves the backslash escapes of a quoted key sequence or macro
+fn unescape_chars(text: &str) -> Result<Vec<EscapedChar>, String> {
+    let mut chars = text.chars().peekable();
+    let mut result = Vec::new();
+    let mut modifier = KeyModifiers::NONE;
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            result.push((modifier, c));
+            modifier = KeyModifiers::NONE;
+            continue;
+        }
+        let escaped = chars.next().ok_or("trailing backslash")?;
+        let c = match escaped {
+            'C' | 'M' if chars.peek() == Some(&'-') => {
+                chars.next();
+                modifier |= if escaped == 'C' {
+                    KeyModifiers::CONTROL
+                } else {
+                    KeyModifiers::ALT
+                };
+                continue;
+            }
+            'e' => '\x1b',
+            'a' => '\x07',
+            'b' => '\x08',
+            'd' => '\x7f',
+            'f' => '\x0c',
+            'n' => '\n',
+            'r' => '\r',
+            't' => '\t',
+            'v' => '\x0b',
+            '0'..='7' => {
+                let mut value = escaped.to_digit(8).unwrap_or_default();
+                for _ in 0..2 {
+                    match chars.peek().and_then(|c| c.to_digit(8)) {
+                        Some(digit) => {
+                            value = value * 8 + digit;
+                            chars.next();
+                        }
+                        None => break,
+                    }
+                }
+                char::from_u32(value).ok_or("invalid octal escape")?
+            }
+            'x' => {
+                let mut value = 0;
+                for _ in 0..2 {
+                    match chars.peek().and_then(|c| c.to_digit(16)) {
+                        Some(digit) => {
+                            value = value * 16 + digit;
+                            chars.next();
+                        }
+                        None => break,
+                    }
+                }
+                char::from_u32(value).ok_or("invalid hex escape")?
+            }
+            c => c,
+        };
+        result.push((modifier, c));
+        modifier = KeyModifiers::NONE;
+    }
+    Ok(result)
+}
+
+// The text of a macro
+fn unescape(text: &str) -> Result<String, String> {
+    unescape_chars(text)?
+        .into_iter()
+        .map(|(modifier, c)| {
+            if modifier == KeyModifiers::NONE {
+                Ok(c)
+            } else {
+                Err("modifiers are not supported in macros".to_string())
+            }
+        })
+        .collect()
+}
+
+// Typing the text of a macro, pressing enter for its line breaks
+fn macro_event(text: &str) -> Result<ReedlineEvent, String> {
+    let mut events = Vec::new();
+    for (index, line) in text.split(['\n', '\r']).enumerate() {
+        if index > 0 {
+            events.push(ReedlineEvent::Enter);
+        }
+        if !line.is_empty() {
+            events.push(edit_bind(EditCommand::InsertString(line.to_string())));
+        }
+    }
+    match events.len() {
+        0 => Err("empty macro".to_string()),
+        1 => Ok(events.remove(0)),
+        _ => Ok(ReedlineEvent::Multiple(events)),
+    }
+}
+
+// The keys terminals send as escape sequences, like `\e[A` for the up arrow
+fn escape_sequence_key(sequence: &str) -> Option<KeyCombination> {
+    let (modifier, key_code) = match sequence {
+        "[A" | "OA" => (KeyModifiers::NONE, KeyCode::Up),
+        "[B" | "OB" => (KeyModifiers::NONE, KeyCode::Down),
+        "[C" | "OC" => (KeyModifiers::NONE, KeyCode::Right),
+        "[D" | "OD" => (KeyModifiers::NONE, KeyCode::Left),
+        "[H" | "OH" | "[1~" => (KeyModifiers::NONE, KeyCode::Home),
+        "[F" | "OF" | "[4~" => (KeyModifiers::NONE, KeyCode::End),
+        "[2~" => (KeyModifiers::NONE, KeyCode::Insert),
+        "[3~" => (KeyModifiers::NONE, KeyCode::Delete),
+        "[5~" => (KeyModifiers::NONE, KeyCode::PageUp),
+        "[6~" => (KeyModifiers::NONE, KeyCode::PageDown),
+        "[Z" => (KeyModifiers::SHIFT, KeyCode::BackTab),
+        "[1;5A" => (KeyModifiers::CONTROL, KeyCode::Up),
+        "[1;5B" => (KeyModifiers::CONTROL, KeyCode::Down),
+        "[1;5C" => (KeyModifiers::CONTROL, KeyCode::Right),
+        "[1;5D" => (KeyModifiers::CONTROL, KeyCode::Left),
+        "[1;3C" => (KeyModifiers::ALT, KeyCode::Right),
+        "[1;3D" => (KeyModifiers::ALT, KeyCode::Left),
+        _ => return None,
+    };
+    Some(KeyCombination { modifier, key_code })
+}
+
+// The key combination crossterm reports for a character typed with `modifier`
+fn key_combination(modifier: KeyModifiers, c: char) -> KeyCombination {
+    let (control, key_code) = match c {
+        '\x1b' => (KeyModifiers::NONE, KeyCode::Esc),
+        '\t' => (KeyModifiers::NONE, KeyCode::Tab),
+        '\r' | '\n' => (KeyModifiers::NONE, KeyCode::Enter),
+        '\x7f' => (KeyModifiers::NONE, KeyCode::Backspace),
+        '\x00'..='\x1f' => (
+            KeyModifiers::CONTROL,
+            KeyCode::Char(char::from(c as u8 + 0x60)),
+        ),
+        _ => (KeyModifiers::NONE, KeyCode::Char(c)),
+    };
+    let modifier = modifier | control;
+    match (modifier.contains(KeyModifiers::CONTROL), key_code) {
+        (true, KeyCode::Char('?')) => KeyCombination {
+            modifier: modifier - KeyModifiers::CONTROL,
+            key_code: KeyCode::Backspace,
+        },
+        (true, KeyCode::Char('i')) => KeyCombination {
+            modifier: modifier - KeyModifiers::CONTROL,
+            key_code: KeyCode::Tab,
+        },
+        (true, KeyCode::Char('m')) => KeyCombination {
+            modifier: modifier - KeyModifiers::CONTROL,
+            key_code: KeyCode::Enter,
+        },
+        (true, KeyCode::Char('[')) => KeyCombination {
+            modifier: modifier - KeyModifiers::CONTROL,
+            key_code: KeyCode::Esc,
+        },
+        (_, KeyCode::Char(c)) if modifier == KeyModifiers::NONE && c.is_ascii_uppercase() => {
+            KeyCombination {
+                modifier: KeyModifiers::SHIFT,
+                key_code: KeyCode::Char(c.to_ascii_lowercase()),
+            }
+        }
+        (_, KeyCode::Char(c)) if modifier != KeyModifiers::NONE => KeyCombination {
+            modifier,
+            key_code: KeyCode::Char(c.to_ascii_lowercase()),
+        },
+        _ => KeyCombination { modifier, key_code },
+    }
+}
+
+// The keys of a quoted key sequence like `\C-x\C-r`
+fn parse_key_sequence(text: &str) -> Result<Vec<KeyCombination>, String> {
+    let chars = unescape_chars(text)?;
+    let mut sequence = Vec::new();
+    let mut index = 0;
+    while let Some(&(modifier, c)) = chars.get(index) {
+        index += 1;
+        let next = chars.get(index).copied();
+        match next {
+            // Escape starts the sequences of special keys and stands for meta before other keys
+            Some((next_modifier, '[' | 'O'))
+                if c == '\x1b' && modifier.is_empty() && next_modifier.is_empty() =>
+            {
+                let start = index;
+                let end = chars[start + 1..]
+                    .iter()
+                    .position(|(_, c)| ('\x40'..='\x7e').contains(c))
+                    .map(|position| start + 1 + position + 1)
+                    .ok_or("unterminated escape sequence")?;
+                let escape_sequence: String = chars[start..end].iter().map(|(_, c)| c).collect();
+                sequence.push(escape_sequence_key(&escape_sequence).ok_or_else(|| {
+                    format!("unsupported escape sequence `\\e{escape_sequence}`")
+                })?);
+                index = end;
+            }
+            Some((next_modifier, next_c)) if c == '\x1b' && modifier.is_empty() => {
+                sequence.push(key_combination(next_modifier | KeyModifiers::ALT, next_c));
+                index += 1;
+            }
+            _ => sequence.push(key_combination(modifier, c)),
+        }
+    }
+    Ok(sequence)
+}
+
+// A key name like `Control-u` or `Meta-Rubout`
+fn parse_key_name(name: &str) -> Result<KeyCombination, String> {
+    let mut modifier = KeyModifiers::NONE;
+    let mut rest = name;
+    loop {
+        let (prefix, tail) = match rest.split_once('-') {
+            Some((prefix, tail)) if !tail.is_empty() => (prefix, tail),
+            _ => break,
+        };
+        match prefix.to_ascii_lowercase().as_str() {
+            "c" | "control" => modifier |= KeyModifiers::CONTROL,
+            "m" | "meta" => modifier |= KeyModifiers::ALT,
+            _ => return Err(format!("unknown modifier `{prefix}`")),
+        }
+        rest = tail;
+    }
+    let c = match rest.to_ascii_lowercase().as_str() {
+        "del" | "rubout" => '\x7f',
+        "esc" | "escape" => '\x1b',
+        "lfd" | "newline" | "ret" | "return" => '\r',
+        "spc" | "space" => ' ',
+        "tab" => '\t',
+        _ => {
+            let mut chars = rest.chars();
+            match (chars.next(), chars.next()) {
+                (Some(c), None) => c,
+                _ => return Err(format!("unknown key name `{rest}`")),
+            }
+        }
+    };
+    Ok(key_combination(modifier, c))
+}
+
+// The event of a readline function
+fn readline_function(name: &str) -> Option<ReedlineEvent> {
+    use EditCommand as EC;
+
+    let event = match name {
+        "accept-line" => ReedlineEvent::Enter,
+        "beginning-of-line" => edit_bind(EC::MoveToLineStart { select: false }),
+        "end-of-line" => edit_bind(EC::MoveToLineEnd { select: false }),
+        "forward-char" => edit_bind(EC::MoveRight { select: false }),
+        "backward-char" => edit_bind(EC::MoveLeft { select: false }),
+        "forward-word" => edit_bind(EC::MoveWordRight { select: false }),
+        "backward-word" => edit_bind(EC::MoveWordLeft { select: false }),
+        "clear-screen" => ReedlineEvent::ClearScreen,
+        "previous-history" | "history-search-backward" => ReedlineEvent::PreviousHistory,
+        "next-history" | "history-search-forward" => ReedlineEvent::NextHistory,
+        "reverse-search-history" => ReedlineEvent::SearchHistory,
+        "end-of-file" => ReedlineEvent::CtrlD,
+        "delete-char" => edit_bind(EC::Delete),
+        "backward-delete-char" => edit_bind(EC::Backspace),
+        "tab-insert" => edit_bind(EC::InsertChar('\t')),
+        "transpose-chars" => edit_bind(EC::SwapGraphemes),
+        "transpose-words" => edit_bind(EC::SwapWords),
+        "upcase-word" => edit_bind(EC::UppercaseWord),
+        "downcase-word" => edit_bind(EC::LowercaseWord),
+        "capitalize-word" => edit_bind(EC::CapitalizeChar),
+        "kill-line" => edit_bind(EC::CutToLineEnd),
+        "backward-kill-line" | "unix-line-discard" => edit_bind(EC::CutFromLineStart),
+        "kill-whole-line" => edit_bind(EC::CutCurrentLine),
+        "kill-word" => edit_bind(EC::CutWordRight),
+        "backward-kill-word" => edit_bind(EC::CutWordLeft),
+        "unix-word-rubout" => edit_bind(EC::CutBigWordLeft),
+        "kill-region" => edit_bind(EC::CutSelection),
+        "copy-region-as-kill" => edit_bind(EC::CopySelection),
+        "yank" => edit_bind(EC::PasteCutBufferBefore),
+        "undo" => edit_bind(EC::Undo),
+        "complete" | "menu-complete" => ReedlineEvent::UntilFound(vec![
+            ReedlineEvent::Menu("completion_menu".to_string()),
+            ReedlineEvent::MenuNext,
+        ]),
+        "menu-complete-backward" => ReedlineEvent::MenuPrevious,
+        "start-kbd-macro" => ReedlineEvent::StartMacro,
+        "end-kbd-macro" => ReedlineEvent::EndMacro,
+        "call-last-kbd-macro" => ReedlineEvent::CallLastMacro(1),
+        "edit-and-execute-command" => ReedlineEvent::OpenEditor,
+        "abort" => ReedlineEvent::Esc,
+        "vi-movement-mode" => ReedlineEvent::ViChangeMode("normal".to_string()),
+        "vi-insertion-mode" => ReedlineEvent::ViChangeMode("insert".to_string()),
+        _ => return None,
+    };
+    Some(event)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{PromptEditMode, PromptViMode, ReedlineRawEvent};
+    use crossterm::event::{Event, KeyEvent};
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+
+    fn key(modifier: KeyModifiers, key_code: KeyCode) -> KeyCombination {
+        KeyCombination { modifier, key_code }
+    }
+
+    fn ctrl(c: char) -> KeyCombination {
+        key(KeyModifiers::CONTROL, KeyCode::Char(c))
+    }
+
+    #[rstest]
+    #[case(r"\C-x\C-r", vec![ctrl('x'), ctrl('r')])]
+    #[case(r"\M-.", vec![key(KeyModifiers::ALT, KeyCode::Char('.'))])]
+    #[case(r"\ef", vec![key(KeyModifiers::ALT, KeyCode::Char('f'))])]
+    #[case(r"\e[A", vec![key(KeyModifiers::NONE, KeyCode::Up)])]
+    #[case(r"\e[1;5C", vec![key(KeyModifiers::CONTROL, KeyCode::Right)])]
+    #[case(r"\C-?", vec![key(KeyModifiers::NONE, KeyCode::Backspace)])]
+    #[case(r"\t", vec![key(KeyModifiers::NONE, KeyCode::Tab)])]
+    #[case(r"\M-\C-h", vec![key(KeyModifiers::CONTROL | KeyModifiers::ALT, KeyCode::Char('h'))])]
+    #[case("\\x01", vec![ctrl('a')])]
+    #[case("aB", vec![
+        key(KeyModifiers::NONE, KeyCode::Char('a')),
+        key(KeyModifiers::SHIFT, KeyCode::Char('b')),
+    ])]
+    fn key_sequences_are_parsed(#[case] text: &str, #[case] expected: Vec<KeyCombination>) {
+        assert_eq!(parse_key_sequence(text), Ok(expected));
+    }
+
+    #[rstest]
+    #[case("Control-u", ctrl('u'))]
+    #[case("C-k", ctrl('k'))]
+    #[case("Meta-Rubout", key(KeyModifiers::ALT, KeyCode::Backspace))]
+    #[case("M-DEL", key(KeyModifiers::ALT, KeyCode::Backspace))]
+    #[case("TAB", key(KeyModifiers::NONE, KeyCode::Tab))]
+    #[case("-", key(KeyModifiers::NONE, KeyCode::Char('-')))]
+    fn key_names_are_parsed(#[case] name: &str, #[case] expected: KeyCombination) {
+        assert_eq!(parse_key_name(name), Ok(expected));
+    }
+
+    #[test]
+    fn bindings_are_added_to_their_keymaps() {
+        let inputrc = Inputrc::parse_with_term(
+            r#"
+# Comments and empty lines are skipped
+"\C-x\C-r": reverse-search-history
+Control-t: kill-whole-line
+"\C-xl": "ls -la\n"
+set keymap vi-insert
+"jk": vi-movement-mode
+set keymap vi-command
+"K": clear-screen
+"#,
+            "bash",
+            "xterm",
+        );
+
+        assert_eq!(inputrc.issues, vec![]);
+        assert_eq!(
+            inputrc
+                .emacs_keybindings
+                .find_sequence_binding(&[ctrl('x'), ctrl('r')]),
+            Some(ReedlineEvent::SearchHistory)
+        );
+        assert_eq!(
+            inputrc
+                .emacs_keybindings
+                .find_binding(KeyModifiers::CONTROL, KeyCode::Char('t')),
+            Some(edit_bind(EditCommand::CutCurrentLine))
+        );
+        assert_eq!(
+            inputrc
+                .emacs_keybindings
+                .find_sequence_binding(&[ctrl('x'), key(KeyModifiers::NONE, KeyCode::Char('l'))]),
+            Some(ReedlineEvent::Multiple(vec![
+                edit_bind(EditCommand::InsertString("ls -la".to_string())),
+                ReedlineEvent::Enter,
+            ]))
+        );
+        assert_eq!(
+            inputrc.vi_insert_keybindings.find_sequence_binding(&[
+                key(KeyModifiers::NONE, KeyCode::Char('j')),
+                key(KeyModifiers::NONE, KeyCode::Char('k')),
+            ]),
+            Some(ReedlineEvent::ViChangeMode("normal".to_string()))
+        );
+        assert_eq!(
+            inputrc
+                .vi_normal_keybindings
+                .find_binding(KeyModifiers::SHIFT, KeyCode::Char('k')),
+            Some(ReedlineEvent::ClearScreen)
+        );
+    }
+
+    #[test]
+    fn vi_mode_functions_switch_the_mode() {
+        let inputrc = Inputrc::parse_with_term(
+            r#"
+set editing-mode vi
+set keymap vi-insert
+"jk": vi-movement-mode
+set keymap vi-command
+Control-o: vi-insertion-mode
+"#,
+            "bash",
+            "xterm",
+        );
+        let mut vi = inputrc.edit_mode();
+        let mut press = |modifiers, code| {
+            let event = Event::Key(KeyEvent::new(code, modifiers));
+            vi.parse_event(ReedlineRawEvent::try_from(event).unwrap());
+            vi.edit_mode()
+        };
+
+        assert_eq!(
+            press(KeyModifiers::NONE, KeyCode::Char('j')),
+            PromptEditMode::Vi(PromptViMode::Insert)
+        );
+        assert_eq!(
+            press(KeyModifiers::NONE, KeyCode::Char('k')),
+            PromptEditMode::Vi(PromptViMode::Normal)
+        );
+        assert_eq!(
+            press(KeyModifiers::CONTROL, KeyCode::Char('o')),
+            PromptEditMode::Vi(PromptViMode::Insert)
+        );
+    }
+
+    #[test]
+    fn settings_and_conditionals_are_applied() {
+        let inputrc = Inputrc::parse_with_term(
+            r#"
+set editing-mode vi
+set completion-ignore-case on
+$if mode=emacs
+"\C-l": undo
+$else
+"\C-l": clear-screen
+$endif
+$if term=xterm
+$if Bash
+"\C-o": undo
+$endif
+"\C-p": previous-history
+$endif
+"#,
+            "bash",
+            "xterm-256color",
+        );
+
+        assert_eq!(inputrc.issues, vec![]);
+        assert_eq!(inputrc.editing_mode, InputrcEditingMode::Vi);
+        assert!(inputrc.completion_ignore_case);
+        assert_eq!(
+            inputrc
+                .vi_insert_keybindings
+                .find_binding(KeyModifiers::CONTROL, KeyCode::Char('l')),
+            Some(ReedlineEvent::ClearScreen)
+        );
+        assert_eq!(
+            inputrc
+                .vi_insert_keybindings
+                .find_binding(KeyModifiers::CONTROL, KeyCode::Char('o')),
+            Some(edit_bind(EditCommand::Undo))
+        );
+        assert_eq!(
+            inputrc
+                .vi_insert_keybindings
+                .find_binding(KeyModifiers::CONTROL, KeyCode::Char('p')),
+            Some(ReedlineEvent::PreviousHistory)
+        );
+    }
+
+    #[test]
+    fn unsupported_lines_are_reported() {
+        let inputrc = Inputrc::parse_with_term(
+            "set bell-style none\n\
+             \"\\C-q\": quoted-insert\n\
+             $include /etc/inputrc\n\
+             \"\\C-x\n\
+             $if mode=emacs\n\
+             set show-all-if-ambiguous on\n\
+             $endif\n\
+             $endif\n",
+            "bash",
+            "xterm",
+        );
+
+        let issues: Vec<(usize, &str)> = inputrc
+            .issues
+            .iter()
+            .map(|issue| (issue.line, issue.reason.as_str()))
+            .collect();
+        assert_eq!(
+            issues,
+            vec![
+                (1, "unsupported variable `bell-style`"),
+                (2, "unsupported function `quoted-insert`"),
+                (3, "unsupported directive `$include`"),
+                (4, "unterminated key sequence"),
+                (6, "unsupported variable `show-all-if-ambiguous`"),
+                (8, "`$endif` without `$if`"),
+            ]
+        );
+        assert_eq!(
+            inputrc.issues[0].to_string(),
+            "line 1: unsupported variable `bell-style`: set bell-style none"
+        );
+    }
+}
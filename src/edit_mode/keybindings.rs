@@ -5,17 +5,46 @@ use {
     std::collections::HashMap,
 };
 
+/// A key together with the modifiers held while pressing it
 #[derive(Serialize, Deserialize, Clone, PartialEq, Eq, Hash, Debug)]
 pub struct KeyCombination {
+    /// The held modifiers
     pub modifier: KeyModifiers,
+    /// The pressed key
     pub key_code: KeyCode,
 }
 
+impl KeyCombination {
+    /// The combination of a key event as it is looked up in the bindings of an insert mode
+    ///
+    /// Characters typed with modifiers are bound by their lowercase letter.
+    pub(crate) fn from_key_event(modifier: KeyModifiers, key_code: KeyCode) -> Self {
+        // Note. The modifier can also be a combination of modifiers, for
+        // example:
+        //     KeyModifiers::CONTROL | KeyModifiers::ALT
+        //     KeyModifiers::CONTROL | KeyModifiers::ALT | KeyModifiers::SHIFT
+        //
+        // Mixed modifiers are used by non american keyboards that have extra
+        // keys like 'alt gr'. Keep this in mind if in the future there are
+        // cases where an event is not being captured
+        let key_code = match (modifier, key_code) {
+            (KeyModifiers::NONE, key_code) => key_code,
+            (_, KeyCode::Char(c)) => KeyCode::Char(c.to_ascii_lowercase()),
+            (_, key_code) => key_code,
+        };
+        KeyCombination { modifier, key_code }
+    }
+}
+
 /// Main definition of editor keybindings
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct Keybindings {
     /// Defines a keybinding for a reedline event
     pub bindings: HashMap<KeyCombination, ReedlineEvent>,
+    /// Defines a binding of a sequence of several key combinations, e.g. `Ctrl-x Ctrl-e`,
+    /// for a reedline event
+    #[serde(default)]
+    pub sequences: HashMap<Vec<KeyCombination>, ReedlineEvent>,
 }
 
 impl Default for Keybindings {
@@ -29,6 +58,7 @@ impl Keybindings {
     pub fn new() -> Self {
         Self {
             bindings: HashMap::new(),
+            sequences: HashMap::new(),
         }
     }
 
@@ -81,6 +111,177 @@ impl Keybindings {
     pub fn get_keybindings(&self) -> &HashMap<KeyCombination, ReedlineEvent> {
         &self.bindings
     }
+
+    /// Adds a binding for a sequence of key combinations typed one after the other
+    ///
+    /// A sequence of a single key combination is added as a plain keybinding, empty sequences
+    /// are ignored. When a sequence starts with a key combination that is bound on its own as
+    /// well, the edit mode waits for the rest of the sequence until its timeout expires.
+    ///
+    /// # Panics
+    ///
+    /// If `command` is an empty [`ReedlineEvent::UntilFound`]
+    pub fn add_sequence_binding(&mut self, sequence: Vec<KeyCombination>, command: ReedlineEvent) {
+        match sequence.as_slice() {
+            [] => {}
+            [key_combo] => self.add_binding(key_combo.modifier, key_combo.key_code, command),
+            _ => {
+                if let ReedlineEvent::UntilFound(subcommands) = &command {
+                    assert!(
+                        !subcommands.is_empty(),
+                        "UntilFound should contain a series of potential events to handle"
+                    );
+                }
+                self.sequences.insert(sequence, command);
+            }
+        }
+    }
+
+    /// Find the binding of a sequence of key combinations
+    pub fn find_sequence_binding(&self, sequence: &[KeyCombination]) -> Option<ReedlineEvent> {
+        match sequence {
+            [key_combo] => self.find_binding(key_combo.modifier, key_combo.key_code),
+            _ => self.sequences.get(sequence).cloned(),
+        }
+    }
+
+    /// Remove the binding of a sequence of key combinations
+    ///
+    /// Returns `Some(ReedlineEvent)` if the sequence was previously bound to a particular [`ReedlineEvent`]
+    pub fn remove_sequence_binding(
+        &mut self,
+        sequence: &[KeyCombination],
+    ) -> Option<ReedlineEvent> {
+        match sequence {
+            [key_combo] => self.remove_binding(key_combo.modifier, key_combo.key_code),
+            _ => self.sequences.remove(sequence),
+        }
+    }
+
+    /// Get assigned bindings of sequences of key combinations
+    pub fn get_sequence_bindings(&self) -> &HashMap<Vec<KeyCombination>, ReedlineEvent> {
+        &self.sequences
+    }
+
+    fn is_sequence_prefix(&self, keys: &[KeyCombination]) -> bool {
+        self.sequences
+            .keys()
+            .any(|sequence| sequence.len() > keys.len() && sequence.starts_with(keys))
+    }
+
+    /// The event of a single key combination typed in an insert mode: its binding or, for
+    /// characters typed without modifiers other than shift or `AltGr`, inserting the character
+    pub(crate) fn find_binding_or_insert(&self, key_combo: &KeyCombination) -> ReedlineEvent {
+        let KeyCombination { modifier, key_code } = key_combo.clone();
+        match key_code {
+            KeyCode::Char(c) => self.find_binding(modifier, key_code).unwrap_or_else(|| {
+                // Mixed modifiers are used by non american keyboards that have extra
+                // keys like 'alt gr'
+                if modifier == KeyModifiers::NONE
+                    || modifier == KeyModifiers::SHIFT
+                    || modifier == KeyModifiers::CONTROL | KeyModifiers::ALT
+                    || modifier == KeyModifiers::CONTROL | KeyModifiers::ALT | KeyModifiers::SHIFT
+                {
+                    ReedlineEvent::Edit(vec![EditCommand::InsertChar(
+                        if modifier == KeyModifiers::SHIFT {
+                            c.to_ascii_uppercase()
+                        } else {
+                            c
+                        },
+                    )])
+                } else {
+                    ReedlineEvent::None
+                }
+            }),
+            _ => self
+                .find_binding(modifier, key_code)
+                .unwrap_or(ReedlineEvent::None),
+        }
+    }
+
+    /// Feeds the next key combination into the `pending` keys of a sequence
+    ///
+    /// Returns [`ReedlineEvent::None`] while the pending keys start a longer sequence. `Esc`
+    /// and `Ctrl-g` cancel a pending sequence. A key that doesn't continue the pending sequence
+    /// resolves the keys before it and is then handled on its own.
+    pub(crate) fn parse_sequence_key(
+        &self,
+        pending: &mut Vec<KeyCombination>,
+        key_combo: KeyCombination,
+    ) -> ReedlineEvent {
+        pending.push(key_combo.clone());
+        if self.is_sequence_prefix(pending) {
+            return ReedlineEvent::None;
+        }
+        if pending.len() > 1 {
+            if let Some(event) = self.sequences.get(pending.as_slice()).cloned() {
+                pending.clear();
+                return event;
+            }
+        }
+
+        pending.pop();
+        if pending.is_empty() {
+            return self.find_binding_or_insert(&key_combo);
+        }
+        let cancels = key_combo.modifier == KeyModifiers::NONE
+            && key_combo.key_code == KeyCode::Esc
+            || key_combo.modifier == KeyModifiers::CONTROL
+                && key_combo.key_code == KeyCode::Char('g');
+        if cancels {
+            pending.clear();
+            return ReedlineEvent::None;
+        }
+
+        let resolved = self.resolve_sequence(pending);
+        let next = self.parse_sequence_key(pending, key_combo);
+        combine_events(vec![resolved, next])
+    }
+
+    /// Resolves the `pending` keys of a sequence that wasn't completed, e.g. after a timeout
+    ///
+    /// The longest bound sequences at the start of the keys are used, the other keys are
+    /// handled on their own.
+    pub(crate) fn resolve_sequence(&self, pending: &mut Vec<KeyCombination>) -> ReedlineEvent {
+        let keys = std::mem::take(pending);
+        let mut rest = keys.as_slice();
+        let mut events = Vec::new();
+        while let Some(first) = rest.first() {
+            let (len, event) = (2..=rest.len())
+                .rev()
+                .find_map(|len| {
+                    self.sequences
+                        .get(&rest[..len])
+                        .map(|event| (len, event.clone()))
+                })
+                .unwrap_or_else(|| (1, self.find_binding_or_insert(first)));
+            events.push(event);
+            rest = &rest[len..];
+        }
+        combine_events(events)
+    }
+}
+
+/// Combines the events of several keys into one, merging the edits following each other
+fn combine_events(events: Vec<ReedlineEvent>) -> ReedlineEvent {
+    let mut combined: Vec<ReedlineEvent> = Vec::new();
+    for event in events {
+        match (combined.last_mut(), event) {
+            (_, ReedlineEvent::None) => {}
+            (_, ReedlineEvent::Multiple(events)) => {
+                combined.extend(events);
+            }
+            (Some(ReedlineEvent::Edit(commands)), ReedlineEvent::Edit(next)) => {
+                commands.extend(next);
+            }
+            (_, event) => combined.push(event),
+        }
+    }
+    match combined.len() {
+        0 => ReedlineEvent::None,
+        1 => combined.remove(0),
+        _ => ReedlineEvent::Multiple(combined),
+    }
 }
 
 pub fn edit_bind(command: EditCommand) -> ReedlineEvent {
@@ -1,11 +1,14 @@
 mod base;
 mod cursors;
 mod emacs;
+mod inputrc;
 mod keybindings;
 mod vi;
 
+pub(crate) use base::parse_mouse_event;
 pub use base::EditMode;
 pub use cursors::CursorConfig;
 pub use emacs::{default_emacs_keybindings, Emacs};
-pub use keybindings::Keybindings;
+pub use inputrc::{Inputrc, InputrcEditingMode, InputrcIssue};
+pub use keybindings::{KeyCombination, Keybindings};
 pub use vi::{default_vi_insert_keybindings, default_vi_normal_keybindings, Vi};
@@ -9,19 +9,7 @@ where
     match input.peek() {
         Some('d') => {
             let _ = input.next();
-            if let Some('i') = input.peek() {
-                let _ = input.next();
-                match input.next() {
-                    Some(c)
-                        if is_valid_change_inside_left(c) || is_valid_change_inside_right(c) =>
-                    {
-                        Some(Command::DeleteInside(*c))
-                    }
-                    _ => None,
-                }
-            } else {
-                Some(Command::Delete)
-            }
+            Some(Command::Delete)
         }
         Some('p') => {
             let _ = input.next();
@@ -45,19 +33,7 @@ where
         }
         Some('c') => {
             let _ = input.next();
-            if let Some('i') = input.peek() {
-                let _ = input.next();
-                match input.next() {
-                    Some(c)
-                        if is_valid_change_inside_left(c) || is_valid_change_inside_right(c) =>
-                    {
-                        Some(Command::ChangeInside(*c))
-                    }
-                    _ => None,
-                }
-            } else {
-                Some(Command::Change)
-            }
+            Some(Command::Change)
         }
         Some('x') => {
             let _ = input.next();
@@ -86,6 +62,14 @@ where
             let _ = input.next();
             Some(Command::DeleteToEnd)
         }
+        Some('y') => {
+            let _ = input.next();
+            Some(Command::Yank)
+        }
+        Some('Y') => {
+            let _ = input.next();
+            Some(Command::YankLine)
+        }
         Some('I') => {
             let _ = input.next();
             Some(Command::PrependToStart)
@@ -106,6 +90,26 @@ where
             let _ = input.next();
             Some(Command::RepeatLastAction)
         }
+        Some('>') => {
+            let _ = input.next();
+            Some(Command::Indent)
+        }
+        Some('<') => {
+            let _ = input.next();
+            Some(Command::Dedent)
+        }
+        Some('J') => {
+            let _ = input.next();
+            Some(Command::JoinLines)
+        }
+        Some('o') => {
+            let _ = input.next();
+            Some(Command::OpenLineBelow)
+        }
+        Some('O') => {
+            let _ = input.next();
+            Some(Command::OpenLineAbove)
+        }
         _ => None,
     }
 }
@@ -131,8 +135,13 @@ pub enum Command {
     HistorySearch,
     Switchcase,
     RepeatLastAction,
-    ChangeInside(char),
-    DeleteInside(char),
+    Yank,
+    YankLine,
+    Indent,
+    Dedent,
+    JoinLines,
+    OpenLineBelow,
+    OpenLineAbove,
 }
 
 impl Command {
@@ -140,12 +149,18 @@ impl Command {
         match self {
             Command::Delete => Some('d'),
             Command::Change => Some('c'),
+            Command::Yank => Some('y'),
+            Command::Indent => Some('>'),
+            Command::Dedent => Some('<'),
             _ => None,
         }
     }
 
     pub fn requires_motion(&self) -> bool {
-        matches!(self, Command::Delete | Command::Change)
+        matches!(
+            self,
+            Command::Delete | Command::Change | Command::Yank | Command::Indent | Command::Dedent
+        )
     }
 
     pub fn to_reedline(&self, vi_state: &mut Vi) -> Vec<ReedlineOption> {
@@ -159,6 +174,7 @@ impl Command {
             Self::Undo => vec![ReedlineOption::Edit(EditCommand::Undo)],
             Self::ChangeToLineEnd => vec![ReedlineOption::Edit(EditCommand::ClearToLineEnd)],
             Self::DeleteToEnd => vec![ReedlineOption::Edit(EditCommand::CutToLineEnd)],
+            Self::YankLine => vec![ReedlineOption::Edit(EditCommand::CopyCurrentLine)],
             Self::AppendToEnd => vec![ReedlineOption::Edit(EditCommand::MoveToLineEnd {
                 select: false,
             })],
@@ -174,46 +190,35 @@ impl Command {
             Self::HistorySearch => vec![ReedlineOption::Event(ReedlineEvent::SearchHistory)],
             Self::Switchcase => vec![ReedlineOption::Edit(EditCommand::SwitchcaseChar)],
             // Whenever a motion is required to finish the command we must be in visual mode
-            Self::Delete | Self::Change => vec![ReedlineOption::Edit(EditCommand::CutSelection)],
+            Self::Delete => vec![ReedlineOption::Edit(EditCommand::CutSelection)],
+            Self::Change => vec![ReedlineOption::Edit(EditCommand::ChangeSelection)],
+            Self::Yank => vec![ReedlineOption::Edit(EditCommand::CopySelection)],
+            // Leaving the visual mode ends the selection
+            Self::Indent => vec![
+                ReedlineOption::Edit(EditCommand::IndentLines),
+                ReedlineOption::Edit(EditCommand::MoveToLineStart { select: false }),
+            ],
+            Self::Dedent => vec![
+                ReedlineOption::Edit(EditCommand::DedentLines),
+                ReedlineOption::Edit(EditCommand::MoveToLineStart { select: false }),
+            ],
+            Self::JoinLines => vec![ReedlineOption::Edit(EditCommand::JoinLines)],
+            Self::OpenLineBelow => vec![
+                ReedlineOption::Edit(EditCommand::MoveToLineEnd { select: false }),
+                ReedlineOption::Edit(EditCommand::InsertNewline),
+                ReedlineOption::Event(ReedlineEvent::Repaint),
+            ],
+            Self::OpenLineAbove => vec![
+                ReedlineOption::Edit(EditCommand::MoveToLineStart { select: false }),
+                ReedlineOption::Edit(EditCommand::InsertString("\n".to_string())),
+                ReedlineOption::Edit(EditCommand::MoveLineUp { select: false }),
+                ReedlineOption::Event(ReedlineEvent::Repaint),
+            ],
             Self::Incomplete => vec![ReedlineOption::Incomplete],
             Self::RepeatLastAction => match &vi_state.previous {
                 Some(event) => vec![ReedlineOption::Event(event.clone())],
                 None => vec![],
             },
-            Self::ChangeInside(left) if is_valid_change_inside_left(left) => {
-                let right = bracket_for(left);
-                vec![
-                    ReedlineOption::Edit(EditCommand::CutLeftBefore(*left)),
-                    ReedlineOption::Edit(EditCommand::CutRightBefore(right)),
-                ]
-            }
-            Self::ChangeInside(right) if is_valid_change_inside_right(right) => {
-                let left = bracket_for(right);
-                vec![
-                    ReedlineOption::Edit(EditCommand::CutLeftBefore(left)),
-                    ReedlineOption::Edit(EditCommand::CutRightBefore(*right)),
-                ]
-            }
-            Self::ChangeInside(_) => {
-                vec![]
-            }
-            Self::DeleteInside(left) if is_valid_change_inside_left(left) => {
-                let right = bracket_for(left);
-                vec![
-                    ReedlineOption::Edit(EditCommand::CutLeftBefore(*left)),
-                    ReedlineOption::Edit(EditCommand::CutRightBefore(right)),
-                ]
-            }
-            Self::DeleteInside(right) if is_valid_change_inside_right(right) => {
-                let left = bracket_for(right);
-                vec![
-                    ReedlineOption::Edit(EditCommand::CutLeftBefore(left)),
-                    ReedlineOption::Edit(EditCommand::CutRightBefore(*right)),
-                ]
-            }
-            Self::DeleteInside(_) => {
-                vec![]
-            }
         }
     }
 
@@ -259,8 +264,7 @@ impl Command {
                 Motion::Start => Some(vec![ReedlineOption::Edit(EditCommand::CutFromLineStart)]),
                 Motion::Left => Some(vec![ReedlineOption::Edit(EditCommand::Backspace)]),
                 Motion::Right => Some(vec![ReedlineOption::Edit(EditCommand::Delete)]),
-                Motion::Up => None,
-                Motion::Down => None,
+                Motion::Up | Motion::Down | Motion::FirstLine | Motion::LastLine => None,
                 Motion::ReplayCharSearch => vi_state
                     .last_char_search
                     .as_ref()
@@ -269,6 +273,11 @@ impl Command {
                     .last_char_search
                     .as_ref()
                     .map(|char_search| vec![ReedlineOption::Edit(char_search.reverse().to_cut())]),
+                Motion::TextObject(text_object) => {
+                    Some(vec![ReedlineOption::Edit(EditCommand::CutTextObject {
+                        text_object: *text_object,
+                    })])
+                }
             },
             Self::Change => {
                 let op = match motion {
@@ -314,8 +323,7 @@ impl Command {
                     }
                     Motion::Left => Some(vec![ReedlineOption::Edit(EditCommand::Backspace)]),
                     Motion::Right => Some(vec![ReedlineOption::Edit(EditCommand::Delete)]),
-                    Motion::Up => None,
-                    Motion::Down => None,
+                    Motion::Up | Motion::Down | Motion::FirstLine | Motion::LastLine => None,
                     Motion::ReplayCharSearch => vi_state
                         .last_char_search
                         .as_ref()
@@ -325,6 +333,11 @@ impl Command {
                             vec![ReedlineOption::Edit(char_search.reverse().to_cut())]
                         })
                     }
+                    Motion::TextObject(text_object) => {
+                        Some(vec![ReedlineOption::Edit(EditCommand::CutTextObject {
+                            text_object: *text_object,
+                        })])
+                    }
                 };
                 // Semihack: Append `Repaint` to ensure the mode change gets displayed
                 op.map(|mut vec| {
@@ -332,29 +345,66 @@ impl Command {
                     vec
                 })
             }
+            Self::Yank => match motion {
+                Motion::End => Some(vec![ReedlineOption::Edit(EditCommand::CopyToLineEnd)]),
+                Motion::Line => Some(vec![ReedlineOption::Edit(EditCommand::CopyCurrentLine)]),
+                Motion::NextWord => {
+                    Some(vec![ReedlineOption::Edit(EditCommand::CopyWordRightToNext)])
+                }
+                Motion::NextBigWord => Some(vec![ReedlineOption::Edit(
+                    EditCommand::CopyBigWordRightToNext,
+                )]),
+                Motion::NextWordEnd => Some(vec![ReedlineOption::Edit(EditCommand::CopyWordRight)]),
+                Motion::NextBigWordEnd => {
+                    Some(vec![ReedlineOption::Edit(EditCommand::CopyBigWordRight)])
+                }
+                Motion::PreviousWord => Some(vec![ReedlineOption::Edit(EditCommand::CopyWordLeft)]),
+                Motion::PreviousBigWord => {
+                    Some(vec![ReedlineOption::Edit(EditCommand::CopyBigWordLeft)])
+                }
+                Motion::RightUntil(c) => {
+                    vi_state.last_char_search = Some(ViCharSearch::ToRight(*c));
+                    Some(vec![ReedlineOption::Edit(EditCommand::CopyRightUntil(*c))])
+                }
+                Motion::RightBefore(c) => {
+                    vi_state.last_char_search = Some(ViCharSearch::TillRight(*c));
+                    Some(vec![ReedlineOption::Edit(EditCommand::CopyRightBefore(*c))])
+                }
+                Motion::LeftUntil(c) => {
+                    vi_state.last_char_search = Some(ViCharSearch::ToLeft(*c));
+                    Some(vec![ReedlineOption::Edit(EditCommand::CopyLeftUntil(*c))])
+                }
+                Motion::LeftBefore(c) => {
+                    vi_state.last_char_search = Some(ViCharSearch::TillLeft(*c));
+                    Some(vec![ReedlineOption::Edit(EditCommand::CopyLeftBefore(*c))])
+                }
+                Motion::Start => Some(vec![ReedlineOption::Edit(EditCommand::CopyFromLineStart)]),
+                Motion::Left => Some(vec![ReedlineOption::Edit(EditCommand::CopyLeft)]),
+                Motion::Right => Some(vec![ReedlineOption::Edit(EditCommand::CopyRight)]),
+                Motion::Up | Motion::Down | Motion::FirstLine | Motion::LastLine => None,
+                Motion::ReplayCharSearch => vi_state
+                    .last_char_search
+                    .as_ref()
+                    .map(|char_search| vec![ReedlineOption::Edit(char_search.to_copy())]),
+                Motion::ReverseCharSearch => vi_state
+                    .last_char_search
+                    .as_ref()
+                    .map(|char_search| vec![ReedlineOption::Edit(char_search.reverse().to_copy())]),
+                Motion::TextObject(text_object) => {
+                    Some(vec![ReedlineOption::Edit(EditCommand::CopyTextObject {
+                        text_object: *text_object,
+                    })])
+                }
+            },
+            Self::Indent => match motion {
+                Motion::Line => Some(vec![ReedlineOption::Edit(EditCommand::IndentLines)]),
+                _ => None,
+            },
+            Self::Dedent => match motion {
+                Motion::Line => Some(vec![ReedlineOption::Edit(EditCommand::DedentLines)]),
+                _ => None,
+            },
             _ => None,
         }
     }
 }
-
-fn bracket_for(c: &char) -> char {
-    match *c {
-        '(' => ')',
-        '[' => ']',
-        '{' => '}',
-        '<' => '>',
-        ')' => '(',
-        ']' => '[',
-        '}' => '{',
-        '>' => '<',
-        _ => *c,
-    }
-}
-
-pub(crate) fn is_valid_change_inside_left(c: &char) -> bool {
-    matches!(c, '(' | '[' | '{' | '"' | '\'' | '`' | '<')
-}
-
-pub(crate) fn is_valid_change_inside_right(c: &char) -> bool {
-    matches!(c, ')' | ']' | '}' | '"' | '\'' | '`' | '>')
-}
@@ -7,10 +7,14 @@ use crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers};
 pub use vi_keybindings::{default_vi_insert_keybindings, default_vi_normal_keybindings};
 
 use self::motion::ViCharSearch;
+use std::time::Duration;
 
-use super::EditMode;
+use super::{emacs::DEFAULT_SEQUENCE_TIMEOUT, parse_mouse_event, EditMode};
 use crate::{
-    edit_mode::{keybindings::Keybindings, vi::parser::parse},
+    edit_mode::{
+        keybindings::{KeyCombination, Keybindings},
+        vi::parser::parse,
+    },
     enums::{EditCommand, ReedlineEvent, ReedlineRawEvent},
     PromptEditMode, PromptViMode,
 };
@@ -20,6 +24,13 @@ enum ViMode {
     Normal,
     Insert,
     Visual,
+    VisualLine,
+}
+
+impl ViMode {
+    fn is_visual(self) -> bool {
+        matches!(self, ViMode::Visual | ViMode::VisualLine)
+    }
 }
 
 /// This parses incoming input `Event`s like a Vi-Style editor
@@ -31,6 +42,9 @@ pub struct Vi {
     previous: Option<ReedlineEvent>,
     // last f, F, t, T motion for ; and ,
     last_char_search: Option<ViCharSearch>,
+    // keys of an insert mode sequence typed so far
+    pending_keys: Vec<KeyCombination>,
+    sequence_timeout: Duration,
 }
 
 impl Default for Vi {
@@ -42,6 +56,8 @@ impl Default for Vi {
             mode: ViMode::Insert,
             previous: None,
             last_char_search: None,
+            pending_keys: Vec::new(),
+            sequence_timeout: DEFAULT_SEQUENCE_TIMEOUT,
         }
     }
 }
@@ -55,6 +71,51 @@ impl Vi {
             ..Default::default()
         }
     }
+
+    /// A builder that sets how long to wait for the next key of an insert mode sequence before
+    /// the keys typed so far are handled on their own, one second by default
+    #[must_use]
+    pub fn with_sequence_timeout(mut self, timeout: Duration) -> Self {
+        self.sequence_timeout = timeout;
+        self
+    }
+
+    /// Applies the mode changes of an event, switching to normal mode for
+    /// [`ReedlineEvent::Esc`] like the Esc key does, e.g. for an insert mode sequence bound to
+    /// it, and to the mode of [`ReedlineEvent::ViChangeMode`]
+    fn apply_mode_changes(&mut self, event: ReedlineEvent) -> ReedlineEvent {
+        let changes_mode = |event: &ReedlineEvent| {
+            matches!(event, ReedlineEvent::Esc | ReedlineEvent::ViChangeMode(_))
+        };
+        let events = match event {
+            ReedlineEvent::Multiple(events) if events.iter().any(changes_mode) => events,
+            event if changes_mode(&event) => vec![event],
+            event => return event,
+        };
+
+        let mut changed = Vec::with_capacity(events.len() + 1);
+        for event in events {
+            match event {
+                ReedlineEvent::Esc => {
+                    self.mode = ViMode::Normal;
+                    changed.push(ReedlineEvent::Esc);
+                }
+                ReedlineEvent::ViChangeMode(mode) => match mode.as_str() {
+                    "normal" => {
+                        self.mode = ViMode::Normal;
+                        changed.push(ReedlineEvent::Esc);
+                    }
+                    "insert" => self.mode = ViMode::Insert,
+                    _ => {}
+                },
+                event => changed.push(event),
+            }
+        }
+        self.cache.clear();
+        self.pending_keys.clear();
+        changed.push(ReedlineEvent::Repaint);
+        ReedlineEvent::Multiple(changed)
+    }
 }
 
 impl EditMode for Vi {
@@ -68,14 +129,27 @@ impl EditMode for Vi {
                     self.mode = ViMode::Visual;
                     ReedlineEvent::Multiple(vec![ReedlineEvent::Esc, ReedlineEvent::Repaint])
                 }
-                (ViMode::Normal | ViMode::Visual, modifier, KeyCode::Char(c)) => {
+                (ViMode::Normal, KeyModifiers::SHIFT, KeyCode::Char('V' | 'v')) => {
+                    self.cache.clear();
+                    self.mode = ViMode::VisualLine;
+                    ReedlineEvent::Multiple(vec![
+                        ReedlineEvent::Esc,
+                        ReedlineEvent::Edit(vec![EditCommand::SelectLines]),
+                        ReedlineEvent::Repaint,
+                    ])
+                }
+                (
+                    ViMode::Normal | ViMode::Visual | ViMode::VisualLine,
+                    modifier,
+                    KeyCode::Char(c),
+                ) => {
                     let c = c.to_ascii_lowercase();
 
                     if let Some(event) = self
                         .normal_keybindings
                         .find_binding(modifiers, KeyCode::Char(c))
                     {
-                        event
+                        self.apply_mode_changes(event)
                     } else if modifier == KeyModifiers::NONE || modifier == KeyModifiers::SHIFT {
                         self.cache.push(if modifier == KeyModifiers::SHIFT {
                             c.to_ascii_uppercase()
@@ -83,7 +157,7 @@ impl EditMode for Vi {
                             c
                         });
 
-                        let res = parse(&mut self.cache.iter().peekable());
+                        let res = parse(&mut self.cache.iter().peekable(), self.mode);
 
                         if !res.is_valid() {
                             self.cache.clear();
@@ -103,63 +177,42 @@ impl EditMode for Vi {
                         ReedlineEvent::None
                     }
                 }
-                (ViMode::Insert, modifier, KeyCode::Char(c)) => {
-                    // Note. The modifier can also be a combination of modifiers, for
-                    // example:
-                    //     KeyModifiers::CONTROL | KeyModifiers::ALT
-                    //     KeyModifiers::CONTROL | KeyModifiers::ALT | KeyModifiers::SHIFT
-                    //
-                    // Mixed modifiers are used by non american keyboards that have extra
-                    // keys like 'alt gr'. Keep this in mind if in the future there are
-                    // cases where an event is not being captured
-                    let c = match modifier {
-                        KeyModifiers::NONE => c,
-                        _ => c.to_ascii_lowercase(),
-                    };
-
-                    self.insert_keybindings
-                        .find_binding(modifier, KeyCode::Char(c))
-                        .unwrap_or_else(|| {
-                            if modifier == KeyModifiers::NONE
-                                || modifier == KeyModifiers::SHIFT
-                                || modifier == KeyModifiers::CONTROL | KeyModifiers::ALT
-                                || modifier
-                                    == KeyModifiers::CONTROL
-                                        | KeyModifiers::ALT
-                                        | KeyModifiers::SHIFT
-                            {
-                                ReedlineEvent::Edit(vec![EditCommand::InsertChar(
-                                    if modifier == KeyModifiers::SHIFT {
-                                        c.to_ascii_uppercase()
-                                    } else {
-                                        c
-                                    },
-                                )])
-                            } else {
-                                ReedlineEvent::None
-                            }
-                        })
+                // The keys of the pending sequence are typed before leaving insert mode
+                (ViMode::Insert, KeyModifiers::NONE, KeyCode::Esc)
+                    if !self.pending_keys.is_empty() =>
+                {
+                    let pending = self
+                        .insert_keybindings
+                        .resolve_sequence(&mut self.pending_keys);
+                    self.apply_mode_changes(ReedlineEvent::Multiple(vec![
+                        pending,
+                        ReedlineEvent::Esc,
+                    ]))
                 }
                 (_, KeyModifiers::NONE, KeyCode::Esc) => {
-                    self.cache.clear();
-                    self.mode = ViMode::Normal;
-                    ReedlineEvent::Multiple(vec![ReedlineEvent::Esc, ReedlineEvent::Repaint])
+                    self.apply_mode_changes(ReedlineEvent::Esc)
                 }
                 (_, KeyModifiers::NONE, KeyCode::Enter) => {
                     self.mode = ViMode::Insert;
+                    self.pending_keys.clear();
                     ReedlineEvent::Enter
                 }
-                (ViMode::Normal | ViMode::Visual, _, _) => self
-                    .normal_keybindings
-                    .find_binding(modifiers, code)
-                    .unwrap_or(ReedlineEvent::None),
-                (ViMode::Insert, _, _) => self
-                    .insert_keybindings
-                    .find_binding(modifiers, code)
-                    .unwrap_or(ReedlineEvent::None),
+                (ViMode::Normal | ViMode::Visual | ViMode::VisualLine, _, _) => {
+                    match self.normal_keybindings.find_binding(modifiers, code) {
+                        Some(event) => self.apply_mode_changes(event),
+                        None => ReedlineEvent::None,
+                    }
+                }
+                (ViMode::Insert, modifier, code) => {
+                    let key_combo = KeyCombination::from_key_event(modifier, code);
+                    let event = self
+                        .insert_keybindings
+                        .parse_sequence_key(&mut self.pending_keys, key_combo);
+                    self.apply_mode_changes(event)
+                }
             },
 
-            Event::Mouse(_) => ReedlineEvent::Mouse,
+            Event::Mouse(event) => parse_mouse_event(event),
             Event::Resize(width, height) => ReedlineEvent::Resize(width, height),
             Event::FocusGained => ReedlineEvent::None,
             Event::FocusLost => ReedlineEvent::None,
@@ -173,8 +226,43 @@ impl EditMode for Vi {
         match self.mode {
             ViMode::Normal | ViMode::Visual => PromptEditMode::Vi(PromptViMode::Normal),
             ViMode::Insert => PromptEditMode::Vi(PromptViMode::Insert),
+            ViMode::VisualLine => PromptEditMode::Vi(PromptViMode::VisualLine),
         }
     }
+
+    fn mode_state(&self) -> Option<String> {
+        let state = match self.mode {
+            ViMode::Normal => "normal",
+            ViMode::Insert => "insert",
+            ViMode::Visual => "visual",
+            ViMode::VisualLine => "visual_line",
+        };
+        Some(state.to_string())
+    }
+
+    fn pending_sequence_timeout(&self) -> Option<Duration> {
+        (!self.pending_keys.is_empty()).then_some(self.sequence_timeout)
+    }
+
+    fn resolve_pending_sequence(&mut self) -> ReedlineEvent {
+        let event = self
+            .insert_keybindings
+            .resolve_sequence(&mut self.pending_keys);
+        self.apply_mode_changes(event)
+    }
+
+    fn restore_mode_state(&mut self, state: &str) {
+        self.mode = match state {
+            "normal" => ViMode::Normal,
+            "insert" => ViMode::Insert,
+            "visual" => ViMode::Visual,
+            "visual_line" => ViMode::VisualLine,
+            _ => return,
+        };
+        self.cache.clear();
+        self.previous = None;
+        self.pending_keys.clear();
+    }
 }
 
 #[cfg(test)]
@@ -294,4 +382,84 @@ mod test {
 
         assert_eq!(result, ReedlineEvent::None);
     }
+
+    #[test]
+    fn visual_line_mode_selects_lines_test() {
+        let mut vi = Vi {
+            mode: ViMode::Normal,
+            ..Default::default()
+        };
+        let shift_v = ReedlineRawEvent::try_from(Event::Key(KeyEvent::new(
+            KeyCode::Char('V'),
+            KeyModifiers::SHIFT,
+        )))
+        .unwrap();
+        let result = vi.parse_event(shift_v);
+
+        assert_eq!(
+            result,
+            ReedlineEvent::Multiple(vec![
+                ReedlineEvent::Esc,
+                ReedlineEvent::Edit(vec![EditCommand::SelectLines]),
+                ReedlineEvent::Repaint
+            ])
+        );
+        assert_eq!(vi.edit_mode(), PromptEditMode::Vi(PromptViMode::VisualLine));
+        assert_eq!(vi.mode_state(), Some("visual_line".to_string()));
+    }
+
+    #[test]
+    fn insert_mode_sequences_test() {
+        let char_key = |c| KeyCombination {
+            modifier: KeyModifiers::NONE,
+            key_code: KeyCode::Char(c),
+        };
+        let mut insert_keybindings = default_vi_insert_keybindings();
+        insert_keybindings
+            .add_sequence_binding(vec![char_key('j'), char_key('k')], ReedlineEvent::Esc);
+        let mut vi = Vi::new(insert_keybindings, default_vi_normal_keybindings());
+        let type_char = |vi: &mut Vi, c| {
+            vi.parse_event(
+                ReedlineRawEvent::try_from(Event::Key(KeyEvent::new(
+                    KeyCode::Char(c),
+                    KeyModifiers::NONE,
+                )))
+                .unwrap(),
+            )
+        };
+
+        assert_eq!(type_char(&mut vi, 'j'), ReedlineEvent::None);
+        assert_eq!(
+            type_char(&mut vi, 'k'),
+            ReedlineEvent::Multiple(vec![ReedlineEvent::Esc, ReedlineEvent::Repaint])
+        );
+        assert_eq!(vi.mode, ViMode::Normal);
+
+        vi.mode = ViMode::Insert;
+        assert_eq!(type_char(&mut vi, 'j'), ReedlineEvent::None);
+        assert_eq!(
+            type_char(&mut vi, 'x'),
+            ReedlineEvent::Edit(vec![
+                EditCommand::InsertChar('j'),
+                EditCommand::InsertChar('x')
+            ])
+        );
+        assert_eq!(vi.mode, ViMode::Insert);
+
+        // Esc types the pending keys and leaves insert mode
+        assert_eq!(type_char(&mut vi, 'j'), ReedlineEvent::None);
+        let esc = vi.parse_event(
+            ReedlineRawEvent::try_from(Event::Key(KeyEvent::new(KeyCode::Esc, KeyModifiers::NONE)))
+                .unwrap(),
+        );
+        assert_eq!(
+            esc,
+            ReedlineEvent::Multiple(vec![
+                ReedlineEvent::Edit(vec![EditCommand::InsertChar('j')]),
+                ReedlineEvent::Esc,
+                ReedlineEvent::Repaint
+            ])
+        );
+        assert_eq!(vi.mode, ViMode::Normal);
+    }
 }
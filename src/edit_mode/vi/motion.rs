@@ -1,17 +1,35 @@
 use std::iter::Peekable;
 
-use crate::{edit_mode::vi::ViMode, EditCommand, ReedlineEvent, Vi};
+use crate::{EditCommand, ReedlineEvent, TextObject, TextObjectScope, TextObjectType, Vi};
 
 use super::parser::{ParseResult, ReedlineOption};
 
 pub fn parse_motion<'iter, I>(
     input: &mut Peekable<I>,
     command_char: Option<char>,
+    text_objects: bool,
 ) -> ParseResult<Motion>
 where
     I: Iterator<Item = &'iter char>,
 {
     match input.peek() {
+        Some(&&scope @ ('i' | 'a')) if text_objects => {
+            let _ = input.next();
+            let scope = if scope == 'i' {
+                TextObjectScope::Inner
+            } else {
+                TextObjectScope::Around
+            };
+            match input.next() {
+                Some(c) => match text_object_type(*c) {
+                    Some(object_type) => {
+                        ParseResult::Valid(Motion::TextObject(TextObject { scope, object_type }))
+                    }
+                    None => ParseResult::Invalid,
+                },
+                None => ParseResult::Incomplete,
+            }
+        }
         Some('h') => {
             let _ = input.next();
             ParseResult::Valid(Motion::Left)
@@ -52,6 +70,18 @@ where
             let _ = input.next();
             ParseResult::Valid(Motion::NextBigWordEnd)
         }
+        Some('g') => {
+            let _ = input.next();
+            match input.next() {
+                Some('g') => ParseResult::Valid(Motion::FirstLine),
+                Some(_) => ParseResult::Invalid,
+                None => ParseResult::Incomplete,
+            }
+        }
+        Some('G') => {
+            let _ = input.next();
+            ParseResult::Valid(Motion::LastLine)
+        }
         Some('0' | '^') => {
             let _ = input.next();
             ParseResult::Valid(Motion::Start)
@@ -117,12 +147,28 @@ where
     }
 }
 
+fn text_object_type(c: char) -> Option<TextObjectType> {
+    match c {
+        'w' => Some(TextObjectType::Word),
+        'W' => Some(TextObjectType::BigWord),
+        '"' | '\'' | '`' => Some(TextObjectType::Quote(c)),
+        '(' | ')' | 'b' => Some(TextObjectType::Bracket('(')),
+        '[' | ']' => Some(TextObjectType::Bracket('[')),
+        '{' | '}' | 'B' => Some(TextObjectType::Bracket('{')),
+        '<' | '>' => Some(TextObjectType::Bracket('<')),
+        'a' => Some(TextObjectType::Argument),
+        _ => None,
+    }
+}
+
 #[derive(Debug, PartialEq, Eq)]
 pub enum Motion {
     Left,
     Right,
     Up,
     Down,
+    FirstLine,
+    LastLine,
     NextWord,
     NextBigWord,
     NextWordEnd,
@@ -138,11 +184,12 @@ pub enum Motion {
     LeftBefore(char),
     ReplayCharSearch,
     ReverseCharSearch,
+    TextObject(TextObject),
 }
 
 impl Motion {
     pub fn to_reedline(&self, vi_state: &mut Vi) -> Vec<ReedlineOption> {
-        let select_mode = vi_state.mode == ViMode::Visual;
+        let select_mode = vi_state.mode.is_visual();
         match self {
             Motion::Left => vec![ReedlineOption::Event(ReedlineEvent::UntilFound(vec![
                 ReedlineEvent::MenuLeft,
@@ -157,16 +204,36 @@ impl Motion {
                     select: select_mode,
                 }]),
             ]))],
+            // Selections stay in the buffer instead of moving through the history
+            Motion::Up if select_mode => {
+                vec![ReedlineOption::Edit(EditCommand::MoveLineUp {
+                    select: true,
+                })]
+            }
+            Motion::Down if select_mode => {
+                vec![ReedlineOption::Edit(EditCommand::MoveLineDown {
+                    select: true,
+                })]
+            }
             Motion::Up => vec![ReedlineOption::Event(ReedlineEvent::UntilFound(vec![
                 ReedlineEvent::MenuUp,
                 ReedlineEvent::Up,
-                // todo: add EditCommand::MoveLineUp
             ]))],
             Motion::Down => vec![ReedlineOption::Event(ReedlineEvent::UntilFound(vec![
                 ReedlineEvent::MenuDown,
                 ReedlineEvent::Down,
-                // todo: add EditCommand::MoveLineDown
             ]))],
+            Motion::FirstLine => vec![ReedlineOption::Edit(EditCommand::MoveToStart {
+                select: select_mode,
+            })],
+            Motion::LastLine => vec![
+                ReedlineOption::Edit(EditCommand::MoveToEnd {
+                    select: select_mode,
+                }),
+                ReedlineOption::Edit(EditCommand::MoveToLineStart {
+                    select: select_mode,
+                }),
+            ],
             Motion::NextWord => vec![ReedlineOption::Edit(EditCommand::MoveWordRightStart {
                 select: select_mode,
             })],
@@ -238,6 +305,11 @@ impl Motion {
                     vec![]
                 }
             }
+            Motion::TextObject(text_object) => {
+                vec![ReedlineOption::Edit(EditCommand::SelectTextObject {
+                    text_object: *text_object,
+                })]
+            }
         }
     }
 }
@@ -295,4 +367,13 @@ impl ViCharSearch {
             ViCharSearch::TillLeft(c) => EditCommand::CutLeftBefore(*c),
         }
     }
+
+    pub fn to_copy(&self) -> EditCommand {
+        match self {
+            ViCharSearch::ToRight(c) => EditCommand::CopyRightUntil(*c),
+            ViCharSearch::ToLeft(c) => EditCommand::CopyLeftUntil(*c),
+            ViCharSearch::TillRight(c) => EditCommand::CopyRightBefore(*c),
+            ViCharSearch::TillLeft(c) => EditCommand::CopyLeftBefore(*c),
+        }
+    }
 }
@@ -1,8 +1,6 @@
-use super::command::{
-    is_valid_change_inside_left, is_valid_change_inside_right, parse_command, Command,
-};
+use super::command::{parse_command, Command};
 use super::motion::{parse_motion, Motion};
-use crate::{edit_mode::vi::ViMode, EditCommand, ReedlineEvent, Vi};
+use crate::{core_editor::is_register, edit_mode::vi::ViMode, EditCommand, ReedlineEvent, Vi};
 use std::iter::Peekable;
 
 #[derive(Debug, Clone)]
@@ -42,6 +40,7 @@ impl<T> ParseResult<T> {
 #[derive(Debug, PartialEq, Eq)]
 pub struct ParsedViSequence {
     multiplier: Option<usize>,
+    register: Option<char>,
     command: Option<Command>,
     count: Option<usize>,
     motion: ParseResult<Motion>,
@@ -53,13 +52,11 @@ impl ParsedViSequence {
     }
 
     pub fn is_complete(&self, mode: ViMode) -> bool {
-        assert!(mode == ViMode::Normal || mode == ViMode::Visual);
+        assert!(mode != ViMode::Insert);
         match (&self.command, &self.motion) {
             (None, ParseResult::Valid(_)) => true,
             (Some(Command::Incomplete), _) => false,
-            (Some(cmd), ParseResult::Incomplete)
-                if !cmd.requires_motion() || mode == ViMode::Visual =>
-            {
+            (Some(cmd), ParseResult::Incomplete) if !cmd.requires_motion() || mode.is_visual() => {
                 true
             }
             (Some(_), ParseResult::Valid(_)) => true,
@@ -81,9 +78,8 @@ impl ParsedViSequence {
 
     fn apply_multiplier(&self, raw_events: Option<Vec<ReedlineOption>>) -> ReedlineEvent {
         if let Some(raw_events) = raw_events {
-            let events = std::iter::repeat(raw_events)
-                .take(self.total_multiplier())
-                .flatten()
+            let events = raw_events
+                .into_iter()
                 .filter_map(ReedlineOption::into_reedline_event)
                 .collect::<Vec<ReedlineEvent>>();
 
@@ -91,13 +87,35 @@ impl ParsedViSequence {
                 // TODO: Clarify if the `contains(ReedlineEvent::None)` path is relevant
                 ReedlineEvent::None
             } else {
-                ReedlineEvent::Multiple(events)
+                ReedlineEvent::Multiple(
+                    (0..self.total_multiplier())
+                        .flat_map(|repetition| {
+                            self.register_event(repetition)
+                                .into_iter()
+                                .chain(events.iter().cloned())
+                        })
+                        .collect(),
+                )
             }
         } else {
             ReedlineEvent::None
         }
     }
 
+    // The register is picked for one command only, so every repetition picks it again. The
+    // repetitions after the first append to a letter register instead of replacing the
+    // contents of the ones before, like `2"add` keeps both lines in the register `a`
+    fn register_event(&self, repetition: usize) -> Option<ReedlineEvent> {
+        self.register.map(|register| {
+            let register = if repetition > 0 {
+                register.to_ascii_uppercase()
+            } else {
+                register
+            };
+            ReedlineEvent::Edit(vec![EditCommand::UseRegister(register)])
+        })
+    }
+
     pub fn changes_mode(&self) -> Option<ViMode> {
         match (&self.command, &self.motion) {
             (Some(Command::EnterViInsert), ParseResult::Incomplete)
@@ -108,13 +126,15 @@ impl ParsedViSequence {
             | (Some(Command::RewriteCurrentLine), ParseResult::Incomplete)
             | (Some(Command::SubstituteCharWithInsert), ParseResult::Incomplete)
             | (Some(Command::HistorySearch), ParseResult::Incomplete)
-            | (Some(Command::Change), ParseResult::Valid(_)) => Some(ViMode::Insert),
-            (Some(Command::ChangeInside(char)), ParseResult::Incomplete)
-                if is_valid_change_inside_left(char) || is_valid_change_inside_right(char) =>
-            {
-                Some(ViMode::Insert)
-            }
-            (Some(Command::Delete), ParseResult::Incomplete) => Some(ViMode::Normal),
+            | (Some(Command::OpenLineBelow), ParseResult::Incomplete)
+            | (Some(Command::OpenLineAbove), ParseResult::Incomplete)
+            | (Some(Command::Change), _) => Some(ViMode::Insert),
+            (Some(Command::Delete), ParseResult::Incomplete)
+            | (Some(Command::Yank), ParseResult::Incomplete)
+            | (Some(Command::YankLine), ParseResult::Incomplete)
+            | (Some(Command::Indent), ParseResult::Incomplete)
+            | (Some(Command::Dedent), ParseResult::Incomplete)
+            | (Some(Command::JoinLines), ParseResult::Incomplete) => Some(ViMode::Normal),
             _ => None,
         }
     }
@@ -171,17 +191,73 @@ where
     }
 }
 
-pub fn parse<'iter, I>(input: &mut Peekable<I>) -> ParsedViSequence
+// The register of the command after `"`, `None` without a `"`
+fn parse_register<'iter, I>(input: &mut Peekable<I>) -> ParseResult<Option<char>>
+where
+    I: Iterator<Item = &'iter char>,
+{
+    match input.peek() {
+        Some('"') => {
+            let _ = input.next();
+            match input.next() {
+                Some(&register) if is_register(register) => ParseResult::Valid(Some(register)),
+                Some(_) => ParseResult::Invalid,
+                None => ParseResult::Incomplete,
+            }
+        }
+        _ => ParseResult::Valid(None),
+    }
+}
+
+pub fn parse<'iter, I>(input: &mut Peekable<I>, mode: ViMode) -> ParsedViSequence
 where
     I: Iterator<Item = &'iter char>,
 {
+    let visual = mode.is_visual();
     let multiplier = parse_number(input);
-    let command = parse_command(input);
+    let register = match parse_register(input) {
+        ParseResult::Valid(register) => register,
+        ParseResult::Incomplete => {
+            return ParsedViSequence {
+                multiplier,
+                register: None,
+                command: Some(Command::Incomplete),
+                count: None,
+                motion: ParseResult::Incomplete,
+            }
+        }
+        ParseResult::Invalid => {
+            return ParsedViSequence {
+                multiplier,
+                register: None,
+                command: None,
+                count: None,
+                motion: ParseResult::Invalid,
+            }
+        }
+    };
+    // The count may come before or after the register, like in `2"ayy` and `"a2yy`
+    let multiplier = match (multiplier, parse_number(input)) {
+        (Some(before), Some(after)) => Some(before * after),
+        (before, after) => before.or(after),
+    };
+    // In visual mode `i` and `a` start a text object to select instead of inserting
+    let command = if visual && matches!(input.peek(), Some('i' | 'a')) {
+        None
+    } else {
+        parse_command(input)
+    };
     let count = parse_number(input);
-    let motion = parse_motion(input, command.as_ref().and_then(Command::whole_line_char));
+    let text_objects = visual || command.as_ref().map_or(false, Command::requires_motion);
+    let motion = parse_motion(
+        input,
+        command.as_ref().and_then(Command::whole_line_char),
+        text_objects,
+    );
 
     ParsedViSequence {
         multiplier,
+        register,
         command,
         count,
         motion,
@@ -191,11 +267,12 @@ where
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::{TextObject, TextObjectScope, TextObjectType};
     use pretty_assertions::assert_eq;
     use rstest::rstest;
 
     fn vi_parse(input: &[char]) -> ParsedViSequence {
-        parse(&mut input.iter().peekable())
+        parse(&mut input.iter().peekable(), ViMode::Normal)
     }
 
     #[test]
@@ -207,6 +284,7 @@ mod tests {
             output,
             ParsedViSequence {
                 multiplier: None,
+                register: None,
                 command: Some(Command::Delete),
                 count: None,
                 motion: ParseResult::Incomplete,
@@ -226,6 +304,7 @@ mod tests {
             output,
             ParsedViSequence {
                 multiplier: None,
+                register: None,
                 command: Some(Command::Delete),
                 count: None,
                 motion: ParseResult::Valid(Motion::NextWord),
@@ -245,6 +324,7 @@ mod tests {
             output,
             ParsedViSequence {
                 multiplier: Some(2),
+                register: None,
                 command: Some(Command::Delete),
                 count: None,
                 motion: ParseResult::Incomplete,
@@ -266,6 +346,7 @@ mod tests {
             output,
             ParsedViSequence {
                 multiplier: Some(2),
+                register: None,
                 command: Some(Command::Delete),
                 count: None,
                 motion: ParseResult::Valid(Motion::NextWord),
@@ -285,6 +366,7 @@ mod tests {
             output,
             ParsedViSequence {
                 multiplier: Some(2),
+                register: None,
                 command: Some(Command::Delete),
                 count: Some(2),
                 motion: ParseResult::Valid(Motion::NextWord),
@@ -304,6 +386,7 @@ mod tests {
             output,
             ParsedViSequence {
                 multiplier: Some(2),
+                register: None,
                 command: Some(Command::Delete),
                 count: Some(20),
                 motion: ParseResult::Valid(Motion::NextWord),
@@ -323,6 +406,7 @@ mod tests {
             output,
             ParsedViSequence {
                 multiplier: Some(2),
+                register: None,
                 command: Some(Command::Delete),
                 count: None,
                 motion: ParseResult::Valid(Motion::Line),
@@ -342,6 +426,7 @@ mod tests {
             output,
             ParsedViSequence {
                 multiplier: None,
+                register: None,
                 command: Some(Command::Delete),
                 count: None,
                 motion: ParseResult::Valid(Motion::RightBefore('d')),
@@ -361,6 +446,7 @@ mod tests {
             output,
             ParsedViSequence {
                 multiplier: Some(2),
+                register: None,
                 command: Some(Command::Delete),
                 count: None,
                 motion: ParseResult::Invalid,
@@ -378,6 +464,7 @@ mod tests {
             output,
             ParsedViSequence {
                 multiplier: None,
+                register: None,
                 command: Some(Command::Incomplete),
                 count: None,
                 motion: ParseResult::Incomplete,
@@ -398,6 +485,7 @@ mod tests {
             output,
             ParsedViSequence {
                 multiplier: None,
+                register: None,
                 command: None,
                 count: None,
                 motion: ParseResult::Incomplete,
@@ -417,6 +505,7 @@ mod tests {
             output,
             ParsedViSequence {
                 multiplier: None,
+                register: None,
                 command: Some(Command::ReplaceChar('k')),
                 count: None,
                 motion: ParseResult::Incomplete,
@@ -437,6 +526,7 @@ mod tests {
             output,
             ParsedViSequence {
                 multiplier: Some(2),
+                register: None,
                 command: None,
                 count: None,
                 motion: ParseResult::Valid(Motion::RightUntil('f')),
@@ -456,6 +546,7 @@ mod tests {
             output,
             ParsedViSequence {
                 multiplier: Some(2),
+                register: None,
                 command: None,
                 count: None,
                 motion: ParseResult::Valid(Motion::Up),
@@ -545,6 +636,37 @@ mod tests {
     #[case(&['c', 't', 'a'], ReedlineEvent::Multiple(vec![ReedlineEvent::Edit(vec![EditCommand::CutRightBefore('a')]), ReedlineEvent::Repaint]))]
     #[case(&['c', 'F', 'a'], ReedlineEvent::Multiple(vec![ReedlineEvent::Edit(vec![EditCommand::CutLeftUntil('a')]), ReedlineEvent::Repaint]))]
     #[case(&['c', 'T', 'a'], ReedlineEvent::Multiple(vec![ReedlineEvent::Edit(vec![EditCommand::CutLeftBefore('a')]), ReedlineEvent::Repaint]))]
+    #[case(&['d', 'a', 'w'], ReedlineEvent::Multiple(vec![ReedlineEvent::Edit(vec![EditCommand::CutTextObject{text_object: TextObject{scope: TextObjectScope::Around, object_type: TextObjectType::Word}}])]))]
+    #[case(&['d', 'i', 'a'], ReedlineEvent::Multiple(vec![ReedlineEvent::Edit(vec![EditCommand::CutTextObject{text_object: TextObject{scope: TextObjectScope::Inner, object_type: TextObjectType::Argument}}])]))]
+    #[case(&['c', 'i', '"'], ReedlineEvent::Multiple(vec![ReedlineEvent::Edit(vec![EditCommand::CutTextObject{text_object: TextObject{scope: TextObjectScope::Inner, object_type: TextObjectType::Quote('"')}}]), ReedlineEvent::Repaint]))]
+    #[case(&['c', 'a', ']'], ReedlineEvent::Multiple(vec![ReedlineEvent::Edit(vec![EditCommand::CutTextObject{text_object: TextObject{scope: TextObjectScope::Around, object_type: TextObjectType::Bracket('[')}}]), ReedlineEvent::Repaint]))]
+    #[case(&['y', 'w'], ReedlineEvent::Multiple(vec![ReedlineEvent::Edit(vec![EditCommand::CopyWordRightToNext])]))]
+    #[case(&['y', 'y'], ReedlineEvent::Multiple(vec![ReedlineEvent::Edit(vec![EditCommand::CopyCurrentLine])]))]
+    #[case(&['Y'], ReedlineEvent::Multiple(vec![ReedlineEvent::Edit(vec![EditCommand::CopyCurrentLine])]))]
+    #[case(&['y', '$'], ReedlineEvent::Multiple(vec![ReedlineEvent::Edit(vec![EditCommand::CopyToLineEnd])]))]
+    #[case(&['y', 't', 'a'], ReedlineEvent::Multiple(vec![ReedlineEvent::Edit(vec![EditCommand::CopyRightBefore('a')])]))]
+    #[case(&['y', 'i', '['], ReedlineEvent::Multiple(vec![ReedlineEvent::Edit(vec![EditCommand::CopyTextObject{text_object: TextObject{scope: TextObjectScope::Inner, object_type: TextObjectType::Bracket('[')}}])]))]
+    #[case(&['"', 'a', 'y', 'b'], ReedlineEvent::Multiple(vec![ReedlineEvent::Edit(vec![EditCommand::UseRegister('a')]), ReedlineEvent::Edit(vec![EditCommand::CopyWordLeft])]))]
+    #[case(&['"', '+', 'p'], ReedlineEvent::Multiple(vec![ReedlineEvent::Edit(vec![EditCommand::UseRegister('+')]), ReedlineEvent::Edit(vec![EditCommand::PasteCutBufferAfter])]))]
+    #[case(&['2', '"', 'a', 'd', 'd'], ReedlineEvent::Multiple(vec![ReedlineEvent::Edit(vec![EditCommand::UseRegister('a')]), ReedlineEvent::Edit(vec![EditCommand::CutCurrentLine]), ReedlineEvent::Edit(vec![EditCommand::UseRegister('A')]), ReedlineEvent::Edit(vec![EditCommand::CutCurrentLine])]))]
+    #[case(&['2', '"', 'A', 'd', 'd'], ReedlineEvent::Multiple(vec![ReedlineEvent::Edit(vec![EditCommand::UseRegister('A')]), ReedlineEvent::Edit(vec![EditCommand::CutCurrentLine]), ReedlineEvent::Edit(vec![EditCommand::UseRegister('A')]), ReedlineEvent::Edit(vec![EditCommand::CutCurrentLine])]))]
+    #[case(&['g', 'g'], ReedlineEvent::Multiple(vec![ReedlineEvent::Edit(vec![EditCommand::MoveToStart{select: false}])]))]
+    #[case(&['G'], ReedlineEvent::Multiple(vec![
+        ReedlineEvent::Edit(vec![EditCommand::MoveToEnd{select: false}]),
+        ReedlineEvent::Edit(vec![EditCommand::MoveToLineStart{select: false}])]))]
+    #[case(&['>', '>'], ReedlineEvent::Multiple(vec![ReedlineEvent::Edit(vec![EditCommand::IndentLines])]))]
+    #[case(&['<', '<'], ReedlineEvent::Multiple(vec![ReedlineEvent::Edit(vec![EditCommand::DedentLines])]))]
+    #[case(&['J'], ReedlineEvent::Multiple(vec![ReedlineEvent::Edit(vec![EditCommand::JoinLines])]))]
+    #[case(&['o'], ReedlineEvent::Multiple(vec![
+        ReedlineEvent::Edit(vec![EditCommand::MoveToLineEnd{select: false}]),
+        ReedlineEvent::Edit(vec![EditCommand::InsertNewline]),
+        ReedlineEvent::Repaint]))]
+    #[case(&['O'], ReedlineEvent::Multiple(vec![
+        ReedlineEvent::Edit(vec![EditCommand::MoveToLineStart{select: false}]),
+        ReedlineEvent::Edit(vec![EditCommand::InsertString("\n".to_string())]),
+        ReedlineEvent::Edit(vec![EditCommand::MoveLineUp{select: false}]),
+        ReedlineEvent::Repaint]))]
+    #[case(&['"', '1', '2', 'P'], ReedlineEvent::Multiple(vec![ReedlineEvent::Edit(vec![EditCommand::UseRegister('1')]), ReedlineEvent::Edit(vec![EditCommand::PasteCutBufferBefore]), ReedlineEvent::Edit(vec![EditCommand::UseRegister('1')]), ReedlineEvent::Edit(vec![EditCommand::PasteCutBufferBefore])]))]
     fn test_reedline_move(#[case] input: &[char], #[case] expected: ReedlineEvent) {
         let mut vi = Vi::default();
         let res = vi_parse(input);
@@ -553,6 +675,19 @@ mod tests {
         assert_eq!(output, expected);
     }
 
+    #[rstest]
+    #[case(&['"'], true, false)]
+    #[case(&['"', 'a'], true, false)]
+    #[case(&['"', 'a', 'y'], true, false)]
+    #[case(&['"', '!'], false, false)]
+    #[case(&['"', 'z', 'x'], true, true)]
+    fn test_registers(#[case] input: &[char], #[case] is_valid: bool, #[case] is_complete: bool) {
+        let output = vi_parse(input);
+
+        assert_eq!(output.is_valid(), is_valid);
+        assert_eq!(output.is_complete(ViMode::Normal), is_complete);
+    }
+
     #[rstest]
     #[case(&['f', 'a'], &[';'], ReedlineEvent::Multiple(vec![ReedlineEvent::Edit(vec![EditCommand::MoveRightUntil{c: 'a',select: false}])]))]
     #[case(&['f', 'a'], &[','], ReedlineEvent::Multiple(vec![ReedlineEvent::Edit(vec![EditCommand::MoveLeftUntil{c: 'a', select: false}])]))]
@@ -590,17 +725,10 @@ mod tests {
     }
 
     #[rstest]
-    #[case(&['2', 'k'], ReedlineEvent::Multiple(vec![ReedlineEvent::UntilFound(vec![
-                ReedlineEvent::MenuUp,
-                ReedlineEvent::Up,
-            ]), ReedlineEvent::UntilFound(vec![
-                ReedlineEvent::MenuUp,
-                ReedlineEvent::Up,
-            ])]))]
-    #[case(&['k'], ReedlineEvent::Multiple(vec![ReedlineEvent::UntilFound(vec![
-                ReedlineEvent::MenuUp,
-                ReedlineEvent::Up,
-            ])]))]
+    #[case(&['2', 'k'], ReedlineEvent::Multiple(vec![
+        ReedlineEvent::Edit(vec![EditCommand::MoveLineUp{select:true}]),
+        ReedlineEvent::Edit(vec![EditCommand::MoveLineUp{select:true}])]))]
+    #[case(&['k'], ReedlineEvent::Multiple(vec![ReedlineEvent::Edit(vec![EditCommand::MoveLineUp{select:true}])]))]
     #[case(&['w'],
         ReedlineEvent::Multiple(vec![ReedlineEvent::Edit(vec![EditCommand::MoveWordRightStart{select:true}])]))]
     #[case(&['W'],
@@ -622,7 +750,8 @@ mod tests {
             ])]))]
     #[case(&['0'], ReedlineEvent::Multiple(vec![ReedlineEvent::Edit(vec![EditCommand::MoveToLineStart{select:true}])]))]
     #[case(&['$'], ReedlineEvent::Multiple(vec![ReedlineEvent::Edit(vec![EditCommand::MoveToLineEnd{select:true}])]))]
-    #[case(&['i'], ReedlineEvent::Multiple(vec![ReedlineEvent::Repaint]))]
+    #[case(&['i', 'w'], ReedlineEvent::Multiple(vec![ReedlineEvent::Edit(vec![EditCommand::SelectTextObject{text_object: TextObject{scope: TextObjectScope::Inner, object_type: TextObjectType::Word}}])]))]
+    #[case(&['a', '{'], ReedlineEvent::Multiple(vec![ReedlineEvent::Edit(vec![EditCommand::SelectTextObject{text_object: TextObject{scope: TextObjectScope::Around, object_type: TextObjectType::Bracket('{')}}])]))]
     #[case(&['p'], ReedlineEvent::Multiple(vec![ReedlineEvent::Edit(vec![EditCommand::PasteCutBufferAfter])]))]
     #[case(&['2', 'p'], ReedlineEvent::Multiple(vec![
         ReedlineEvent::Edit(vec![EditCommand::PasteCutBufferAfter]),
@@ -635,14 +764,48 @@ mod tests {
         ]))]
     #[case(&['d'], ReedlineEvent::Multiple(vec![
         ReedlineEvent::Edit(vec![EditCommand::CutSelection])]))]
+    #[case(&['"', 'b', 'y'], ReedlineEvent::Multiple(vec![
+        ReedlineEvent::Edit(vec![EditCommand::UseRegister('b')]),
+        ReedlineEvent::Edit(vec![EditCommand::CopySelection])]))]
     fn test_reedline_move_in_visual_mode(#[case] input: &[char], #[case] expected: ReedlineEvent) {
         let mut vi = Vi {
             mode: ViMode::Visual,
             ..Default::default()
         };
-        let res = vi_parse(input);
+        let res = parse(&mut input.iter().peekable(), ViMode::Visual);
         let output = res.to_reedline_event(&mut vi);
 
         assert_eq!(output, expected);
     }
+
+    #[rstest]
+    #[case(&['j'], ReedlineEvent::Multiple(vec![ReedlineEvent::Edit(vec![EditCommand::MoveLineDown{select: true}])]), None)]
+    #[case(&['G'], ReedlineEvent::Multiple(vec![
+        ReedlineEvent::Edit(vec![EditCommand::MoveToEnd{select: true}]),
+        ReedlineEvent::Edit(vec![EditCommand::MoveToLineStart{select: true}])]), None)]
+    #[case(&['d'], ReedlineEvent::Multiple(vec![ReedlineEvent::Edit(vec![EditCommand::CutSelection])]), Some(ViMode::Normal))]
+    #[case(&['c'], ReedlineEvent::Multiple(vec![ReedlineEvent::Edit(vec![EditCommand::ChangeSelection])]), Some(ViMode::Insert))]
+    #[case(&['y'], ReedlineEvent::Multiple(vec![ReedlineEvent::Edit(vec![EditCommand::CopySelection])]), Some(ViMode::Normal))]
+    #[case(&['>'], ReedlineEvent::Multiple(vec![
+        ReedlineEvent::Edit(vec![EditCommand::IndentLines]),
+        ReedlineEvent::Edit(vec![EditCommand::MoveToLineStart{select: false}])]), Some(ViMode::Normal))]
+    #[case(&['<'], ReedlineEvent::Multiple(vec![
+        ReedlineEvent::Edit(vec![EditCommand::DedentLines]),
+        ReedlineEvent::Edit(vec![EditCommand::MoveToLineStart{select: false}])]), Some(ViMode::Normal))]
+    #[case(&['J'], ReedlineEvent::Multiple(vec![ReedlineEvent::Edit(vec![EditCommand::JoinLines])]), Some(ViMode::Normal))]
+    fn test_reedline_move_in_visual_line_mode(
+        #[case] input: &[char],
+        #[case] expected: ReedlineEvent,
+        #[case] mode: Option<ViMode>,
+    ) {
+        let mut vi = Vi {
+            mode: ViMode::VisualLine,
+            ..Default::default()
+        };
+        let res = parse(&mut input.iter().peekable(), ViMode::VisualLine);
+
+        assert!(res.is_complete(ViMode::VisualLine));
+        assert_eq!(res.changes_mode(), mode);
+        assert_eq!(res.to_reedline_event(&mut vi), expected);
+    }
 }
@@ -1,24 +1,30 @@
-use std::path::PathBuf;
+use std::{collections::HashMap, path::PathBuf};
 
 use itertools::Itertools;
 use nu_ansi_term::{Color, Style};
+use unicode_segmentation::UnicodeSegmentation;
 
+#[cfg(feature = "system_clipboard")]
+use crate::{
+    core_editor::{Osc52Terminal, PASTE_REQUEST},
+    Osc52Clipboard,
+};
 use crate::{enums::ReedlineRawEvent, CursorConfig};
 #[cfg(feature = "bashisms")]
 use crate::{
     history::SearchFilter,
     menu_functions::{parse_selection_char, ParseAction},
 };
-#[cfg(feature = "external_printer")]
-use {
-    crate::external_printer::ExternalPrinter,
-    crossbeam::channel::TryRecvError,
-    std::io::{Error, ErrorKind},
+#[cfg(feature = "async")]
+use futures_util::{
+    future::{self, Either},
+    StreamExt,
 };
 use {
     crate::{
+        backend::{DefaultInputSource, DefaultOutputSink, InputSource, OutputSink},
         completion::{Completer, DefaultCompleter},
-        core_editor::Editor,
+        core_editor::{AutoPairs, Editor},
         edit_mode::{EditMode, Emacs},
         enums::{EventStatus, ReedlineEvent},
         highlighter::SimpleMatchHighlighter,
@@ -27,24 +33,47 @@ use {
             FileBackedHistory, History, HistoryCursor, HistoryItem, HistoryItemId,
             HistoryNavigationQuery, HistorySessionId, SearchDirection, SearchQuery,
         },
-        painting::{Painter, PainterSuspendedState, PromptLines},
-        prompt::{PromptEditMode, PromptHistorySearchStatus},
+        hooks::{EditorHooks, HookRunner, PreSubmit},
+        keyboard_macro::KeyboardMacro,
+        painting::{
+            columns_after_prompt, render_diagnostics, underline_diagnostics, HorizontalScroll,
+            LineNumberGutter, Painter, PainterSuspendedState, PromptLines,
+        },
+        prompt::{PromptEditMode, PromptHistorySearchStatus, PromptInvalidator},
         result::{ReedlineError, ReedlineErrorVariants},
-        terminal_extensions::{bracketed_paste::BracketedPasteGuard, kitty::KittyProtocolGuard},
+        session_snapshot::SessionSnapshot,
+        terminal_extensions::{
+            bracketed_paste::BracketedPasteGuard, kitty::KittyProtocolGuard,
+            mouse_capture::MouseCaptureGuard,
+        },
         utils::text_manipulation,
-        EditCommand, ExampleHighlighter, Highlighter, LineBuffer, Menu, MenuEvent, Prompt,
-        PromptHistorySearch, ReedlineMenu, Signal, UndoBehavior, ValidationResult, Validator,
+        widgets::{
+            confirm_choices, confirm_key, select_action, ConfirmKey, SelectAction, SelectItems,
+        },
+        ColumnarMenu, Diagnostic, EditCommand, ExampleHighlighter, Highlighter, Indenter,
+        LineBuffer, Menu, MenuBuilder, MenuEvent, Prompt, PromptHistorySearch, ReedlineMenu,
+        Signal, StyledText, UndoBehavior, ValidationResult, Validator,
     },
     crossterm::{
         cursor::{SetCursorStyle, Show},
-        event,
         event::{Event, KeyCode, KeyEvent, KeyModifiers},
-        terminal, QueueableCommand,
+        QueueableCommand,
     },
     std::{
-        fs::File, io, io::Result, io::Write, process::Command, time::Duration, time::SystemTime,
+        fs::File,
+        io,
+        io::Result,
+        io::Write,
+        process::Command,
+        time::{Duration, Instant, SystemTime},
     },
 };
+#[cfg(feature = "external_printer")]
+use {
+    crate::{event_injector::EventInjector, external_printer::ExternalPrinter},
+    crossbeam::channel::TryRecvError,
+    std::io::{Error, ErrorKind},
+};
 
 // The POLL_WAIT is used to specify for how long the POLL should wait for
 // events, to accelerate the handling of paste or compound resize events. Having
@@ -62,6 +91,27 @@ const EVENTS_THRESHOLD: usize = 10;
 #[cfg(feature = "external_printer")]
 const EXTERNAL_PRINTER_WAIT: Duration = Duration::from_millis(100);
 
+/// Maximum time Reedline will block on input before checking if the prompt
+/// was invalidated through a [`PromptInvalidator`].
+const PROMPT_INVALIDATION_WAIT: Duration = Duration::from_millis(100);
+
+// Helper function that returns true if the input is complete and
+// can be sent to the hosting application.
+fn completed(events: &[Event]) -> bool {
+    if let Some(event) = events.last() {
+        matches!(
+            event,
+            Event::Key(KeyEvent {
+                code: KeyCode::Enter,
+                modifiers: KeyModifiers::NONE,
+                ..
+            })
+        )
+    } else {
+        false
+    }
+}
+
 /// Determines if inputs should be used to extend the regular line buffer,
 /// traverse the history in the standard prompt or edit the search string in the
 /// reverse search
@@ -119,12 +169,18 @@ pub struct Reedline {
 
     // Validator
     validator: Option<Box<dyn Validator>>,
+    // Diagnostics that blocked the last submit, validated again while they are shown
+    diagnostics: Vec<Diagnostic>,
 
     // Stdout
     painter: Painter,
 
     transient_prompt: Option<Box<dyn Prompt>>,
 
+    // Repaint the prompt while the user is idle
+    prompt_refresh: Option<Duration>,
+    prompt_invalidator: PromptInvalidator,
+
     // Edit Mode: Vi, Emacs
     edit_mode: Box<dyn EditMode>,
 
@@ -146,6 +202,23 @@ pub struct Reedline {
     // Use ansi coloring or not
     use_ansi_coloring: bool,
 
+    // Callbacks following the changes of the editor
+    editor_hooks: Option<HookRunner>,
+
+    // Escape sequences of the OSC 52 clipboard to pass through the terminal
+    #[cfg(feature = "system_clipboard")]
+    osc52_terminal: Option<Osc52Terminal>,
+
+    // Keep the buffer on a single row scrolling horizontally instead of wrapping it
+    horizontal_scroll: Option<HorizontalScroll>,
+
+    // Paint line numbers in front of the lines of the buffer
+    line_numbers: Option<LineNumberGutter>,
+
+    // A secret is read with `Reedline::read_secret()`, painted as its mask
+    reading_secret: bool,
+    secret_mask: Option<char>,
+
     // Current working directory as defined by the application. If set, it will
     // override the actual working directory of the process.
     cwd: Option<String>,
@@ -153,6 +226,9 @@ pub struct Reedline {
     // Engine Menus
     menus: Vec<ReedlineMenu>,
 
+    // Menu painting the items of `Reedline::select()` and `Reedline::multi_select()`
+    select_menu: Option<ReedlineMenu>,
+
     // Text editor used to open the line buffer for editing
     buffer_editor: Option<BufferEditor>,
 
@@ -165,8 +241,66 @@ pub struct Reedline {
     // Manage optional kitty protocol
     kitty_protocol: KittyProtocolGuard,
 
+    // Manage optional mouse capture
+    mouse_capture: MouseCaptureGuard,
+
+    // Keyboard macros
+    recording_macro: Option<KeyboardMacro>,
+    last_macro: Option<KeyboardMacro>,
+    macros: HashMap<String, KeyboardMacro>,
+    playing_macro: bool,
+    recording_macro_call: bool,
+
     #[cfg(feature = "external_printer")]
     external_printer: Option<ExternalPrinter<String>>,
+
+    #[cfg(feature = "external_printer")]
+    event_injector: Option<EventInjector>,
+
+    // Source of the terminal events
+    input_source: Box<dyn InputSource>,
+
+    // Commands run in place by `ReedlineEvent::CustomCommand`
+    custom_commands: HashMap<String, CustomCommand>,
+}
+
+/// Closure run by [`ReedlineEvent::CustomCommand`], see [`Reedline::with_custom_command()`]
+type CustomCommand = Box<dyn FnMut(&mut Editor, &dyn History) + Send>;
+
+/// Raw mode context of [`Reedline::read_line_async()`]
+///
+/// Restores the terminal when the future is dropped before completion.
+#[cfg(feature = "async")]
+struct AsyncSession<'a> {
+    reedline: &'a mut Reedline,
+    active: bool,
+}
+
+#[cfg(feature = "async")]
+impl<'a> AsyncSession<'a> {
+    fn enter(reedline: &'a mut Reedline) -> Result<Self> {
+        reedline.enter_terminal()?;
+        Ok(AsyncSession {
+            reedline,
+            active: true,
+        })
+    }
+
+    fn exit(mut self) -> Result<()> {
+        self.active = false;
+        self.reedline.leave_terminal()
+    }
+}
+
+#[cfg(feature = "async")]
+impl Drop for AsyncSession<'_> {
+    fn drop(&mut self) {
+        if self.active {
+            // Cancelled while waiting for input, leave the buffer on screen
+            let _ignore = self.reedline.painter.move_cursor_to_end();
+            let _ignore = self.reedline.leave_terminal();
+        }
+    }
 }
 
 struct BufferEditor {
@@ -177,16 +311,20 @@ struct BufferEditor {
 impl Drop for Reedline {
     fn drop(&mut self) {
         if self.cursor_shapes.is_some() {
-            let _ignore = terminal::enable_raw_mode();
-            let mut stdout = std::io::stdout();
+            let _ignore = self.input_source.enable_raw_mode();
+            let stdout = self.painter.output_sink();
             let _ignore = stdout.queue(SetCursorStyle::DefaultUserShape);
             let _ignore = stdout.queue(Show);
             let _ignore = stdout.flush();
         }
+        // Terminal extensions left enabled, e.g. by a panic while reading a line
+        self.bracketed_paste.exit(self.painter.output_sink());
+        self.kitty_protocol.exit(self.painter.output_sink());
+        self.mouse_capture.exit(self.painter.output_sink());
 
         // Ensures that the terminal is in a good state if we panic semigracefully
         // Calling `disable_raw_mode()` twice is fine with Linux
-        let _ignore = terminal::disable_raw_mode();
+        let _ignore = self.input_source.disable_raw_mode();
     }
 }
 
@@ -197,7 +335,7 @@ impl Reedline {
     #[must_use]
     pub fn create() -> Self {
         let history = Box::<FileBackedHistory>::default();
-        let painter = Painter::new(std::io::BufWriter::new(std::io::stderr()));
+        let painter = Painter::new(Box::<DefaultOutputSink>::default());
         let buffer_highlighter = Box::<ExampleHighlighter>::default();
         let visual_selection_style = Style::new().on(Color::LightGray);
         let completer = Box::<DefaultCompleter>::default();
@@ -222,6 +360,8 @@ impl Reedline {
             suspended_state: None,
             painter,
             transient_prompt: None,
+            prompt_refresh: None,
+            prompt_invalidator: PromptInvalidator::default(),
             edit_mode,
             completer,
             quick_completions: false,
@@ -231,15 +371,34 @@ impl Reedline {
             hinter,
             hide_hints: false,
             validator,
+            diagnostics: Vec::new(),
             use_ansi_coloring: true,
+            editor_hooks: None,
+            #[cfg(feature = "system_clipboard")]
+            osc52_terminal: None,
+            horizontal_scroll: None,
+            line_numbers: None,
+            reading_secret: false,
+            secret_mask: None,
             cwd: None,
             menus: Vec::new(),
+            select_menu: None,
             buffer_editor: None,
             cursor_shapes: None,
             bracketed_paste: BracketedPasteGuard::default(),
             kitty_protocol: KittyProtocolGuard::default(),
+            mouse_capture: MouseCaptureGuard::default(),
+            recording_macro: None,
+            last_macro: None,
+            macros: HashMap::new(),
+            playing_macro: false,
+            recording_macro_call: false,
             #[cfg(feature = "external_printer")]
             external_printer: None,
+            #[cfg(feature = "external_printer")]
+            event_injector: None,
+            input_source: Box::<DefaultInputSource>::default(),
+            custom_commands: HashMap::new(),
         }
     }
 
@@ -280,6 +439,28 @@ impl Reedline {
         self
     }
 
+    /// Toggle whether reedline emits semantic prompt marks (OSC 133)
+    ///
+    /// Terminals with shell integration use the marks around the prompt, the input and the
+    /// command output to jump between prompts, select the output of a command or show its exit
+    /// status. Report the exit status of the entered command with
+    /// [`Reedline::report_command_finished()`]. Terminals without support ignore the marks.
+    pub fn use_semantic_prompt_marks(mut self, enable: bool) -> Self {
+        self.painter.set_semantic_prompt_marks(enable);
+        self
+    }
+
+    /// Toggle whether reedline captures the mouse
+    ///
+    /// With the mouse captured, a click in the buffer moves the cursor there, a click on an
+    /// entry of the active menu selects it and the wheel scrolls through the menu pages.
+    /// While captured, the terminal can't select text with the mouse on its own, most
+    /// terminals still do so while shift is held.
+    pub fn use_mouse_capture(mut self, enable: bool) -> Self {
+        self.mouse_capture.set(enable);
+        self
+    }
+
     /// Return the previously generated history session id
     pub fn get_history_session_id(&self) -> Option<HistorySessionId> {
         self.history_session_id
@@ -369,6 +550,39 @@ impl Reedline {
         self
     }
 
+    /// A builder which keeps the buffer on the row of the prompt instead of wrapping it
+    ///
+    /// The visible part of the buffer scrolls horizontally to follow the cursor, `<` and `>`
+    /// mark the edges where it continues. Newlines in the buffer are shown as `↵`. Useful for
+    /// prompts embedded in a fixed region of the screen.
+    #[must_use]
+    pub fn with_horizontal_scroll(mut self, horizontal_scroll: bool) -> Self {
+        self.horizontal_scroll = horizontal_scroll.then(HorizontalScroll::default);
+        self
+    }
+
+    /// A builder that closes brackets and quotes automatically while typing
+    ///
+    /// Applies to the emacs and the vi insert mode. See [`AutoPairs`] for the pairs and how
+    /// they are typed. Each pair is inserted or deleted as a single undo step.
+    #[must_use]
+    pub fn with_auto_pairs(mut self, auto_pairs: AutoPairs) -> Self {
+        self.editor.set_auto_pairs(Some(auto_pairs));
+        self
+    }
+
+    /// A builder which paints a gutter with line numbers in front of the lines of the buffer
+    ///
+    /// The number of the line with the cursor is highlighted. The gutter replaces the
+    /// [`Prompt::render_prompt_multiline_indicator`] and lines too long for the terminal are
+    /// wrapped with a `↪` in the gutter, telling them apart from the lines of the buffer. Has no
+    /// effect together with [`Reedline::with_horizontal_scroll`].
+    #[must_use]
+    pub fn with_line_numbers(mut self, line_numbers: bool) -> Self {
+        self.line_numbers = line_numbers.then(LineNumberGutter::default);
+        self
+    }
+
     /// Update current working directory.
     #[must_use]
     pub fn with_cwd(mut self, cwd: Option<String>) -> Self {
@@ -446,6 +660,20 @@ impl Reedline {
         self
     }
 
+    /// A builder that registers [`EditorHooks`] following the changes of the editor
+    ///
+    /// The hooks learn about changes of the buffer, the cursor and the edit mode and can
+    /// rewrite or veto a buffer before it is submitted.
+    #[must_use]
+    pub fn with_editor_hooks(mut self, hooks: Box<dyn EditorHooks>) -> Self {
+        self.editor_hooks = Some(HookRunner::new(
+            hooks,
+            self.editor.line_buffer(),
+            self.prompt_edit_mode(),
+        ));
+        self
+    }
+
     /// A builder that configures the validator for your instance of the Reedline engine
     /// # Example
     /// ```rust
@@ -462,6 +690,25 @@ impl Reedline {
         self
     }
 
+    /// A builder that indents the lines of multi-line input while they are typed
+    ///
+    /// New lines start at the indentation the [`Indenter`] computes for them and lines are
+    /// dedented again when the indenter asks for it, e.g. for a closing bracket. Also sets the
+    /// level of indentation of [`EditCommand::IndentLines`] and [`EditCommand::DedentLines`].
+    /// # Example
+    /// ```rust
+    /// use reedline::{DefaultIndenter, DefaultValidator, Reedline};
+    ///
+    /// let mut line_editor = Reedline::create()
+    ///     .with_validator(Box::new(DefaultValidator))
+    ///     .with_indenter(Box::new(DefaultIndenter::default()));
+    /// ```
+    #[must_use]
+    pub fn with_indenter(mut self, indenter: Box<dyn Indenter>) -> Self {
+        self.editor.set_indenter(Some(indenter));
+        self
+    }
+
     /// A builder that configures the alternate text editor used to edit the line buffer
     ///
     /// You are responsible for providing a file path that is unique to this reedline session
@@ -495,6 +742,87 @@ impl Reedline {
         self
     }
 
+    /// A builder that sets the named keyboard macros, e.g. restored from the
+    /// configuration, that can be replayed with [`ReedlineEvent::CallMacro`]
+    #[must_use]
+    pub fn with_macros(mut self, macros: HashMap<String, KeyboardMacro>) -> Self {
+        self.macros = macros;
+        self
+    }
+
+    /// A builder that registers a command run in place by
+    /// [`ReedlineEvent::CustomCommand`] with the given `name`
+    ///
+    /// The command can change the buffer and the cursor through the [`Editor`] and look up
+    /// the [`History`]. Editing continues with the changed buffer afterwards.
+    ///
+    /// ```rust
+    /// use reedline::{Reedline, UndoBehavior};
+    ///
+    /// let line_editor = Reedline::create().with_custom_command("sudo", |editor, _history| {
+    ///     editor.edit_buffer(
+    ///         |line_buffer| {
+    ///             line_buffer.move_to_start();
+    ///             line_buffer.insert_str("sudo ");
+    ///             line_buffer.move_to_end();
+    ///         },
+    ///         UndoBehavior::CreateUndoPoint,
+    ///     )
+    /// });
+    /// ```
+    #[must_use]
+    pub fn with_custom_command<F>(mut self, name: impl Into<String>, command: F) -> Self
+    where
+        F: FnMut(&mut Editor, &dyn History) + Send + 'static,
+    {
+        self.custom_commands.insert(name.into(), Box::new(command));
+        self
+    }
+
+    /// A builder that repaints the prompt every `interval` while the user is
+    /// idle, e.g. to keep a clock in the prompt up to date
+    ///
+    /// A zero interval disables the refresh. Applies to [`Reedline::read_line()`]
+    /// and [`Reedline::read_line_with_idle_timeout()`].
+    #[must_use]
+    pub fn with_prompt_refresh(mut self, interval: Duration) -> Self {
+        self.prompt_refresh = (!interval.is_zero()).then_some(interval);
+        self
+    }
+
+    /// A builder that configures where the terminal events are read from
+    ///
+    /// Defaults to [`DefaultInputSource`] reading from the terminal. Use a
+    /// [`crate::VirtualTerminal`] to script the input for tests.
+    #[must_use]
+    pub fn with_input_source(mut self, input_source: Box<dyn InputSource>) -> Self {
+        self.input_source = input_source;
+        self
+    }
+
+    /// A builder that configures where the prompt and buffer are painted to
+    ///
+    /// Defaults to [`DefaultOutputSink`] writing to `stderr`. Use a
+    /// [`crate::VirtualTerminal`] to inspect the rendered screen in tests.
+    #[must_use]
+    pub fn with_output_sink(mut self, output_sink: Box<dyn OutputSink>) -> Self {
+        self.painter.set_output_sink(output_sink);
+        self
+    }
+
+    /// Use the clipboard of the terminal through OSC 52 for the commands working on the
+    /// system clipboard
+    ///
+    /// Applies to [`EditCommand::CopySelectionSystem`], [`EditCommand::CutSelectionSystem`] and
+    /// [`EditCommand::PasteSystem`].
+    #[cfg(feature = "system_clipboard")]
+    #[must_use]
+    pub fn with_osc52_clipboard(mut self, clipboard: Osc52Clipboard) -> Self {
+        self.osc52_terminal = Some(clipboard.terminal());
+        self.editor.set_system_clipboard(Box::new(clipboard));
+        self
+    }
+
     /// Remove the current [`Validator`]
     #[must_use]
     pub fn disable_validator(mut self) -> Self {
@@ -513,6 +841,11 @@ impl Reedline {
     #[must_use]
     pub fn with_edit_mode(mut self, edit_mode: Box<dyn EditMode>) -> Self {
         self.edit_mode = edit_mode;
+        // The initial mode of the new edit mode is no change the hooks need to hear about
+        let mode = self.prompt_edit_mode();
+        if let Some(hooks) = self.editor_hooks.as_mut() {
+            hooks.sync_mode(mode);
+        }
         self
     }
 
@@ -530,6 +863,18 @@ impl Reedline {
         self
     }
 
+    /// A builder that sets the menu painting the items of [`Reedline::select()`] and
+    /// [`Reedline::multi_select()`]
+    ///
+    /// Defaults to a [`ColumnarMenu`] with a single column, e.g. a
+    /// [`ListMenu`](crate::ListMenu) pages through long lists instead. Custom menus have to
+    /// implement [`Menu::selected_index()`] and [`Menu::select_index()`].
+    #[must_use]
+    pub fn with_select_menu(mut self, menu: Box<dyn Menu>) -> Self {
+        self.select_menu = Some(ReedlineMenu::EngineCompleter(menu));
+        self
+    }
+
     /// A builder that adds the history item id
     #[must_use]
     pub fn with_history_session_id(mut self, session: Option<HistorySessionId>) -> Self {
@@ -638,23 +983,280 @@ impl Reedline {
         }
     }
 
+    /// Report that the command entered with the last [`Reedline::read_line()`] finished
+    ///
+    /// Marks the end of the command output for the terminal when
+    /// [`Reedline::use_semantic_prompt_marks()`] is enabled, `exit_code` is shown as its
+    /// status. Call it before reading the next line.
+    pub fn report_command_finished(&mut self, exit_code: Option<i32>) -> Result<()> {
+        self.painter.mark_command_finished(exit_code)
+    }
+
     /// Wait for input and provide the user with a specified [`Prompt`].
     ///
     /// Returns a [`std::io::Result`] in which the `Err` type is [`std::io::Result`]
     /// and the `Ok` variant wraps a [`Signal`] which handles user inputs.
+    ///
+    /// If the input is not an interactive terminal (see [`InputSource::is_terminal()`]), the
+    /// line is read as is without any editing. The [`Validator`], the history and the
+    /// exclusion prefix still apply, the end of the input is reported as [`Signal::CtrlD`].
     pub fn read_line(&mut self, prompt: &dyn Prompt) -> Result<Signal> {
-        terminal::enable_raw_mode()?;
-        self.bracketed_paste.enter();
-        self.kitty_protocol.enter();
+        if !self.input_source.is_terminal() {
+            return self.read_plain_line(prompt);
+        }
+
+        self.enter_terminal()?;
+
+        let result = self.read_line_helper(prompt, None);
+
+        self.leave_terminal()?;
+        result
+    }
+
+    /// Wait for input like [`Reedline::read_line()`] but give up after
+    /// `idle_timeout` without any input.
+    ///
+    /// Returns [`Signal::IdleTimeout`] if the user didn't type anything for the
+    /// duration of `idle_timeout`. The buffer is kept as is.
+    pub fn read_line_with_idle_timeout(
+        &mut self,
+        prompt: &dyn Prompt,
+        idle_timeout: Duration,
+    ) -> Result<Signal> {
+        if !self.input_source.is_terminal() {
+            return self.read_plain_line(prompt);
+        }
+
+        self.enter_terminal()?;
+
+        let result = self.read_line_helper(prompt, Some(idle_timeout));
+
+        self.leave_terminal()?;
+        result
+    }
+
+    /// Returns a handle to repaint the prompt of the running line editor from
+    /// other threads
+    pub fn prompt_invalidator(&self) -> PromptInvalidator {
+        self.prompt_invalidator.clone()
+    }
+
+    /// Wait for input without blocking the async runtime and provide the user
+    /// with a specified [`Prompt`].
+    ///
+    /// Behaves like [`Reedline::read_line()`] but awaits the terminal events
+    /// from [`InputSource::event_stream()`]. Pastes are not batched, as waiting
+    /// for the rest of the pasted events would block the runtime.
+    /// The returned future is `Send` to run it in a task of a multi-threaded runtime.
+    ///
+    /// To cancel the line editor, e.g. from another task, drop the returned
+    /// future (for example by racing it against a cancellation signal with
+    /// `select!` or by wrapping it with `futures::future::abortable`). The
+    /// cursor is moved below the input area and the terminal is restored; the
+    /// buffer is kept for the next invocation.
+    ///
+    /// ```rust,no_run
+    /// use futures::{channel::oneshot, future, pin_mut};
+    /// use reedline::{DefaultPrompt, Reedline, Signal};
+    ///
+    /// async fn read_or_cancel(
+    ///     line_editor: &mut Reedline,
+    ///     cancel: oneshot::Receiver<()>,
+    /// ) -> std::io::Result<Option<Signal>> {
+    ///     let prompt = DefaultPrompt::default();
+    ///     let read_line = line_editor.read_line_async(&prompt);
+    ///     pin_mut!(read_line);
+    ///     match future::select(read_line, cancel).await {
+    ///         future::Either::Left((signal, _)) => signal.map(Some),
+    ///         // Dropping `read_line` restores the terminal
+    ///         future::Either::Right(_) => Ok(None),
+    ///     }
+    /// }
+    /// ```
+    #[cfg(feature = "async")]
+    pub async fn read_line_async(&mut self, prompt: &(dyn Prompt + Sync)) -> Result<Signal> {
+        if !self.input_source.is_terminal() {
+            return self.read_plain_line(prompt);
+        }
+
+        let session = AsyncSession::enter(self)?;
+
+        let result = session.reedline.read_line_async_helper(prompt).await;
+
+        session.exit()?;
+        result
+    }
+
+    /// Wait for a secret like a password, painting `mask` in place of every typed character
+    ///
+    /// Without a `mask` nothing of the secret is painted at all. The secret is edited in a
+    /// buffer of its own, the buffer edited before is kept as is. It never reaches the history,
+    /// the [`Hinter`], the [`Completer`], the [`Validator`], the [`EditorHooks`], the clipboard
+    /// or the external editor and its undo history is dropped once it is entered.
+    ///
+    /// Fails with [`io::ErrorKind::Unsupported`] if the input is not an interactive terminal,
+    /// reading the line without raw mode would echo the secret.
+    ///
+    /// ## Example
+    /// ```no_run
+    /// use reedline::{DefaultPrompt, DefaultPromptSegment, Reedline, Signal};
+    ///
+    /// let mut line_editor = Reedline::create();
+    /// let prompt = DefaultPrompt::new(
+    ///     DefaultPromptSegment::Basic("password".to_string()),
+    ///     DefaultPromptSegment::Empty,
+    /// );
+    ///
+    /// if let Ok(Signal::Success(password)) = line_editor.read_secret(&prompt, Some('*')) {
+    ///     // connect with the password
+    /// }
+    /// ```
+    pub fn read_secret(&mut self, prompt: &dyn Prompt, mask: Option<char>) -> Result<Signal> {
+        self.require_terminal()?;
+        let editor = std::mem::replace(&mut self.editor, Editor::with_local_clipboards());
+        self.reading_secret = true;
+        self.secret_mask = mask;
+
+        let result = self.read_line(prompt);
+
+        self.reading_secret = false;
+        self.secret_mask = None;
+        self.editor = editor;
+        result
+    }
+
+    /// Ask a yes or no question answered with a single key
+    ///
+    /// The choices are shown after the `prompt`, `y` and `n` answer right away. Enter answers
+    /// with the `default`, if there is one. Returns `None` if the user cancels with Esc, Ctrl-C
+    /// or Ctrl-D.
+    pub fn confirm(&mut self, prompt: &dyn Prompt, default: Option<bool>) -> Result<Option<bool>> {
+        self.require_terminal()?;
+        self.enter_terminal()?;
+
+        let result = self.confirm_helper(prompt, default);
+
+        self.leave_terminal()?;
+        result
+    }
+
+    /// Let the user pick one of the `items`
+    ///
+    /// The items are shown in the select menu, see [`Reedline::with_select_menu()`]. Typing
+    /// filters them, the keybindings of the edit mode move through them and Enter picks the
+    /// selected one. Returns the index of the picked item, `None` if the user cancels with Esc,
+    /// Ctrl-C or Ctrl-D.
+    pub fn select(
+        &mut self,
+        prompt: &dyn Prompt,
+        items: &[impl AsRef<str>],
+    ) -> Result<Option<usize>> {
+        let mut items = SelectItems::new(items, false);
+        Ok(self.run_select(prompt, &mut items)?.flatten())
+    }
+
+    /// Let the user check any of the `items`
+    ///
+    /// Works like [`Reedline::select()`], items are checked and unchecked with Space and Enter
+    /// finishes. Returns the indices of the checked items, `None` if the user cancels.
+    pub fn multi_select(
+        &mut self,
+        prompt: &dyn Prompt,
+        items: &[impl AsRef<str>],
+    ) -> Result<Option<Vec<usize>>> {
+        let mut items = SelectItems::new(items, true);
+        Ok(self
+            .run_select(prompt, &mut items)?
+            .map(|_| items.checked()))
+    }
+
+    /// The widgets read single keys and secrets must not be echoed, neither works without a
+    /// terminal
+    fn require_terminal(&self) -> Result<()> {
+        if self.input_source.is_terminal() {
+            Ok(())
+        } else {
+            Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "the input is not an interactive terminal",
+            ))
+        }
+    }
+
+    /// Runs the select menu for `items` in raw mode
+    ///
+    /// Returns the selected item once Enter was pressed, `None` if the user cancelled.
+    fn run_select(
+        &mut self,
+        prompt: &dyn Prompt,
+        items: &mut SelectItems,
+    ) -> Result<Option<Option<usize>>> {
+        self.require_terminal()?;
+        let mut menu = self.select_menu.take().unwrap_or_else(|| {
+            ReedlineMenu::EngineCompleter(Box::new(
+                ColumnarMenu::default()
+                    .with_name("select_menu")
+                    .with_columns(1),
+            ))
+        });
+        self.enter_terminal()?;
 
-        let result = self.read_line_helper(prompt);
+        let result = self.select_helper(prompt, items, &mut menu);
 
-        self.bracketed_paste.exit();
-        self.kitty_protocol.exit();
-        terminal::disable_raw_mode()?;
+        menu.menu_event(MenuEvent::Deactivate);
+        self.select_menu = Some(menu);
+        self.leave_terminal()?;
         result
     }
 
+    /// Snapshot of the buffer, its undo history, the selection and the edit mode state
+    ///
+    /// The [`SessionSnapshot`] can be serialized to carry a half-typed command across a
+    /// restart of the process, see [`Reedline::restore_session_snapshot()`].
+    pub fn session_snapshot(&self) -> SessionSnapshot {
+        SessionSnapshot::new(self.editor.state(), self.edit_mode.mode_state())
+    }
+
+    /// Continue editing from a [`SessionSnapshot`] taken with [`Reedline::session_snapshot()`]
+    ///
+    /// The restored buffer is shown by the next [`Reedline::read_line()`].
+    pub fn restore_session_snapshot(&mut self, snapshot: SessionSnapshot) {
+        let (editor, edit_mode) = snapshot.into_parts();
+        self.editor.restore_state(editor);
+        if let Some(state) = edit_mode {
+            self.edit_mode.restore_mode_state(&state);
+        }
+    }
+
+    /// Returns the keyboard macro recorded last, if any
+    pub fn last_macro(&self) -> Option<&KeyboardMacro> {
+        self.last_macro.as_ref()
+    }
+
+    /// Checks if a keyboard macro is currently being recorded
+    pub fn is_recording_macro(&self) -> bool {
+        self.recording_macro.is_some()
+    }
+
+    /// Store the last recorded keyboard macro under `name` to replay it with
+    /// [`ReedlineEvent::CallMacro`]
+    ///
+    /// Returns `false` if no macro was recorded yet
+    pub fn save_last_macro(&mut self, name: impl Into<String>) -> bool {
+        match &self.last_macro {
+            Some(keyboard_macro) => {
+                self.macros.insert(name.into(), keyboard_macro.clone());
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Returns the named keyboard macros, e.g. to persist them
+    pub fn macros(&self) -> &HashMap<String, KeyboardMacro> {
+        &self.macros
+    }
+
     /// Returns the current insertion point of the input buffer.
     pub fn current_insertion_point(&self) -> usize {
         self.editor.insertion_point()
@@ -685,139 +1287,513 @@ impl Reedline {
         Ok(())
     }
 
-    /// Helper implementing the logic for [`Reedline::read_line()`] to be wrapped
-    /// in a `raw_mode` context.
-    fn read_line_helper(&mut self, prompt: &dyn Prompt) -> Result<Signal> {
-        self.painter
-            .initialize_prompt_position(self.suspended_state.as_ref())?;
-        if self.suspended_state.is_some() {
-            // Last editor was suspended to run a ExecuteHostCommand event,
-            // we are resuming operation now.
-            self.suspended_state = None;
-        }
-        self.hide_hints = false;
-
-        self.repaint(prompt)?;
-
-        loop {
-            #[cfg(feature = "external_printer")]
-            if let Some(ref external_printer) = self.external_printer {
-                // get messages from printer as crlf separated "lines"
-                let messages = Self::external_messages(external_printer)?;
-                if !messages.is_empty() {
-                    // print the message(s)
-                    self.painter.print_external_message(
-                        messages,
-                        self.editor.line_buffer(),
-                        prompt,
-                    )?;
-                    self.repaint(prompt)?;
+    /// Reads a line without raw mode when the input is not a terminal
+    ///
+    /// Lines are joined for as long as the [`Validator`] reports them as incomplete.
+    fn read_plain_line(&mut self, prompt: &dyn Prompt) -> Result<Signal> {
+        'line: loop {
+            self.painter.paint_plain(&format!(
+                "{}{}",
+                prompt.render_prompt_left(),
+                prompt.render_prompt_indicator(self.prompt_edit_mode())
+            ))?;
+
+            let mut buffer = match self.input_source.read_plain_line()? {
+                Some(line) => line,
+                None => return Ok(Signal::CtrlD),
+            };
+            loop {
+                match self.validate(&buffer) {
+                    Some(ValidationResult::Incomplete) => {
+                        self.painter
+                            .paint_plain(&prompt.render_prompt_multiline_indicator())?;
+                        match self.input_source.read_plain_line()? {
+                            Some(line) => {
+                                buffer.push('\n');
+                                buffer.push_str(&line);
+                            }
+                            None => break,
+                        }
+                    }
+                    // Nothing can fix the line, report why it's dropped and read the next one
+                    Some(ValidationResult::Invalid(diagnostics)) => {
+                        let report = render_diagnostics(&diagnostics, false);
+                        self.painter
+                            .paint_plain(&format!("{}\n", report.trim_start()))?;
+                        continue 'line;
+                    }
+                    _ => break,
                 }
             }
-
-            // Helper function that returns true if the input is complete and
-            // can be sent to the hosting application.
-            fn completed(events: &[Event]) -> bool {
-                if let Some(event) = events.last() {
-                    matches!(
-                        event,
-                        Event::Key(KeyEvent {
-                            code: KeyCode::Enter,
-                            modifiers: KeyModifiers::NONE,
-                            ..
-                        })
-                    )
-                } else {
-                    false
+            if let Some(hooks) = self.active_hooks() {
+                match hooks.pre_submit(&buffer) {
+                    PreSubmit::Accept => {}
+                    PreSubmit::Rewrite(rewritten) => buffer = rewritten,
+                    // There is nothing to keep editing, read the next line instead
+                    PreSubmit::Veto => continue 'line,
                 }
             }
+            self.save_submitted_line(&buffer);
+            if let Some(hooks) = self.active_hooks() {
+                hooks.post_submit(&buffer);
+            }
 
-            let mut events: Vec<Event> = vec![];
+            return Ok(Signal::Success(buffer));
+        }
+    }
 
-            // If the `external_printer` feature is enabled, we need to
-            // periodically yield so that external printers get a chance to
-            // print. Otherwise, we can just block until we receive an event.
-            #[cfg(feature = "external_printer")]
-            if event::poll(EXTERNAL_PRINTER_WAIT)? {
-                events.push(crossterm::event::read()?);
-            }
-            #[cfg(not(feature = "external_printer"))]
-            events.push(crossterm::event::read()?);
+    /// Puts the terminal into raw mode and enables the configured terminal extensions
+    fn enter_terminal(&mut self) -> Result<()> {
+        self.input_source.enable_raw_mode()?;
+        self.bracketed_paste.enter(self.painter.output_sink());
+        self.kitty_protocol.enter(self.painter.output_sink());
+        self.mouse_capture.enter(self.painter.output_sink());
+        Ok(())
+    }
 
-            // Receive all events in the queue without blocking. Will stop when
-            // a line of input is completed.
-            while !completed(&events) && event::poll(Duration::from_millis(0))? {
-                events.push(crossterm::event::read()?);
-            }
+    /// Restores the terminal set up by [`Reedline::enter_terminal()`]
+    fn leave_terminal(&mut self) -> Result<()> {
+        self.bracketed_paste.exit(self.painter.output_sink());
+        self.kitty_protocol.exit(self.painter.output_sink());
+        self.mouse_capture.exit(self.painter.output_sink());
+        self.input_source.disable_raw_mode()
+    }
 
-            // If we believe there's text pasting or resizing going on, batch
-            // more events at the cost of a slight delay.
-            if events.len() > EVENTS_THRESHOLD
+    /// Stops the process like a shell job until it receives `SIGCONT`
+    ///
+    /// The terminal is handed back in its original state while the process is stopped.
+    #[cfg(unix)]
+    fn suspend(&mut self) -> Result<()> {
+        use rustix::process::{kill_current_process_group, Signal as ProcessSignal};
+
+        self.painter.move_cursor_to_end()?;
+        self.painter.print_crlf()?;
+        let suspended_state = self.painter.state_before_suspension();
+        if self.cursor_shapes.is_some() {
+            let stdout = self.painter.output_sink();
+            stdout.queue(SetCursorStyle::DefaultUserShape)?;
+            stdout.flush()?;
+        }
+        self.leave_terminal()?;
+
+        // Like Ctrl-Z outside of raw mode, this stops the whole foreground job. Execution
+        // continues once the shell sends SIGCONT.
+        let stopped = kill_current_process_group(ProcessSignal::Tstp);
+
+        self.enter_terminal()?;
+        self.painter
+            .initialize_prompt_position(Some(&suspended_state))?;
+        Ok(stopped?)
+    }
+
+    /// Helper implementing the logic for [`Reedline::read_line()`] to be wrapped
+    /// in a `raw_mode` context.
+    fn read_line_helper(
+        &mut self,
+        prompt: &dyn Prompt,
+        idle_timeout: Option<Duration>,
+    ) -> Result<Signal> {
+        self.start_read_line(prompt)?;
+
+        let mut last_input = Instant::now();
+        let mut next_refresh = self.prompt_refresh.map(|interval| last_input + interval);
+
+        loop {
+            #[cfg(feature = "external_printer")]
+            self.print_external_messages(prompt)?;
+
+            #[cfg(feature = "external_printer")]
+            if let Some(signal) = self.handle_injected_events(prompt)? {
+                return Ok(signal);
+            }
+
+            let mut events: Vec<Event> = vec![];
+
+            // If external printers, prompt refreshes, the idle timeout or a
+            // pending key sequence need to run, we periodically yield. Otherwise, we can just block until
+            // we receive an event.
+            match self.input_wait(last_input, idle_timeout, next_refresh) {
+                Some(wait) => {
+                    if self.input_source.poll(wait)? {
+                        events.push(self.input_source.read()?);
+                    }
+                }
+                None => events.push(self.input_source.read()?),
+            }
+
+            if events.is_empty() {
+                if let Some(signal) =
+                    self.handle_no_input(prompt, last_input, idle_timeout, &mut next_refresh)?
+                {
+                    return Ok(signal);
+                }
+                continue;
+            }
+            last_input = Instant::now();
+
+            // Receive all events in the queue without blocking. Will stop when
+            // a line of input is completed.
+            while !completed(&events) && self.input_source.poll(Duration::from_millis(0))? {
+                events.push(self.input_source.read()?);
+            }
+
+            // If we believe there's text pasting or resizing going on, batch
+            // more events at the cost of a slight delay.
+            if events.len() > EVENTS_THRESHOLD
                 || events.iter().any(|e| matches!(e, Event::Resize(_, _)))
             {
-                while !completed(&events) && event::poll(POLL_WAIT)? {
-                    events.push(crossterm::event::read()?);
+                while !completed(&events) && self.input_source.poll(POLL_WAIT)? {
+                    events.push(self.input_source.read()?);
                 }
             }
 
-            // Convert `Event` into `ReedlineEvent`. Also, fuse consecutive
-            // `ReedlineEvent::EditCommand` into one. Also, if there're multiple
-            // `ReedlineEvent::Resize`, only keep the last one.
-            let mut reedline_events: Vec<ReedlineEvent> = vec![];
-            let mut edits = vec![];
-            let mut resize = None;
-            for event in events {
-                if let Ok(event) = ReedlineRawEvent::try_from(event) {
-                    match self.edit_mode.parse_event(event) {
-                        ReedlineEvent::Edit(edit) => edits.extend(edit),
-                        ReedlineEvent::Resize(x, y) => resize = Some((x, y)),
-                        event => {
-                            if !edits.is_empty() {
-                                reedline_events
-                                    .push(ReedlineEvent::Edit(std::mem::take(&mut edits)));
-                            }
-                            reedline_events.push(event);
-                        }
+            if let Some(signal) = self.handle_events(prompt, events)? {
+                return Ok(signal);
+            }
+        }
+    }
+
+    /// Helper implementing the logic for [`Reedline::read_line_async()`] to be
+    /// wrapped in a `raw_mode` context.
+    #[cfg(feature = "async")]
+    async fn read_line_async_helper(&mut self, prompt: &(dyn Prompt + Sync)) -> Result<Signal> {
+        let mut event_stream = self.input_source.event_stream();
+
+        self.start_read_line(prompt)?;
+
+        let mut last_input = Instant::now();
+        let mut next_refresh = self.prompt_refresh.map(|interval| last_input + interval);
+
+        loop {
+            #[cfg(feature = "external_printer")]
+            self.print_external_messages(prompt)?;
+
+            #[cfg(feature = "external_printer")]
+            if let Some(signal) = self.handle_injected_events(prompt)? {
+                return Ok(signal);
+            }
+
+            let mut events: Vec<Event> = vec![];
+
+            // Yield to the runtime until the next event arrives, or until external printers,
+            // prompt refreshes or a pending key sequence need to run like in `read_line`
+            let next_event = match self.input_wait(last_input, None, next_refresh) {
+                Some(wait) => {
+                    match future::select(event_stream.next(), futures_timer::Delay::new(wait)).await
+                    {
+                        Either::Left((event, _)) => Some(event),
+                        Either::Right(_) => None,
                     }
                 }
+                None => Some(event_stream.next().await),
+            };
+            match next_event {
+                Some(Some(event)) => events.push(event?),
+                Some(None) => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::UnexpectedEof,
+                        "input event stream closed",
+                    ))
+                }
+                None => {}
+            }
+
+            if events.is_empty() {
+                if let Some(signal) =
+                    self.handle_no_input(prompt, last_input, None, &mut next_refresh)?
+                {
+                    return Ok(signal);
+                }
+                continue;
+            }
+            last_input = Instant::now();
+
+            // Receive all events in the queue without blocking. Will stop when
+            // a line of input is completed. Waiting for more events of a paste
+            // would block the runtime, so unlike `read_line` we don't batch them.
+            while !completed(&events) && self.input_source.poll(Duration::from_millis(0))? {
+                events.push(self.input_source.read()?);
             }
-            if !edits.is_empty() {
-                reedline_events.push(ReedlineEvent::Edit(edits));
+
+            if let Some(signal) = self.handle_events(prompt, events)? {
+                return Ok(signal);
+            }
+        }
+    }
+
+    /// How long to wait for input before yielding, `None` blocks until the
+    /// next event
+    fn input_wait(
+        &self,
+        last_input: Instant,
+        idle_timeout: Option<Duration>,
+        next_refresh: Option<Instant>,
+    ) -> Option<Duration> {
+        let now = Instant::now();
+        let mut waits = vec![];
+        #[cfg(feature = "external_printer")]
+        waits.push(EXTERNAL_PRINTER_WAIT);
+        if self.prompt_invalidator.is_shared() {
+            waits.push(PROMPT_INVALIDATION_WAIT);
+        }
+        if let Some(timeout) = idle_timeout {
+            waits.push((last_input + timeout).saturating_duration_since(now));
+        }
+        if let Some(at) = next_refresh {
+            waits.push(at.saturating_duration_since(now));
+        }
+        if let Some(timeout) = self.edit_mode.pending_sequence_timeout() {
+            waits.push((last_input + timeout).saturating_duration_since(now));
+        }
+        waits.into_iter().min()
+    }
+
+    /// Run the work that is due when no input arrived within the wait of
+    /// [`Reedline::input_wait()`]
+    ///
+    /// Returns the [`Signal`] if the idle timeout or the resolved key sequence exits the
+    /// line editor
+    fn handle_no_input(
+        &mut self,
+        prompt: &dyn Prompt,
+        last_input: Instant,
+        idle_timeout: Option<Duration>,
+        next_refresh: &mut Option<Instant>,
+    ) -> Result<Option<Signal>> {
+        let now = Instant::now();
+        let sequence_expired = self
+            .edit_mode
+            .pending_sequence_timeout()
+            .map_or(false, |timeout| now >= last_input + timeout);
+        if sequence_expired {
+            let event = self.edit_mode.resolve_pending_sequence();
+            if let Some(signal) = self.handle_reedline_events(prompt, vec![event], true)? {
+                return Ok(Some(signal));
             }
-            if let Some((x, y)) = resize {
-                reedline_events.push(ReedlineEvent::Resize(x, y));
+        }
+        if idle_timeout.map_or(false, |timeout| now >= last_input + timeout) {
+            self.painter.move_cursor_to_end()?;
+            return Ok(Some(Signal::IdleTimeout));
+        }
+        let refresh = next_refresh.map_or(false, |at| now >= at);
+        if refresh {
+            *next_refresh = self.prompt_refresh.map(|interval| now + interval);
+        }
+        if refresh || self.prompt_invalidator.take_invalidated() {
+            self.repaint(prompt)?;
+        }
+        Ok(None)
+    }
+
+    /// Paint the initial prompt of a new line editor invocation
+    fn start_read_line(&mut self, prompt: &dyn Prompt) -> Result<()> {
+        self.painter
+            .initialize_prompt_position(self.suspended_state.as_ref())?;
+        if self.suspended_state.is_some() {
+            // Last editor was suspended to run a ExecuteHostCommand event,
+            // we are resuming operation now.
+            self.suspended_state = None;
+        }
+        self.hide_hints = false;
+
+        self.repaint(prompt)
+    }
+
+    #[cfg(feature = "external_printer")]
+    fn print_external_messages(&mut self, prompt: &dyn Prompt) -> Result<()> {
+        if let Some(ref external_printer) = self.external_printer {
+            // get messages from printer as crlf separated "lines"
+            let messages = Self::external_messages(external_printer)?;
+            if !messages.is_empty() {
+                // print the message(s)
+                self.painter
+                    .print_external_message(messages, self.editor.line_buffer(), prompt)?;
+                self.repaint(prompt)?;
             }
+        }
+        Ok(())
+    }
+
+    #[cfg(feature = "external_printer")]
+    fn handle_injected_events(&mut self, prompt: &dyn Prompt) -> Result<Option<Signal>> {
+        let events = match self.event_injector {
+            Some(ref event_injector) => event_injector.pending_events(),
+            None => return Ok(None),
+        };
+        self.handle_reedline_events(prompt, events, false)
+    }
 
-            // Handle reedline events.
-            for event in reedline_events {
-                match self.handle_event(prompt, event)? {
-                    EventStatus::Exits(signal) => {
-                        // Check if we are merely suspended (to process an ExecuteHostCommand event)
-                        // or if we're about to quit the editor.
-                        if self.suspended_state.is_none() {
-                            // We are about to quit the editor, move the cursor below the input
-                            // area, for external commands or new read_line call
-                            self.painter.move_cursor_to_end()?;
+    /// Parse and handle a batch of terminal events
+    ///
+    /// Returns the [`Signal`] if one of the events exits the line editor
+    fn handle_events(&mut self, prompt: &dyn Prompt, events: Vec<Event>) -> Result<Option<Signal>> {
+        // Convert `Event` into `ReedlineEvent`. Also, fuse consecutive
+        // `ReedlineEvent::EditCommand` into one. Also, if there're multiple
+        // `ReedlineEvent::Resize`, only keep the last one.
+        let mut reedline_events: Vec<ReedlineEvent> = vec![];
+        let mut edits = vec![];
+        let mut resize = None;
+        for event in events {
+            if let Ok(event) = ReedlineRawEvent::try_from(event) {
+                match self.edit_mode.parse_event(event) {
+                    ReedlineEvent::Edit(edit) => edits.extend(edit),
+                    ReedlineEvent::Resize(x, y) => resize = Some((x, y)),
+                    event => {
+                        if !edits.is_empty() {
+                            reedline_events.push(ReedlineEvent::Edit(std::mem::take(&mut edits)));
                         }
-                        return Ok(signal);
-                    }
-                    EventStatus::Handled => {
-                        self.repaint(prompt)?;
+                        reedline_events.push(event);
                     }
-                    EventStatus::Inapplicable => {
-                        // Nothing changed, no need to repaint
+                }
+            }
+        }
+        if !edits.is_empty() {
+            reedline_events.push(ReedlineEvent::Edit(edits));
+        }
+        if let Some((x, y)) = resize {
+            reedline_events.push(ReedlineEvent::Resize(x, y));
+        }
+
+        self.handle_reedline_events(prompt, reedline_events, true)
+    }
+
+    /// Handle reedline events, repainting after every handled one
+    ///
+    /// Events coming from the edit mode are `recorded` into the keyboard macro
+    /// if one is being recorded.
+    /// Returns the [`Signal`] if one of the events exits the line editor
+    fn handle_reedline_events(
+        &mut self,
+        prompt: &dyn Prompt,
+        reedline_events: Vec<ReedlineEvent>,
+        recorded: bool,
+    ) -> Result<Option<Signal>> {
+        for event in reedline_events {
+            if recorded {
+                self.record_macro_event(&event);
+            }
+            // Calls of other macros are recorded as the events they replay
+            self.recording_macro_call = recorded
+                && matches!(
+                    event,
+                    ReedlineEvent::CallLastMacro(_) | ReedlineEvent::CallMacro(_, _)
+                );
+            let status = self.handle_event(prompt, event);
+            self.recording_macro_call = false;
+
+            match status? {
+                EventStatus::Exits(signal) => {
+                    // Check if we are merely suspended (to process an ExecuteHostCommand event)
+                    // or if we're about to quit the editor.
+                    if self.suspended_state.is_none() {
+                        // We are about to quit the editor, move the cursor below the input
+                        // area, for external commands or new read_line call
+                        self.painter.move_cursor_to_end()?;
+                        if matches!(signal, Signal::Success(_)) {
+                            self.painter.mark_command_executed()?;
+                        }
                     }
+                    return Ok(Some(signal));
                 }
+                EventStatus::Handled => {
+                    self.report_changes();
+                    self.repaint(prompt)?;
+                }
+                EventStatus::Inapplicable => {
+                    // Nothing changed, no need to repaint
+                }
+            }
+        }
+        Ok(None)
+    }
+
+    fn record_macro_event(&mut self, event: &ReedlineEvent) {
+        if !self.reading_secret {
+            if let Some(keyboard_macro) = self.recording_macro.as_mut() {
+                keyboard_macro.record(event);
             }
         }
     }
 
     fn handle_event(&mut self, prompt: &dyn Prompt, event: ReedlineEvent) -> Result<EventStatus> {
-        if self.input_mode == InputMode::HistorySearch {
-            self.handle_history_search_event(event)
-        } else {
-            self.handle_editor_event(prompt, event)
+        match event {
+            ReedlineEvent::StartMacro
+            | ReedlineEvent::EndMacro
+            | ReedlineEvent::CallLastMacro(_)
+            | ReedlineEvent::CallMacro(_, _) => self.handle_macro_event(prompt, event),
+            event if self.input_mode == InputMode::HistorySearch => {
+                self.handle_history_search_event(event)
+            }
+            event => self.handle_editor_event(prompt, event),
+        }
+    }
+
+    fn handle_macro_event(
+        &mut self,
+        prompt: &dyn Prompt,
+        event: ReedlineEvent,
+    ) -> Result<EventStatus> {
+        match event {
+            ReedlineEvent::StartMacro => {
+                self.recording_macro = Some(KeyboardMacro::default());
+                Ok(EventStatus::Handled)
+            }
+            ReedlineEvent::EndMacro => match self.recording_macro.take() {
+                Some(keyboard_macro) => {
+                    self.last_macro = Some(keyboard_macro);
+                    Ok(EventStatus::Handled)
+                }
+                None => Ok(EventStatus::Inapplicable),
+            },
+            ReedlineEvent::CallLastMacro(repeat) => match self.last_macro.clone() {
+                Some(keyboard_macro) => self.play_macro(prompt, &keyboard_macro, repeat),
+                None => Ok(EventStatus::Inapplicable),
+            },
+            ReedlineEvent::CallMacro(name, repeat) => match self.macros.get(&name).cloned() {
+                Some(keyboard_macro) => self.play_macro(prompt, &keyboard_macro, repeat),
+                None => Ok(EventStatus::Inapplicable),
+            },
+            _ => Ok(EventStatus::Inapplicable),
+        }
+    }
+
+    fn play_macro(
+        &mut self,
+        prompt: &dyn Prompt,
+        keyboard_macro: &KeyboardMacro,
+        repeat: usize,
+    ) -> Result<EventStatus> {
+        // Macros calling macros could recurse endlessly
+        if self.playing_macro {
+            return Ok(EventStatus::Inapplicable);
+        }
+
+        self.playing_macro = true;
+        let result = self.play_macro_events(prompt, keyboard_macro, repeat);
+        self.playing_macro = false;
+        result
+    }
+
+    fn play_macro_events(
+        &mut self,
+        prompt: &dyn Prompt,
+        keyboard_macro: &KeyboardMacro,
+        repeat: usize,
+    ) -> Result<EventStatus> {
+        let mut status = EventStatus::Inapplicable;
+        for _ in 0..repeat {
+            for event in keyboard_macro.events() {
+                if self.recording_macro_call {
+                    self.record_macro_event(event);
+                }
+                match self.handle_event(prompt, event.clone())? {
+                    EventStatus::Handled => status = EventStatus::Handled,
+                    EventStatus::Inapplicable => {}
+                    EventStatus::Exits(signal) => return Ok(EventStatus::Exits(signal)),
+                }
+            }
         }
+        Ok(status)
     }
 
     fn handle_history_search_event(&mut self, event: ReedlineEvent) -> io::Result<EventStatus> {
@@ -858,6 +1834,10 @@ impl Reedline {
                 self.painter.clear_scrollback()?;
                 Ok(EventStatus::Handled)
             }
+            #[cfg(unix)]
+            ReedlineEvent::Suspend => self.suspend().map(|_| EventStatus::Handled),
+            #[cfg(not(unix))]
+            ReedlineEvent::Suspend => Ok(EventStatus::Inapplicable),
             ReedlineEvent::Enter
             | ReedlineEvent::HistoryHintComplete
             | ReedlineEvent::Submit
@@ -878,7 +1858,6 @@ impl Reedline {
                 self.run_history_commands(&commands);
                 Ok(EventStatus::Handled)
             }
-            ReedlineEvent::Mouse => Ok(EventStatus::Handled),
             ReedlineEvent::Resize(width, height) => {
                 self.painter.handle_resize(width, height);
                 Ok(EventStatus::Handled)
@@ -924,7 +1903,14 @@ impl Reedline {
             | ReedlineEvent::MenuLeft
             | ReedlineEvent::MenuRight
             | ReedlineEvent::MenuPageNext
-            | ReedlineEvent::MenuPagePrevious => Ok(EventStatus::Inapplicable),
+            | ReedlineEvent::MenuPagePrevious
+            | ReedlineEvent::MouseClick(_, _)
+            | ReedlineEvent::StartMacro
+            | ReedlineEvent::EndMacro
+            | ReedlineEvent::CallLastMacro(_)
+            | ReedlineEvent::CallMacro(_, _)
+            | ReedlineEvent::ViChangeMode(_)
+            | ReedlineEvent::CustomCommand(_) => Ok(EventStatus::Inapplicable),
         }
     }
 
@@ -933,6 +1919,9 @@ impl Reedline {
         prompt: &dyn Prompt,
         event: ReedlineEvent,
     ) -> io::Result<EventStatus> {
+        if self.reading_secret && Self::exposes_secret(&event) {
+            return Ok(EventStatus::Inapplicable);
+        }
         match event {
             ReedlineEvent::Menu(name) => {
                 if self.active_menu().is_none() {
@@ -1113,11 +2102,16 @@ impl Reedline {
                 }
 
                 let buffer = self.editor.get_buffer().to_string();
-                match self.validator.as_mut().map(|v| v.validate(&buffer)) {
+                match self.validate(&buffer) {
                     None | Some(ValidationResult::Complete) => Ok(self.submit_buffer(prompt)?),
                     Some(ValidationResult::Incomplete) => {
                         self.run_edit_commands(&[EditCommand::InsertNewline]);
 
+                        Ok(EventStatus::Handled)
+                    }
+                    Some(ValidationResult::Invalid(diagnostics)) => {
+                        self.diagnostics = diagnostics;
+
                         Ok(EventStatus::Handled)
                     }
                 }
@@ -1140,11 +2134,16 @@ impl Reedline {
                     self.run_edit_commands(&[EditCommand::InsertNewline]);
                     return Ok(EventStatus::Handled);
                 }
-                match self.validator.as_mut().map(|v| v.validate(&buffer)) {
+                match self.validate(&buffer) {
                     None | Some(ValidationResult::Complete) => Ok(self.submit_buffer(prompt)?),
                     Some(ValidationResult::Incomplete) => {
                         self.run_edit_commands(&[EditCommand::InsertNewline]);
 
+                        Ok(EventStatus::Handled)
+                    }
+                    Some(ValidationResult::Invalid(diagnostics)) => {
+                        self.diagnostics = diagnostics;
+
                         Ok(EventStatus::Handled)
                     }
                 }
@@ -1197,6 +2196,20 @@ impl Reedline {
                 Ok(EventStatus::Handled)
             }
             ReedlineEvent::OpenEditor => self.open_editor().map(|_| EventStatus::Handled),
+            ReedlineEvent::CustomCommand(name) => {
+                self.leave_history_traversal();
+                match self.custom_commands.get_mut(&name) {
+                    Some(command) => {
+                        command(&mut self.editor, self.history.as_ref());
+                        Ok(EventStatus::Handled)
+                    }
+                    None => Ok(EventStatus::Inapplicable),
+                }
+            }
+            #[cfg(unix)]
+            ReedlineEvent::Suspend => self.suspend().map(|_| EventStatus::Handled),
+            #[cfg(not(unix))]
+            ReedlineEvent::Suspend => Ok(EventStatus::Inapplicable),
             ReedlineEvent::Resize(width, height) => {
                 self.painter.handle_resize(width, height);
                 Ok(EventStatus::Handled)
@@ -1268,7 +2281,70 @@ impl Reedline {
                 // Exhausting the event handlers is still considered handled
                 Ok(EventStatus::Inapplicable)
             }
-            ReedlineEvent::None | ReedlineEvent::Mouse => Ok(EventStatus::Inapplicable),
+            ReedlineEvent::StartMacro
+            | ReedlineEvent::EndMacro
+            | ReedlineEvent::CallLastMacro(_)
+            | ReedlineEvent::CallMacro(_, _) => self.handle_macro_event(prompt, event),
+            ReedlineEvent::MouseClick(column, row) => {
+                Ok(self.handle_mouse_click(prompt, column, row))
+            }
+            // Applied by the vi edit mode, ignored with the others
+            ReedlineEvent::None | ReedlineEvent::ViChangeMode(_) => Ok(EventStatus::Inapplicable),
+        }
+    }
+
+    /// Selects the clicked entry of the active menu or moves the cursor to the clicked
+    /// position in the buffer
+    fn handle_mouse_click(&mut self, prompt: &dyn Prompt, column: u16, row: u16) -> EventStatus {
+        let screen_width = self.painter.screen_width();
+
+        if let Some((menu_row, available_lines)) = self.painter.menu_relative_row(row) {
+            let selected = self.active_menu().map_or(false, |menu| {
+                menu.select_at(column, menu_row, available_lines, screen_width)
+            });
+            return if selected {
+                EventStatus::Handled
+            } else {
+                EventStatus::Inapplicable
+            };
+        }
+
+        let row = match self.painter.prompt_relative_row(row) {
+            Some(row) => row,
+            None => return EventStatus::Inapplicable,
+        };
+
+        // Lay out the prompt like it was painted
+        let mut lines = PromptLines::new(prompt, self.prompt_edit_mode(), None, "", "", "");
+        if let Some(menu) = self.menus.iter().find(|menu| menu.is_active()) {
+            lines.prompt_indicator = menu.indicator().to_owned().into();
+        }
+
+        let (prompt_width, prompt_row) = lines.cursor_pos(screen_width);
+        let position = match (&self.horizontal_scroll, &self.line_numbers) {
+            (Some(scroll), _) => (row == prompt_row && column >= prompt_width)
+                .then(|| scroll.buffer_offset_at((column - prompt_width) as usize)),
+            (None, Some(gutter)) => row
+                .checked_sub(prompt_row)
+                .and_then(|row| gutter.buffer_offset_at(column as usize, row as usize)),
+            (None, None) => lines.buffer_offset_at(
+                column,
+                row,
+                screen_width,
+                self.editor.get_buffer(),
+                &prompt.render_prompt_multiline_indicator(),
+            ),
+        };
+        match position {
+            Some(position) => {
+                self.deactivate_menus();
+                self.run_edit_commands(&[EditCommand::MoveToPosition {
+                    position,
+                    select: false,
+                }]);
+                EventStatus::Handled
+            }
+            None => EventStatus::Inapplicable,
         }
     }
 
@@ -1463,6 +2539,59 @@ impl Reedline {
 
     /// Executes [`EditCommand`] actions by modifying the internal state appropriately. Does not output itself.
     pub fn run_edit_commands(&mut self, commands: &[EditCommand]) {
+        self.leave_history_traversal();
+
+        // Run the commands over the edit buffer
+        for command in commands {
+            #[cfg(feature = "system_clipboard")]
+            if matches!(command, EditCommand::PasteSystem) {
+                self.read_osc52_clipboard();
+            }
+            self.editor.run_edit_command(command);
+        }
+        #[cfg(feature = "system_clipboard")]
+        self.write_osc52_copies();
+        self.report_changes();
+    }
+
+    /// Asks the terminal for the clipboard the following paste of the [`Osc52Clipboard`]
+    /// inserts, if it reads pastes from the terminal
+    #[cfg(feature = "system_clipboard")]
+    fn read_osc52_clipboard(&mut self) {
+        let terminal = match &self.osc52_terminal {
+            Some(terminal) => terminal.clone(),
+            None => return,
+        };
+        if let Some(timeout) = terminal.paste_timeout() {
+            // Copies made before go out first, the terminal answers in order
+            self.write_osc52_copies();
+            let sink = self.painter.output_sink();
+            let reply = match sink.write_all(PASTE_REQUEST).and_then(|()| sink.flush()) {
+                Ok(()) => self
+                    .input_source
+                    .read_terminal_reply(timeout)
+                    .unwrap_or_default(),
+                Err(_) => None,
+            };
+            terminal.set_reply(reply.as_deref());
+        }
+    }
+
+    /// Writes the copies to the [`Osc52Clipboard`] to the terminal
+    #[cfg(feature = "system_clipboard")]
+    fn write_osc52_copies(&mut self) {
+        if let Some(terminal) = &self.osc52_terminal {
+            let output = terminal.take_output();
+            if !output.is_empty() {
+                let sink = self.painter.output_sink();
+                // Like the other clipboards, copies fail silently
+                let _ = sink.write_all(&output).and_then(|()| sink.flush());
+            }
+        }
+    }
+
+    /// Keeps the history entry shown while traversing the history to edit it
+    fn leave_history_traversal(&mut self) {
         if self.input_mode == InputMode::HistoryTraversal {
             if matches!(
                 self.history_cursor.get_navigation(),
@@ -1475,11 +2604,60 @@ impl Reedline {
             }
             self.input_mode = InputMode::Regular;
         }
+    }
 
-        // Run the commands over the edit buffer
-        for command in commands {
-            self.editor.run_edit_command(command);
+    /// Tells the [`EditorHooks`] about changes of the buffer and the edit mode
+    fn report_changes(&mut self) {
+        if self.reading_secret {
+            return;
         }
+        if let Some(hooks) = self.editor_hooks.as_mut() {
+            hooks.report_changes(self.editor.line_buffer(), self.edit_mode.edit_mode());
+        }
+    }
+
+    /// The [`EditorHooks`] unless a secret is read
+    fn active_hooks(&mut self) -> Option<&mut HookRunner> {
+        if self.reading_secret {
+            None
+        } else {
+            self.editor_hooks.as_mut()
+        }
+    }
+
+    /// Asks the [`Validator`] if the buffer is complete, secrets are never validated
+    fn validate(&mut self, buffer: &str) -> Option<ValidationResult> {
+        if self.reading_secret {
+            None
+        } else {
+            self.validator.as_mut().map(|v| v.validate(buffer))
+        }
+    }
+
+    /// Checks if `event` would hand the secret being read to the history, the completer, the
+    /// hinter or the external editor
+    fn exposes_secret(event: &ReedlineEvent) -> bool {
+        matches!(
+            event,
+            ReedlineEvent::Menu(_)
+                | ReedlineEvent::MenuNext
+                | ReedlineEvent::MenuPrevious
+                | ReedlineEvent::MenuUp
+                | ReedlineEvent::MenuDown
+                | ReedlineEvent::MenuLeft
+                | ReedlineEvent::MenuRight
+                | ReedlineEvent::MenuPageNext
+                | ReedlineEvent::MenuPagePrevious
+                | ReedlineEvent::HistoryHintComplete
+                | ReedlineEvent::HistoryHintWordComplete
+                | ReedlineEvent::PreviousHistory
+                | ReedlineEvent::NextHistory
+                | ReedlineEvent::Up
+                | ReedlineEvent::Down
+                | ReedlineEvent::SearchHistory
+                | ReedlineEvent::OpenEditor
+                | ReedlineEvent::CustomCommand(_)
+        )
     }
 
     fn up_command(&mut self) {
@@ -1504,7 +2682,7 @@ impl Reedline {
 
     /// Checks if hints should be displayed and are able to be completed
     fn hints_active(&self) -> bool {
-        !self.hide_hints && matches!(self.input_mode, InputMode::Regular)
+        !self.hide_hints && !self.reading_secret && matches!(self.input_mode, InputMode::Regular)
     }
 
     /// Repaint of either the buffer or the parts for reverse history search
@@ -1520,6 +2698,9 @@ impl Reedline {
     #[cfg(feature = "bashisms")]
     /// Parses the ! command to replace entries from the history
     fn parse_bang_command(&mut self) -> Option<ReedlineEvent> {
+        if self.reading_secret {
+            return None;
+        }
         let buffer = self.editor.get_buffer();
         let parsed = parse_selection_char(buffer, '!');
         let parsed_prefix = parsed.prefix.unwrap_or_default().to_string();
@@ -1745,18 +2926,25 @@ impl Reedline {
     ///
     /// Includes the highlighting and hinting calls.
     fn buffer_paint(&mut self, prompt: &dyn Prompt) -> Result<()> {
+        self.refresh_diagnostics();
         let cursor_position_in_buffer = self.editor.insertion_point();
         let buffer_to_paint = self.editor.get_buffer();
 
-        let mut styled_text = self
-            .highlighter
-            .highlight(buffer_to_paint, cursor_position_in_buffer);
-        if let Some((from, to)) = self.editor.get_selection() {
-            styled_text.style_range(from, to, self.visual_selection_style);
-        }
+        let (styled_text, painted_cursor_position) = if self.reading_secret {
+            self.masked_buffer()
+        } else {
+            let mut styled_text = self
+                .highlighter
+                .highlight(buffer_to_paint, cursor_position_in_buffer);
+            underline_diagnostics(&mut styled_text, buffer_to_paint, &self.diagnostics);
+            if let Some((from, to)) = self.editor.get_selection() {
+                styled_text.style_range(from, to, self.visual_selection_style);
+            }
+            (styled_text, cursor_position_in_buffer)
+        };
 
         let (before_cursor, after_cursor) = styled_text.render_around_insertion_point(
-            cursor_position_in_buffer,
+            painted_cursor_position,
             prompt,
             self.use_ansi_coloring,
         );
@@ -1791,6 +2979,44 @@ impl Reedline {
             &after_cursor,
             &hint,
         );
+        let diagnostics = render_diagnostics(&self.diagnostics, self.use_ansi_coloring);
+        lines.diagnostics = diagnostics.into();
+
+        let indicator = self
+            .menus
+            .iter()
+            .find(|menu| menu.is_active())
+            .map_or(lines.prompt_indicator.as_ref(), |menu| menu.indicator());
+        let screen_width = self.painter.screen_width();
+        let columns = columns_after_prompt(
+            &format!("{}{}", lines.prompt_str_left, indicator),
+            screen_width,
+        );
+        let multiline_style = Style::new().fg(prompt.get_prompt_multiline_color());
+        if let Some(scroll) = self.horizontal_scroll.as_mut() {
+            let (before_cursor, after_cursor, hint) = scroll.render(
+                &styled_text,
+                painted_cursor_position,
+                &hint,
+                columns,
+                multiline_style,
+                self.use_ansi_coloring,
+            );
+            lines.before_cursor = before_cursor.into();
+            lines.after_cursor = after_cursor.into();
+            lines.hint = hint.into();
+        } else if let Some(gutter) = self.line_numbers.as_mut() {
+            let (before_cursor, after_cursor) = gutter.render(
+                &styled_text,
+                painted_cursor_position,
+                screen_width as usize - columns,
+                screen_width as usize,
+                (multiline_style.dimmed(), multiline_style.bold()),
+                self.use_ansi_coloring,
+            );
+            lines.before_cursor = before_cursor.into();
+            lines.after_cursor = after_cursor.into();
+        }
 
         // Updating the working details of the active menu
         for menu in self.menus.iter_mut() {
@@ -1821,25 +3047,195 @@ impl Reedline {
         )
     }
 
-    /// Adds an external printer
-    ///
-    /// ## Required feature:
-    /// `external_printer`
-    #[cfg(feature = "external_printer")]
-    pub fn with_external_printer(mut self, printer: ExternalPrinter<String>) -> Self {
-        self.external_printer = Some(printer);
-        self
-    }
+    /// Helper implementing the logic for [`Reedline::confirm()`] to be wrapped in a `raw_mode`
+    /// context.
+    fn confirm_helper(
+        &mut self,
+        prompt: &dyn Prompt,
+        default: Option<bool>,
+    ) -> Result<Option<bool>> {
+        self.painter.initialize_prompt_position(None)?;
+        let choices = if self.use_ansi_coloring {
+            Color::DarkGray.paint(confirm_choices(default)).to_string()
+        } else {
+            confirm_choices(default).to_string()
+        };
+        self.paint_widget(prompt, "", &choices, None)?;
 
-    #[cfg(feature = "external_printer")]
-    fn external_messages(external_printer: &ExternalPrinter<String>) -> Result<Vec<String>> {
-        let mut messages = Vec::new();
         loop {
-            let result = external_printer.receiver().try_recv();
-            match result {
-                Ok(line) => {
-                    let lines = line.lines().map(String::from).collect::<Vec<_>>();
-                    messages.extend(lines);
+            let event = self.input_source.read()?;
+            if let Event::Resize(width, height) = event {
+                self.painter.handle_resize(width, height);
+                self.paint_widget(prompt, "", &choices, None)?;
+                continue;
+            }
+            match confirm_key(&event, default) {
+                ConfirmKey::Answer(answer) => {
+                    self.paint_widget(prompt, if answer { "yes" } else { "no" }, "", None)?;
+                    self.painter.move_cursor_to_end()?;
+                    return Ok(Some(answer));
+                }
+                ConfirmKey::Cancel => {
+                    self.painter.move_cursor_to_end()?;
+                    return Ok(None);
+                }
+                ConfirmKey::Ignore => {}
+            }
+        }
+    }
+
+    /// Helper implementing the logic for [`Reedline::select()`] and
+    /// [`Reedline::multi_select()`] to be wrapped in a `raw_mode` context.
+    ///
+    /// The typed filter is edited in an editor of its own, the buffer stays as is.
+    fn select_helper(
+        &mut self,
+        prompt: &dyn Prompt,
+        items: &mut SelectItems,
+        menu: &mut ReedlineMenu,
+    ) -> Result<Option<Option<usize>>> {
+        let mut filter = Editor::with_local_clipboards();
+        self.painter.initialize_prompt_position(None)?;
+        menu.menu_event(MenuEvent::Activate(false));
+
+        loop {
+            menu.update_working_details(&mut filter, items, self.history.as_ref(), &self.painter);
+            let (before_cursor, after_cursor) =
+                filter.get_buffer().split_at(filter.insertion_point());
+            self.paint_widget(prompt, before_cursor, after_cursor, Some(menu))?;
+
+            let event = match ReedlineRawEvent::try_from(self.input_source.read()?) {
+                Ok(event) => event,
+                Err(()) => continue,
+            };
+            let mode = self.edit_mode.edit_mode();
+            let event = self.edit_mode.parse_event(event);
+            let mode_changed = self.edit_mode.edit_mode() != mode;
+            match select_action(event, items.is_multiple(), mode_changed) {
+                SelectAction::Accept => {
+                    let selected = items.selected(menu);
+                    if selected.is_some() || items.is_multiple() {
+                        self.painter.move_cursor_to_end()?;
+                        return Ok(Some(selected));
+                    }
+                }
+                SelectAction::Cancel => {
+                    self.painter.move_cursor_to_end()?;
+                    return Ok(None);
+                }
+                SelectAction::Menu(event) => menu.menu_event(event),
+                SelectAction::Edit(commands) => {
+                    for command in &commands {
+                        filter.run_edit_command(command);
+                    }
+                    menu.menu_event(MenuEvent::Edit(false));
+                }
+                SelectAction::Toggle => {
+                    if let (Some(index), Some(position)) =
+                        (items.selected(menu), menu.selected_index())
+                    {
+                        items.toggle(index);
+                        // Query the checkbox again, the toggled item stays selected
+                        menu.update_values(&mut filter, items, self.history.as_ref());
+                        menu.select_index(position);
+                    }
+                }
+                SelectAction::Resize(width, height) => self.painter.handle_resize(width, height),
+                SelectAction::Ignore => {}
+            }
+        }
+    }
+
+    /// Paints the `prompt` of a widget followed by the given text and `menu`
+    fn paint_widget(
+        &mut self,
+        prompt: &dyn Prompt,
+        before_cursor: &str,
+        after_cursor: &str,
+        mut menu: Option<&mut ReedlineMenu>,
+    ) -> Result<()> {
+        let lines = PromptLines::new(
+            prompt,
+            self.prompt_edit_mode(),
+            None,
+            before_cursor,
+            after_cursor,
+            "",
+        );
+        if let Some(menu) = menu.as_deref_mut() {
+            menu.set_cursor_pos(lines.cursor_pos(self.painter.screen_width()));
+        }
+
+        self.painter.repaint_buffer(
+            prompt,
+            &lines,
+            self.prompt_edit_mode(),
+            menu.as_deref(),
+            self.use_ansi_coloring,
+            &self.cursor_shapes,
+        )
+    }
+
+    /// Validates the buffer again while diagnostics are shown, dropping the ones that were fixed
+    fn refresh_diagnostics(&mut self) {
+        if self.diagnostics.is_empty() {
+            return;
+        }
+        let buffer = self.editor.get_buffer().to_string();
+        self.diagnostics = match self.validate(&buffer) {
+            Some(ValidationResult::Invalid(diagnostics)) => diagnostics,
+            _ => Vec::new(),
+        };
+    }
+
+    /// The secret being read as painted, a mask for every grapheme, with the cursor position
+    fn masked_buffer(&self) -> (StyledText, usize) {
+        let buffer = self.editor.get_buffer();
+        let mask = self.secret_mask.map(String::from).unwrap_or_default();
+        let graphemes = buffer.graphemes(true).count();
+        let graphemes_before_cursor = buffer[..self.editor.insertion_point()]
+            .graphemes(true)
+            .count();
+
+        let mut styled_text = StyledText::new();
+        styled_text.push((Style::new(), mask.repeat(graphemes)));
+        (styled_text, mask.len() * graphemes_before_cursor)
+    }
+
+    /// Adds an external printer
+    ///
+    /// ## Required feature:
+    /// `external_printer`
+    #[cfg(feature = "external_printer")]
+    pub fn with_external_printer(mut self, printer: ExternalPrinter<String>) -> Self {
+        self.external_printer = Some(printer);
+        self
+    }
+
+    /// Adds an event injector to send [`ReedlineEvent`]s to the running
+    /// [`Reedline::read_line()`] from other threads
+    ///
+    /// The events are received when the line editor polls for
+    /// `external_printer` messages.
+    ///
+    /// ## Required feature:
+    /// `external_printer`
+    #[cfg(feature = "external_printer")]
+    #[must_use]
+    pub fn with_event_injector(mut self, event_injector: EventInjector) -> Self {
+        self.event_injector = Some(event_injector);
+        self
+    }
+
+    #[cfg(feature = "external_printer")]
+    fn external_messages(external_printer: &ExternalPrinter<String>) -> Result<Vec<String>> {
+        let mut messages = Vec::new();
+        loop {
+            let result = external_printer.receiver().try_recv();
+            match result {
+                Ok(line) => {
+                    let lines = line.lines().map(String::from).collect::<Vec<_>>();
+                    messages.extend(lines);
                 }
                 Err(TryRecvError::Empty) => {
                     break;
@@ -1856,8 +3252,20 @@ impl Reedline {
     }
 
     fn submit_buffer(&mut self, prompt: &dyn Prompt) -> io::Result<EventStatus> {
-        let buffer = self.editor.get_buffer().to_string();
+        let mut buffer = self.editor.get_buffer().to_string();
+        if let Some(hooks) = self.active_hooks() {
+            match hooks.pre_submit(&buffer) {
+                PreSubmit::Accept => {}
+                PreSubmit::Rewrite(rewritten) => {
+                    buffer = rewritten;
+                    self.editor
+                        .set_buffer(buffer.clone(), UndoBehavior::CreateUndoPoint);
+                }
+                PreSubmit::Veto => return Ok(EventStatus::Handled),
+            }
+        }
         self.hide_hints = true;
+        self.diagnostics.clear();
         // Additional repaint to show the content without hints etc.
         if let Some(transient_prompt) = self.transient_prompt.take() {
             self.repaint(transient_prompt.as_ref())?;
@@ -1865,30 +3273,39 @@ impl Reedline {
         } else {
             self.repaint(prompt)?;
         }
-        if !buffer.is_empty() {
-            let mut entry = HistoryItem::from_command_line(&buffer);
-            entry.session_id = self.get_history_session_id();
-
-            if self
-                .history_exclusion_prefix
-                .as_ref()
-                .map(|prefix| buffer.starts_with(prefix))
-                .unwrap_or(false)
-            {
-                entry.id = Some(Self::FILTERED_ITEM_ID);
-                self.history_last_run_id = entry.id;
-                self.history_excluded_item = Some(entry);
-            } else {
-                entry = self.history.save(entry).expect("todo: error handling");
-                self.history_last_run_id = entry.id;
-                self.history_excluded_item = None;
-            }
+        self.save_submitted_line(&buffer);
+        if let Some(hooks) = self.active_hooks() {
+            hooks.post_submit(&buffer);
         }
         self.run_edit_commands(&[EditCommand::Clear]);
         self.editor.reset_undo_stack();
 
         Ok(EventStatus::Exits(Signal::Success(buffer)))
     }
+
+    /// Adds a submitted line to the history unless it starts with the exclusion prefix
+    fn save_submitted_line(&mut self, buffer: &str) {
+        if buffer.is_empty() || self.reading_secret {
+            return;
+        }
+        let mut entry = HistoryItem::from_command_line(buffer);
+        entry.session_id = self.get_history_session_id();
+
+        if self
+            .history_exclusion_prefix
+            .as_ref()
+            .map(|prefix| buffer.starts_with(prefix))
+            .unwrap_or(false)
+        {
+            entry.id = Some(Self::FILTERED_ITEM_ID);
+            self.history_last_run_id = entry.id;
+            self.history_excluded_item = Some(entry);
+        } else {
+            entry = self.history.save(entry).expect("todo: error handling");
+            self.history_last_run_id = entry.id;
+            self.history_excluded_item = None;
+        }
+    }
 }
 
 #[test]
@@ -1896,3 +3313,961 @@ fn thread_safe() {
     fn f<S: Send>(_: S) {}
     f(Reedline::create());
 }
+
+#[cfg(feature = "async")]
+#[test]
+fn read_line_async_is_send() {
+    fn f<S: Send>(_: S) {}
+    let mut line_editor = Reedline::create();
+    f(line_editor.read_line_async(&crate::DefaultPrompt::default()));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::test_fixtures::{key, line_editor, prompt};
+    use crate::{
+        default_emacs_keybindings, ColumnarMenu, IdeMenu, KeyCombination, MenuBuilder,
+        SearchFilter, Severity, Span, Vi, VirtualTerminal,
+    };
+    use crossterm::event::{
+        KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind,
+    };
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+    use std::{
+        borrow::Cow,
+        sync::atomic::{AtomicUsize, Ordering},
+    };
+
+    // Prompt showing how often it was rendered
+    #[derive(Default)]
+    struct CountingPrompt {
+        renders: AtomicUsize,
+    }
+
+    impl Prompt for CountingPrompt {
+        fn render_prompt_left(&self) -> Cow<str> {
+            let renders = self.renders.fetch_add(1, Ordering::SeqCst) + 1;
+            Cow::Owned(format!("{renders}"))
+        }
+
+        fn render_prompt_right(&self) -> Cow<str> {
+            Cow::Borrowed("")
+        }
+
+        fn render_prompt_indicator(&self, _prompt_mode: PromptEditMode) -> Cow<str> {
+            Cow::Borrowed("> ")
+        }
+
+        fn render_prompt_multiline_indicator(&self) -> Cow<str> {
+            Cow::Borrowed("::: ")
+        }
+
+        fn render_prompt_history_search_indicator(
+            &self,
+            _history_search: PromptHistorySearch,
+        ) -> Cow<str> {
+            Cow::Borrowed("? ")
+        }
+    }
+
+    // Input piped into the program instead of a terminal
+    struct PipedInput {
+        lines: std::collections::VecDeque<String>,
+    }
+
+    impl PipedInput {
+        fn new(lines: &[&str]) -> Self {
+            PipedInput {
+                lines: lines.iter().map(|line| line.to_string()).collect(),
+            }
+        }
+    }
+
+    impl InputSource for PipedInput {
+        fn poll(&mut self, _timeout: Duration) -> Result<bool> {
+            unreachable!("piped input is read by lines")
+        }
+
+        fn read(&mut self) -> Result<Event> {
+            unreachable!("piped input is read by lines")
+        }
+
+        fn enable_raw_mode(&mut self) -> Result<()> {
+            unreachable!("piped input has no raw mode")
+        }
+
+        fn disable_raw_mode(&mut self) -> Result<()> {
+            // Also called when the line editor is dropped
+            Ok(())
+        }
+
+        fn is_terminal(&self) -> bool {
+            false
+        }
+
+        fn read_plain_line(&mut self) -> Result<Option<String>> {
+            Ok(self.lines.pop_front())
+        }
+    }
+
+    fn piped_line_editor(terminal: &VirtualTerminal, lines: &[&str]) -> Reedline {
+        Reedline::create()
+            .with_input_source(Box::new(PipedInput::new(lines)))
+            .with_output_sink(Box::new(terminal.clone()))
+    }
+
+    #[test]
+    fn piped_input_is_read_by_lines() {
+        let terminal = VirtualTerminal::new(20, 5);
+        let prompt = CountingPrompt::default();
+
+        let mut line_editor = piped_line_editor(&terminal, &["ls -l", ""]);
+
+        let signals = [
+            line_editor.read_line(&prompt).unwrap(),
+            line_editor.read_line(&prompt).unwrap(),
+            line_editor.read_line(&prompt).unwrap(),
+        ];
+
+        assert!(matches!(&signals[0], Signal::Success(line) if line == "ls -l"));
+        assert!(matches!(&signals[1], Signal::Success(line) if line.is_empty()));
+        assert!(matches!(signals[2], Signal::CtrlD));
+        assert_eq!(terminal.output(), "1> 2> 3> ");
+    }
+
+    #[test]
+    fn piped_input_continues_incomplete_lines() {
+        let terminal = VirtualTerminal::new(20, 5);
+        let prompt = CountingPrompt::default();
+
+        let mut line_editor = piped_line_editor(&terminal, &["echo (", "a", ")", "(b"])
+            .with_validator(Box::new(crate::DefaultValidator));
+
+        let first = line_editor.read_line(&prompt).unwrap();
+        let second = line_editor.read_line(&prompt).unwrap();
+
+        assert!(matches!(first, Signal::Success(line) if line == "echo (\na\n)"));
+        assert!(matches!(second, Signal::Success(line) if line == "(b"));
+        assert_eq!(terminal.output(), "1> ::: ::: 2> ::: ");
+    }
+
+    #[test]
+    fn piped_input_with_diagnostics_is_dropped() {
+        let terminal = VirtualTerminal::new(40, 5);
+        let prompt = CountingPrompt::default();
+
+        let mut line_editor = piped_line_editor(&terminal, &["select usrs", "select users"])
+            .with_validator(Box::new(TableValidator));
+        let signal = line_editor.read_line(&prompt).unwrap();
+
+        assert!(matches!(signal, Signal::Success(line) if line == "select users"));
+        assert_eq!(
+            terminal.output(),
+            "1> error: unknown table at column 8\n2> "
+        );
+    }
+
+    #[test]
+    fn piped_input_is_saved_to_the_history() {
+        let terminal = VirtualTerminal::new(20, 5);
+        let prompt = CountingPrompt::default();
+
+        let mut line_editor = piped_line_editor(&terminal, &["first", " secret", "second"])
+            .with_history_exclusion_prefix(Some(" ".into()));
+        while let Signal::Success(_) = line_editor.read_line(&prompt).unwrap() {}
+
+        let history: Vec<_> = line_editor
+            .history()
+            .search(SearchQuery::everything(SearchDirection::Forward, None))
+            .unwrap()
+            .into_iter()
+            .map(|item| item.command_line)
+            .collect();
+        assert_eq!(history, vec!["first", "second"]);
+    }
+
+    // Vetoes every line but `submit`
+    struct VetoHooks;
+
+    impl EditorHooks for VetoHooks {
+        fn pre_submit(&mut self, buffer: &str) -> PreSubmit {
+            if buffer == "submit" {
+                PreSubmit::Accept
+            } else {
+                PreSubmit::Veto
+            }
+        }
+    }
+
+    #[test]
+    fn piped_input_skips_many_vetoed_lines() {
+        let terminal = VirtualTerminal::new(20, 5);
+        let mut lines = vec!["vetoed"; 100_000];
+        lines.push("submit");
+
+        let mut line_editor =
+            piped_line_editor(&terminal, &lines).with_editor_hooks(Box::new(VetoHooks));
+        let signal = line_editor.read_line(&prompt()).unwrap();
+
+        assert!(matches!(signal, Signal::Success(line) if line == "submit"));
+    }
+
+    #[test]
+    fn piped_input_skips_many_invalid_lines() {
+        let terminal = VirtualTerminal::new(20, 5);
+        let mut lines = vec!["select usrs"; 20_000];
+        lines.push("select users");
+
+        let mut line_editor =
+            piped_line_editor(&terminal, &lines).with_validator(Box::new(TableValidator));
+        let signal = line_editor.read_line(&prompt()).unwrap();
+
+        assert!(matches!(signal, Signal::Success(line) if line == "select users"));
+    }
+
+    #[test]
+    fn secrets_are_not_read_from_piped_input() {
+        let terminal = VirtualTerminal::new(20, 5);
+        let prompt = CountingPrompt::default();
+
+        let mut line_editor = piped_line_editor(&terminal, &["hunter2"]);
+        let error = line_editor.read_secret(&prompt, Some('*')).unwrap_err();
+
+        assert_eq!(error.kind(), io::ErrorKind::Unsupported);
+        assert_eq!(terminal.output(), "");
+    }
+
+    #[test]
+    fn idle_timeout_keeps_the_buffer() {
+        let terminal = VirtualTerminal::new(20, 5);
+        terminal.push_str("abc");
+        let prompt = prompt();
+
+        let mut line_editor = line_editor(&terminal);
+        let signal = line_editor
+            .read_line_with_idle_timeout(&prompt, Duration::from_millis(10))
+            .unwrap();
+
+        assert!(matches!(signal, Signal::IdleTimeout));
+        assert_eq!(line_editor.current_buffer_contents(), "abc");
+        assert_eq!(terminal.cursor(), (0, 1));
+    }
+
+    #[test]
+    fn prompt_refreshes_while_idle() {
+        let terminal = VirtualTerminal::new(20, 5);
+        let prompt = CountingPrompt::default();
+
+        let mut line_editor = line_editor(&terminal).with_prompt_refresh(Duration::from_millis(5));
+        assert!(line_editor.read_line(&prompt).is_err());
+
+        assert_eq!(terminal.screen()[0], "2>");
+    }
+
+    #[test]
+    fn invalidated_prompt_is_repainted() {
+        let terminal = VirtualTerminal::new(20, 5);
+        let prompt = CountingPrompt::default();
+
+        let mut line_editor = line_editor(&terminal);
+        let invalidator = line_editor.prompt_invalidator();
+        invalidator.invalidate();
+        assert!(line_editor.read_line(&prompt).is_err());
+
+        assert_eq!(terminal.screen()[0], "2>");
+    }
+
+    #[test]
+    fn prompt_without_refresh_is_painted_once() {
+        let terminal = VirtualTerminal::new(20, 5);
+        let prompt = CountingPrompt::default();
+
+        let mut line_editor = line_editor(&terminal);
+        assert!(line_editor.read_line(&prompt).is_err());
+
+        assert_eq!(terminal.screen()[0], "1>");
+    }
+
+    fn click(column: u16, row: u16) -> Event {
+        Event::Mouse(MouseEvent {
+            kind: MouseEventKind::Down(MouseButton::Left),
+            column,
+            row,
+            modifiers: KeyModifiers::NONE,
+        })
+    }
+
+    fn menu_line_editor(terminal: &VirtualTerminal, menu: Box<dyn Menu>) -> Reedline {
+        let completer = DefaultCompleter::new_with_wordlen(
+            ["apple", "apricot", "april", "apron", "apse", "apt"]
+                .iter()
+                .map(|word| word.to_string())
+                .collect(),
+            2,
+        );
+        let mut keybindings = default_emacs_keybindings();
+        keybindings.add_binding(
+            KeyModifiers::NONE,
+            KeyCode::Tab,
+            ReedlineEvent::Menu("completion_menu".to_string()),
+        );
+        line_editor(terminal)
+            .with_edit_mode(Box::new(Emacs::new(keybindings)))
+            .with_completer(Box::new(completer))
+            .with_menu(ReedlineMenu::EngineCompleter(menu))
+    }
+
+    #[test]
+    fn custom_commands_edit_in_place() {
+        let terminal = VirtualTerminal::new(40, 5);
+        terminal.push_str("ls");
+        terminal.push_event(key(KeyCode::Enter));
+        terminal.push_str("make");
+        terminal.push_event(key(KeyCode::F(5)));
+        terminal.push_event(key(KeyCode::F(6)));
+        terminal.push_event(key(KeyCode::F(7)));
+        terminal.push_event(key(KeyCode::Enter));
+        let prompt = prompt();
+        let mut keybindings = default_emacs_keybindings();
+        for (key, name) in [(5, "sudo"), (6, "last"), (7, "unknown")] {
+            keybindings.add_binding(
+                KeyModifiers::NONE,
+                KeyCode::F(key),
+                ReedlineEvent::CustomCommand(name.to_string()),
+            );
+        }
+
+        let mut line_editor = line_editor(&terminal)
+            .with_edit_mode(Box::new(Emacs::new(keybindings)))
+            .with_custom_command("sudo", |editor, _history| {
+                editor.edit_buffer(
+                    |line_buffer| {
+                        line_buffer.move_to_start();
+                        line_buffer.insert_str("sudo ");
+                        line_buffer.move_to_end();
+                    },
+                    UndoBehavior::CreateUndoPoint,
+                )
+            })
+            .with_custom_command("last", |editor, history| {
+                let last = history
+                    .search(SearchQuery::last_with_search(SearchFilter::anything(None)))
+                    .unwrap()
+                    .remove(0)
+                    .command_line;
+                editor.edit_buffer(
+                    |line_buffer| line_buffer.insert_str(&format!(" && {last}")),
+                    UndoBehavior::CreateUndoPoint,
+                )
+            });
+        line_editor.read_line(&prompt).unwrap();
+        let signal = line_editor.read_line(&prompt).unwrap();
+
+        assert!(matches!(signal, Signal::Success(buffer) if buffer == "sudo make && ls"));
+    }
+
+    #[rstest]
+    #[case(Some('*'), "> 〉*******")]
+    #[case(None, "> 〉")]
+    fn secrets_are_painted_as_their_mask(#[case] mask: Option<char>, #[case] expected: &str) {
+        let terminal = VirtualTerminal::new(20, 5);
+        terminal.push_str("hunter");
+        terminal.push_event(key(KeyCode::Left));
+        terminal.push_str("2");
+        terminal.push_event(key(KeyCode::Enter));
+        let prompt = prompt();
+
+        let mut line_editor = line_editor(&terminal);
+        let signal = line_editor.read_secret(&prompt, mask).unwrap();
+
+        assert!(matches!(signal, Signal::Success(buffer) if buffer == "hunte2r"));
+        assert_eq!(terminal.screen()[0].trim_end(), expected);
+        assert!(!terminal.output().contains("hunte"));
+        assert!(line_editor
+            .history()
+            .search(SearchQuery::everything(SearchDirection::Forward, None))
+            .unwrap()
+            .is_empty());
+    }
+
+    #[rstest]
+    #[case(Box::new(Emacs::default()))]
+    #[case(Box::new(Vi::default()))]
+    fn auto_pairs_close_while_typing(#[case] edit_mode: Box<dyn EditMode>) {
+        let terminal = VirtualTerminal::new(40, 5);
+        terminal.push_str("say(\"hi");
+        terminal.push_event(key(KeyCode::Backspace));
+        terminal.push_event(key(KeyCode::Backspace));
+        terminal.push_event(key(KeyCode::Backspace));
+        terminal.push_str("[x]");
+        terminal.push_event(key(KeyCode::Enter));
+
+        let mut line_editor = line_editor(&terminal)
+            .with_edit_mode(edit_mode)
+            .with_auto_pairs(AutoPairs::default());
+        let signal = line_editor.read_line(&CountingPrompt::default()).unwrap();
+
+        assert!(matches!(signal, Signal::Success(buffer) if buffer == "say([x])"));
+    }
+
+    #[test]
+    fn vi_text_objects_are_changed_and_deleted() {
+        let terminal = VirtualTerminal::new(40, 5);
+        terminal.push_str("echo \"hi there\" f(a, b)");
+        terminal.push_event(key(KeyCode::Esc));
+        terminal.push_str("dia0ci\"x");
+        terminal.push_event(key(KeyCode::Enter));
+
+        let mut line_editor = line_editor(&terminal).with_edit_mode(Box::new(Vi::default()));
+        let signal = line_editor.read_line(&CountingPrompt::default()).unwrap();
+
+        assert!(matches!(signal, Signal::Success(buffer) if buffer == "echo \"x\" f(a, )"));
+    }
+
+    #[rstest]
+    #[case("0w\"ayww\"Ayw0\"aP", "one twoecho one two")]
+    #[case("0wywdw$p", "echo twoone ")]
+    #[case("0wywdw$\"0p\"1p", "echo twoone one ")]
+    #[case("yyp", "echo one two\necho one two")]
+    #[case("0\"a2dw$\"ap", "twoecho one ")]
+    #[case("0\"aywwyw$2\"ap", "echo one twoecho echo ")]
+    fn vi_yanks_fill_the_registers(#[case] keys: &str, #[case] expected: &str) {
+        let terminal = VirtualTerminal::new(40, 5);
+        terminal.push_str("echo one two");
+        terminal.push_event(key(KeyCode::Esc));
+        terminal.push_str(keys);
+        terminal.push_event(key(KeyCode::Enter));
+
+        let mut line_editor = line_editor(&terminal).with_edit_mode(Box::new(Vi::default()));
+        let signal = line_editor.read_line(&CountingPrompt::default()).unwrap();
+
+        assert!(matches!(signal, Signal::Success(buffer) if buffer == expected));
+    }
+
+    #[rstest]
+    #[case("ggjVjd", "one")]
+    #[case("GVkcx", "one\nx")]
+    #[case("ggVjJ", "one two\nthree")]
+    #[case("ggVj>", "    one\n    two\nthree")]
+    #[case("ggjVyggP", "two\none\ntwo\nthree")]
+    #[case("ggOzero", "zero\none\ntwo\nthree")]
+    fn vi_visual_line_mode_works_on_whole_lines(#[case] keys: &str, #[case] expected: &str) {
+        let terminal = VirtualTerminal::new(40, 5);
+        terminal.push_str("one");
+        terminal.push_event(key(KeyCode::Esc));
+        terminal.push_str("otwo");
+        terminal.push_event(key(KeyCode::Esc));
+        terminal.push_str("othree");
+        terminal.push_event(key(KeyCode::Esc));
+        terminal.push_str(keys);
+        terminal.push_event(key(KeyCode::Enter));
+
+        let mut line_editor = line_editor(&terminal).with_edit_mode(Box::new(Vi::default()));
+        let signal = line_editor.read_line(&CountingPrompt::default()).unwrap();
+
+        assert!(matches!(signal, Signal::Success(buffer) if buffer == expected));
+    }
+
+    #[test]
+    fn incomplete_lines_are_indented() {
+        let terminal = VirtualTerminal::new(40, 5);
+        terminal.push_str("if $x {");
+        terminal.push_event(key(KeyCode::Enter));
+        terminal.push_str("ls");
+        terminal.push_event(key(KeyCode::Enter));
+        terminal.push_str("}");
+        terminal.push_event(key(KeyCode::Enter));
+
+        let mut line_editor = line_editor(&terminal)
+            .with_validator(Box::new(crate::DefaultValidator))
+            .with_indenter(Box::new(crate::DefaultIndenter::default()));
+        let signal = line_editor.read_line(&CountingPrompt::default()).unwrap();
+
+        assert!(matches!(signal, Signal::Success(buffer) if buffer == "if $x {\n    ls\n}"));
+    }
+
+    #[test]
+    fn secrets_leave_the_buffer_and_the_clipboard_alone() {
+        let terminal = VirtualTerminal::new(40, 5);
+        terminal.push_str("ls");
+        terminal.push_event(key(KeyCode::Enter));
+        terminal.push_str("secret");
+        terminal.push_event(key(KeyCode::Up));
+        terminal.push_event(Event::Key(KeyEvent::new(
+            KeyCode::Char('u'),
+            KeyModifiers::CONTROL,
+        )));
+        terminal.push_str("pw");
+        terminal.push_event(key(KeyCode::Enter));
+        terminal.push_event(Event::Key(KeyEvent::new(
+            KeyCode::Char('y'),
+            KeyModifiers::CONTROL,
+        )));
+        terminal.push_event(key(KeyCode::Enter));
+        let prompt = CountingPrompt::default();
+
+        let mut line_editor = line_editor(&terminal);
+        line_editor.read_line(&prompt).unwrap();
+        line_editor.run_edit_commands(&[EditCommand::InsertString("draft".to_string())]);
+        let secret = line_editor.read_secret(&prompt, Some('*')).unwrap();
+        let signal = line_editor.read_line(&prompt).unwrap();
+
+        assert!(matches!(secret, Signal::Success(buffer) if buffer == "pw"));
+        assert!(matches!(signal, Signal::Success(buffer) if buffer == "draft"));
+        let history: Vec<_> = line_editor
+            .history()
+            .search(SearchQuery::everything(SearchDirection::Forward, None))
+            .unwrap()
+            .into_iter()
+            .map(|item| item.command_line)
+            .collect();
+        assert_eq!(history, vec!["ls", "draft"]);
+    }
+
+    struct TableValidator;
+
+    impl Validator for TableValidator {
+        fn validate(&self, line: &str) -> ValidationResult {
+            match line.find("usrs") {
+                Some(start) => ValidationResult::Invalid(vec![Diagnostic::new(
+                    Severity::Error,
+                    format!("unknown table at column {}", start + 1),
+                    Span::new(start, start + 4),
+                )]),
+                None => ValidationResult::Complete,
+            }
+        }
+    }
+
+    #[test]
+    fn diagnostics_block_the_submit_until_fixed() {
+        let terminal = VirtualTerminal::new(40, 5);
+        terminal.push_str("select * from usrs");
+        terminal.push_event(key(KeyCode::Enter));
+        let prompt = prompt();
+
+        let mut line_editor = line_editor(&terminal)
+            .with_validator(Box::new(TableValidator))
+            .with_ansi_colors(false);
+        assert!(line_editor.read_line(&prompt).is_err());
+
+        assert_eq!(terminal.screen()[0].trim_end(), "> 〉select * from usrs");
+        assert_eq!(
+            terminal.screen()[1].trim_end(),
+            "error: unknown table at column 15"
+        );
+
+        terminal.push_event(key(KeyCode::Left));
+        terminal.push_event(key(KeyCode::Left));
+        terminal.push_str("e");
+        terminal.push_event(key(KeyCode::Enter));
+        let signal = line_editor.read_line(&prompt).unwrap();
+
+        assert!(matches!(signal, Signal::Success(buffer) if buffer == "select * from users"));
+        assert!(!terminal.screen().iter().any(|row| row.contains("error")));
+    }
+
+    #[test]
+    fn diagnostics_stay_above_an_open_menu() {
+        let terminal = VirtualTerminal::new(40, 5);
+        terminal.push_str("usrs ap");
+        terminal.push_event(key(KeyCode::Enter));
+        terminal.push_event(key(KeyCode::Tab));
+        // Clicks on the menu still pick the item below the pointer
+        terminal.push_event(click(12, 2));
+        let prompt = prompt();
+
+        let mut line_editor = menu_line_editor(
+            &terminal,
+            Box::new(ColumnarMenu::default().with_name("completion_menu")),
+        )
+        .with_validator(Box::new(TableValidator))
+        .with_ansi_colors(false);
+        assert!(line_editor.read_line(&prompt).is_err());
+
+        let screen = terminal.screen();
+        assert_eq!(screen[0].trim_end(), "> | usrs ap");
+        assert_eq!(screen[1].trim_end(), "error: unknown table at column 1");
+        assert!(screen[2].contains("APRICOT"));
+    }
+
+    #[test]
+    fn confirm_answers_on_a_single_key() {
+        let terminal = VirtualTerminal::new(20, 5);
+        terminal.push_str("xY");
+        terminal.push_event(key(KeyCode::Enter));
+        terminal.push_event(key(KeyCode::Enter));
+        terminal.push_event(Event::Key(KeyEvent::new(
+            KeyCode::Char('c'),
+            KeyModifiers::CONTROL,
+        )));
+        let prompt = prompt();
+
+        let mut line_editor = line_editor(&terminal);
+
+        assert_eq!(line_editor.confirm(&prompt, None).unwrap(), Some(true));
+        assert_eq!(terminal.screen()[0].trim_end(), "> 〉yes");
+        assert_eq!(
+            line_editor.confirm(&prompt, Some(false)).unwrap(),
+            Some(false)
+        );
+        assert_eq!(line_editor.confirm(&prompt, None).unwrap(), None);
+        assert_eq!(terminal.screen()[2].trim_end(), "> 〉[y/n]");
+    }
+
+    #[test]
+    fn select_filters_the_items() {
+        let terminal = VirtualTerminal::new(30, 8);
+        terminal.push_event(key(KeyCode::Down));
+        terminal.push_event(key(KeyCode::Down));
+        terminal.push_event(key(KeyCode::Enter));
+        terminal.push_str("AN");
+        terminal.push_event(key(KeyCode::Enter));
+        terminal.push_event(key(KeyCode::Esc));
+        let prompt = prompt();
+        let items = ["apple", "banana", "cherry"];
+
+        let mut line_editor = line_editor(&terminal);
+
+        assert_eq!(line_editor.select(&prompt, &items).unwrap(), Some(2));
+        assert_eq!(line_editor.select(&prompt, &items).unwrap(), Some(1));
+        assert_eq!(terminal.screen()[1].trim_end(), "> 〉AN");
+        assert_eq!(terminal.screen()[2].trim_end(), "banana");
+        assert_eq!(line_editor.select(&prompt, &items).unwrap(), None);
+        assert_eq!(line_editor.current_buffer_contents(), "");
+    }
+
+    #[test]
+    fn multi_select_checks_items_with_space() {
+        let terminal = VirtualTerminal::new(30, 8);
+        terminal.push_str(" ");
+        terminal.push_event(key(KeyCode::Down));
+        terminal.push_event(key(KeyCode::Down));
+        terminal.push_str(" ");
+        terminal.push_event(key(KeyCode::Up));
+        terminal.push_event(key(KeyCode::Up));
+        terminal.push_str(" ");
+        terminal.push_str(" ");
+        terminal.push_event(key(KeyCode::Enter));
+        let prompt = prompt();
+        let items = ["apple", "banana", "cherry"];
+
+        let mut line_editor = line_editor(&terminal);
+        let checked = line_editor.multi_select(&prompt, &items).unwrap();
+
+        assert_eq!(checked, Some(vec![0, 2]));
+        assert_eq!(
+            terminal.screen()[1..4]
+                .iter()
+                .map(|row| row.trim_end())
+                .collect::<Vec<_>>(),
+            vec!["[x] apple", "[ ] banana", "[x] cherry"]
+        );
+    }
+
+    #[test]
+    fn select_tells_duplicate_items_apart() {
+        let terminal = VirtualTerminal::new(30, 8);
+        terminal.push_event(key(KeyCode::Down));
+        terminal.push_event(key(KeyCode::Enter));
+        terminal.push_event(key(KeyCode::Down));
+        terminal.push_str(" ");
+        terminal.push_event(key(KeyCode::Enter));
+        let prompt = prompt();
+        let items = ["same", "same"];
+
+        let mut line_editor = line_editor(&terminal);
+
+        assert_eq!(line_editor.select(&prompt, &items).unwrap(), Some(1));
+        assert_eq!(
+            line_editor.multi_select(&prompt, &items).unwrap(),
+            Some(vec![1])
+        );
+    }
+
+    #[test]
+    fn click_moves_the_cursor_in_a_multiline_buffer() {
+        let terminal = VirtualTerminal::new(20, 5);
+        // "> 〉" takes the first 4 columns, "::: " prefixes the second line
+        terminal.push_event(click(6, 1));
+        terminal.push_str("X");
+        terminal.push_event(click(5, 0));
+        terminal.push_str("Y");
+        terminal.push_event(click(15, 0));
+        terminal.push_str("Z");
+        terminal.push_event(key(KeyCode::Enter));
+        let prompt = prompt();
+
+        let mut line_editor = line_editor(&terminal);
+        line_editor.run_edit_commands(&[EditCommand::InsertString("abc\ndef".to_string())]);
+        let signal = line_editor.read_line(&prompt).unwrap();
+
+        assert!(matches!(signal, Signal::Success(buffer) if buffer == "aYbcZ\ndeXf"));
+    }
+
+    #[test]
+    fn horizontal_scroll_keeps_the_buffer_on_one_row() {
+        let terminal = VirtualTerminal::new(20, 5);
+        terminal.push_str("abcdefghijklmnopqrstuvwxyz");
+        let prompt = prompt();
+
+        let mut line_editor = line_editor(&terminal).with_horizontal_scroll(true);
+        assert!(line_editor.read_line(&prompt).is_err());
+
+        assert_eq!(terminal.screen()[0], "> 〉<nopqrstuvwxyz");
+        assert_eq!(terminal.screen()[1], "");
+        assert_eq!(terminal.cursor(), (18, 0));
+
+        // The next prompt starts on the following row
+        terminal.push_event(key(KeyCode::Home));
+        assert!(line_editor.read_line(&prompt).is_err());
+
+        assert_eq!(terminal.screen()[1], "> 〉abcdefghijklmn>");
+        assert_eq!(terminal.cursor(), (4, 1));
+    }
+
+    #[test]
+    fn click_in_a_horizontally_scrolled_buffer() {
+        let terminal = VirtualTerminal::new(20, 5);
+        terminal.push_str("abcdefghijklmnopqrstuvwxyz");
+        // "<" is painted in column 4, "n" in column 5
+        terminal.push_event(click(6, 0));
+        terminal.push_str("X");
+        terminal.push_event(key(KeyCode::Enter));
+        let prompt = prompt();
+
+        let mut line_editor = line_editor(&terminal).with_horizontal_scroll(true);
+        let signal = line_editor.read_line(&prompt).unwrap();
+
+        assert!(
+            matches!(signal, Signal::Success(buffer) if buffer == "abcdefghijklmnXopqrstuvwxyz")
+        );
+    }
+
+    #[test]
+    fn line_numbers_wrap_long_lines_with_a_marker() {
+        let terminal = VirtualTerminal::new(16, 6);
+        terminal.push_str("ab");
+        terminal.push_event(Event::Key(KeyEvent::new(KeyCode::Enter, KeyModifiers::ALT)));
+        terminal.push_str("cdefghijklmnop");
+        // "d" is painted in column 7 of the row below the prompt
+        terminal.push_event(click(7, 1));
+        terminal.push_str("X");
+
+        let mut line_editor = line_editor(&terminal).with_line_numbers(true);
+        assert!(line_editor.read_line(&prompt()).is_err());
+
+        assert_eq!(terminal.screen()[0], "> 〉1 ab");
+        assert_eq!(terminal.screen()[1], "    2 cXdefghij");
+        assert_eq!(terminal.screen()[2], "    ↪ klmnop");
+        assert_eq!(terminal.cursor(), (8, 1));
+    }
+
+    #[test]
+    fn click_selects_a_menu_entry() {
+        let terminal = VirtualTerminal::new(40, 5);
+        terminal.push_str("ap");
+        terminal.push_event(key(KeyCode::Tab));
+        // Four columns of 10 cells, the menu starts below the prompt
+        terminal.push_event(click(12, 1));
+        terminal.push_event(key(KeyCode::Enter));
+        terminal.push_event(key(KeyCode::Enter));
+        let prompt = prompt();
+
+        let mut line_editor = menu_line_editor(
+            &terminal,
+            Box::new(ColumnarMenu::default().with_name("completion_menu")),
+        );
+        let signal = line_editor.read_line(&prompt).unwrap();
+
+        assert!(matches!(signal, Signal::Success(buffer) if buffer == "apricot"));
+    }
+
+    #[test]
+    fn wheel_scrolls_menu_pages() {
+        let terminal = VirtualTerminal::new(40, 5);
+        terminal.push_str("ap");
+        terminal.push_event(key(KeyCode::Tab));
+        terminal.push_event(Event::Mouse(MouseEvent {
+            kind: MouseEventKind::ScrollDown,
+            column: 0,
+            row: 0,
+            modifiers: KeyModifiers::NONE,
+        }));
+        terminal.push_event(key(KeyCode::Enter));
+        terminal.push_event(key(KeyCode::Enter));
+        let prompt = prompt();
+
+        // The menu gets the 4 lines below the prompt
+        let mut line_editor = menu_line_editor(
+            &terminal,
+            Box::new(IdeMenu::default().with_name("completion_menu")),
+        );
+        let signal = line_editor.read_line(&prompt).unwrap();
+
+        assert!(matches!(signal, Signal::Success(buffer) if buffer == "apse"));
+    }
+
+    #[test]
+    fn semantic_prompt_marks_surround_the_prompt_and_the_output() {
+        let terminal = VirtualTerminal::new(20, 5);
+        terminal.push_str("ls");
+        terminal.push_event(key(KeyCode::Enter));
+        let prompt = prompt();
+
+        let mut line_editor = line_editor(&terminal).use_semantic_prompt_marks(true);
+        line_editor.read_line(&prompt).unwrap();
+        line_editor.report_command_finished(Some(1)).unwrap();
+
+        // Every repaint marks the prompt again
+        let output = terminal.output();
+        let marks: Vec<&str> = output
+            .split("\x1b]133;")
+            .skip(1)
+            .filter_map(|mark| mark.split('\x1b').next())
+            .collect();
+        assert_eq!(marks, ["A", "B", "A", "B", "A", "B", "C", "D;1"]);
+        assert_eq!(terminal.screen()[0], "> 〉ls");
+    }
+
+    #[test]
+    fn semantic_prompt_marks_are_disabled_by_default() {
+        let terminal = VirtualTerminal::new(20, 5);
+        terminal.push_str("ls");
+        terminal.push_event(key(KeyCode::Enter));
+        let prompt = prompt();
+
+        let mut line_editor = line_editor(&terminal);
+        line_editor.read_line(&prompt).unwrap();
+        line_editor.report_command_finished(Some(0)).unwrap();
+
+        assert!(!terminal.output().contains("\x1b]133;"));
+    }
+
+    #[test]
+    fn pending_sequences_are_resolved_after_their_timeout() {
+        let ctrl_x = KeyCombination {
+            modifier: KeyModifiers::CONTROL,
+            key_code: KeyCode::Char('x'),
+        };
+        let mut keybindings = default_emacs_keybindings();
+        keybindings.add_binding(
+            KeyModifiers::CONTROL,
+            KeyCode::Char('x'),
+            ReedlineEvent::Edit(vec![EditCommand::InsertChar('!')]),
+        );
+        keybindings.add_sequence_binding(
+            vec![ctrl_x.clone(), ctrl_x],
+            ReedlineEvent::Edit(vec![EditCommand::InsertString("twice".to_string())]),
+        );
+        let terminal = VirtualTerminal::new(20, 5);
+        terminal.push_str("a");
+        for _ in 0..2 {
+            terminal.push_event(Event::Key(KeyEvent::new(
+                KeyCode::Char('x'),
+                KeyModifiers::CONTROL,
+            )));
+        }
+        terminal.push_str("b");
+        terminal.push_event(Event::Key(KeyEvent::new(
+            KeyCode::Char('x'),
+            KeyModifiers::CONTROL,
+        )));
+
+        let mut line_editor = line_editor(&terminal).with_edit_mode(Box::new(
+            Emacs::new(keybindings).with_sequence_timeout(Duration::from_millis(20)),
+        ));
+
+        assert!(line_editor.read_line(&prompt()).is_err());
+        assert_eq!(line_editor.current_buffer_contents(), "atwiceb!");
+    }
+
+    #[cfg(feature = "async")]
+    #[test]
+    fn pending_sequences_time_out_in_read_line_async() {
+        use crate::backend::test_fixtures::eventually;
+
+        let ctrl_x = KeyCombination {
+            modifier: KeyModifiers::CONTROL,
+            key_code: KeyCode::Char('x'),
+        };
+        let mut keybindings = default_emacs_keybindings();
+        keybindings.add_binding(
+            KeyModifiers::CONTROL,
+            KeyCode::Char('x'),
+            ReedlineEvent::Edit(vec![EditCommand::InsertChar('!')]),
+        );
+        keybindings.add_sequence_binding(
+            vec![ctrl_x.clone(), ctrl_x],
+            ReedlineEvent::Edit(vec![EditCommand::InsertString("twice".to_string())]),
+        );
+        let terminal = VirtualTerminal::new(20, 5);
+        terminal.push_str("a");
+        terminal.push_event(Event::Key(KeyEvent::new(
+            KeyCode::Char('x'),
+            KeyModifiers::CONTROL,
+        )));
+
+        let mut line_editor = line_editor(&terminal).with_edit_mode(Box::new(
+            Emacs::new(keybindings).with_sequence_timeout(Duration::from_millis(20)),
+        ));
+        let watched = terminal.clone();
+        let watcher = std::thread::spawn(move || {
+            let resolved = eventually(|| watched.screen()[0].ends_with("a!"));
+            watched.push_event(key(KeyCode::Enter));
+            resolved
+        });
+        let signal = futures::executor::block_on(line_editor.read_line_async(&prompt())).unwrap();
+
+        assert!(watcher.join().unwrap());
+        assert!(matches!(signal, Signal::Success(buffer) if buffer == "a!"));
+    }
+
+    #[cfg(feature = "system_clipboard")]
+    #[test]
+    fn osc52_copies_are_written_to_the_sink() {
+        let terminal = VirtualTerminal::new(20, 5);
+        let mut line_editor = line_editor(&terminal).with_osc52_clipboard(Osc52Clipboard::new());
+
+        line_editor.run_edit_commands(&[
+            EditCommand::InsertString("abc".to_string()),
+            EditCommand::SelectAll,
+            EditCommand::CopySelectionSystem,
+        ]);
+
+        assert_eq!(terminal.output(), "\x1b]52;c;YWJj\x1b\\");
+    }
+
+    #[cfg(feature = "system_clipboard")]
+    #[rstest]
+    #[case(Some(&b"\x1b]52;c;Zm9v\x07\x1b[?62;22c"[..]), "abcfoo")]
+    #[case(None, "abcabc")]
+    fn osc52_pastes_read_the_reply_from_the_input_source(
+        #[case] reply: Option<&[u8]>,
+        #[case] expected: &str,
+    ) {
+        let terminal = VirtualTerminal::new(20, 5);
+        if let Some(reply) = reply {
+            terminal.push_reply(reply);
+        }
+        let clipboard =
+            Osc52Clipboard::new().with_paste_from_terminal(true, Duration::from_millis(10));
+        let mut line_editor = line_editor(&terminal).with_osc52_clipboard(clipboard);
+
+        line_editor.run_edit_commands(&[
+            EditCommand::InsertString("abc".to_string()),
+            EditCommand::SelectAll,
+            EditCommand::CopySelectionSystem,
+            EditCommand::MoveToEnd { select: false },
+            EditCommand::PasteSystem,
+        ]);
+
+        assert_eq!(line_editor.current_buffer_contents(), expected);
+        assert_eq!(
+            terminal.output(),
+            "\x1b]52;c;YWJj\x1b\\\x1b]52;c;?\x1b\\\x1b[c"
+        );
+    }
+}
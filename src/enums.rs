@@ -12,6 +12,45 @@ pub enum Signal {
     CtrlC, // Interrupt current editing
     /// Abort with `Ctrl+D` signalling `EOF` or abort of a whole interactive session
     CtrlD, // End terminal session
+    /// No input was received within the timeout of [`crate::Reedline::read_line_with_idle_timeout()`]
+    IdleTimeout,
+}
+
+/// Whether a [`TextObject`] covers only its content or the text around it as well
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, Default, PartialEq, Eq)]
+pub enum TextObjectScope {
+    /// The content, like the text between two brackets (vi `i`)
+    #[default]
+    Inner,
+    /// The content with its delimiters or the whitespace after it (vi `a`)
+    Around,
+}
+
+/// The kind of text a [`TextObject`] covers
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, Default, PartialEq, Eq)]
+pub enum TextObjectType {
+    /// A word of letters, digits and underscores, or of other non-blank characters (vi `w`)
+    #[default]
+    Word,
+    /// A word of non-blank characters (vi `W`)
+    BigWord,
+    /// A string between two of the given quote characters on the cursor line
+    Quote(char),
+    /// The text between the given opening bracket and its closing bracket, which may be on
+    /// another line
+    Bracket(char),
+    /// An argument between the commas of the innermost brackets around the cursor (vi `a`)
+    Argument,
+}
+
+/// The text around the cursor an operator works on, like the word of vi `daw` or the
+/// brackets of vi `ci(`
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, Default, PartialEq, Eq)]
+pub struct TextObject {
+    /// Whether the delimiters or the whitespace around the text are included
+    pub scope: TextObjectScope,
+    /// The kind of text
+    pub object_type: TextObjectType,
 }
 
 /// Editing actions which can be mapped to key bindings.
@@ -106,6 +145,18 @@ pub enum EditCommand {
         select: bool,
     },
 
+    /// Move to the line above, staying in the buffer on its first line
+    MoveLineUp {
+        /// Select the text between the current cursor position and destination
+        select: bool,
+    },
+
+    /// Move to the line below, staying in the buffer on its last line
+    MoveLineDown {
+        /// Select the text between the current cursor position and destination
+        select: bool,
+    },
+
     /// Insert a character at the current insertion point
     InsertChar(char),
 
@@ -118,6 +169,15 @@ pub enum EditCommand {
     /// - On Windows CRLF (`"\r\n"`)
     InsertNewline,
 
+    /// Add one level of indentation to the selected lines or the current line
+    IndentLines,
+
+    /// Remove one level of indentation from the selected lines or the current line
+    DedentLines,
+
+    /// Join the selected lines, or the current line and the line below, with single spaces
+    JoinLines,
+
     /// Replace a character
     ReplaceChar(char),
 
@@ -258,12 +318,89 @@ pub enum EditCommand {
     /// Select whole input buffer
     SelectAll,
 
+    /// Select the current line, moves with `select` extend the selection by whole lines
+    SelectLines,
+
     /// Cut selection to local buffer
     CutSelection,
 
+    /// Cut selection to local buffer to replace it, selected lines leave an empty line behind
+    ChangeSelection,
+
+    /// Cut the text object around the cursor to local buffer
+    CutTextObject {
+        /// The text object to cut
+        text_object: TextObject,
+    },
+
+    /// Select the text object around the cursor
+    SelectTextObject {
+        /// The text object to select
+        text_object: TextObject,
+    },
+
     /// Copy selection to local buffer
     CopySelection,
 
+    /// Copy the current line (vi `yy`)
+    CopyCurrentLine,
+
+    /// Copy from the start of the current line to the insertion point
+    CopyFromLineStart,
+
+    /// Copy from the insertion point to the end of the current line
+    CopyToLineEnd,
+
+    /// Copy the word left of the insertion point
+    CopyWordLeft,
+
+    /// Copy the WORD left of the insertion point
+    CopyBigWordLeft,
+
+    /// Copy the word right of the insertion point
+    CopyWordRight,
+
+    /// Copy the WORD right of the insertion point
+    CopyBigWordRight,
+
+    /// Copy the word right of the insertion point and any following space
+    CopyWordRightToNext,
+
+    /// Copy the WORD right of the insertion point and any following space
+    CopyBigWordRightToNext,
+
+    /// Copy the grapheme left of the insertion point
+    CopyLeft,
+
+    /// Copy the grapheme right of the insertion point
+    CopyRight,
+
+    /// Copy right until char
+    CopyRightUntil(char),
+
+    /// Copy right before char
+    CopyRightBefore(char),
+
+    /// Copy left until char
+    CopyLeftUntil(char),
+
+    /// Copy left before char
+    CopyLeftBefore(char),
+
+    /// Copy the text object around the cursor to local buffer
+    CopyTextObject {
+        /// The text object to copy
+        text_object: TextObject,
+    },
+
+    /// Use the given register instead of the local buffer for the next cut, copy or paste
+    /// (vi `"a`)
+    ///
+    /// `a`-`z` are named registers, `A`-`Z` append to them, `0` holds the last copy and
+    /// `1`-`9` the last cuts. `+` is the system clipboard when the feature `system_clipboard`
+    /// is enabled.
+    UseRegister(char),
+
     /// Paste content from local buffer at the current cursor position
     Paste,
 
@@ -315,6 +452,10 @@ impl Display for EditCommand {
             EditCommand::MoveToPosition { .. } => {
                 write!(f, "MoveToPosition  Value: <int>, Optional[select: <bool>]")
             }
+            EditCommand::MoveLineUp { .. } => write!(f, "MoveLineUp Optional[select: <bool>]"),
+            EditCommand::MoveLineDown { .. } => {
+                write!(f, "MoveLineDown Optional[select: <bool>]")
+            }
             EditCommand::MoveLeftUntil { .. } => {
                 write!(f, "MoveLeftUntil Value: <char>, Optional[select: <bool>]")
             }
@@ -324,6 +465,9 @@ impl Display for EditCommand {
             EditCommand::InsertChar(_) => write!(f, "InsertChar  Value: <char>"),
             EditCommand::InsertString(_) => write!(f, "InsertString Value: <string>"),
             EditCommand::InsertNewline => write!(f, "InsertNewline"),
+            EditCommand::IndentLines => write!(f, "IndentLines"),
+            EditCommand::DedentLines => write!(f, "DedentLines"),
+            EditCommand::JoinLines => write!(f, "JoinLines"),
             EditCommand::ReplaceChar(_) => write!(f, "ReplaceChar <char>"),
             EditCommand::ReplaceChars(_, _) => write!(f, "ReplaceChars <int> <string>"),
             EditCommand::Backspace => write!(f, "Backspace"),
@@ -362,8 +506,31 @@ impl Display for EditCommand {
             EditCommand::CutLeftUntil(_) => write!(f, "CutLeftUntil Value: <char>"),
             EditCommand::CutLeftBefore(_) => write!(f, "CutLeftBefore Value: <char>"),
             EditCommand::SelectAll => write!(f, "SelectAll"),
+            EditCommand::SelectLines => write!(f, "SelectLines"),
             EditCommand::CutSelection => write!(f, "CutSelection"),
+            EditCommand::ChangeSelection => write!(f, "ChangeSelection"),
+            EditCommand::CutTextObject { .. } => write!(f, "CutTextObject Value: <TextObject>"),
+            EditCommand::SelectTextObject { .. } => {
+                write!(f, "SelectTextObject Value: <TextObject>")
+            }
             EditCommand::CopySelection => write!(f, "CopySelection"),
+            EditCommand::CopyCurrentLine => write!(f, "CopyCurrentLine"),
+            EditCommand::CopyFromLineStart => write!(f, "CopyFromLineStart"),
+            EditCommand::CopyToLineEnd => write!(f, "CopyToLineEnd"),
+            EditCommand::CopyWordLeft => write!(f, "CopyWordLeft"),
+            EditCommand::CopyBigWordLeft => write!(f, "CopyBigWordLeft"),
+            EditCommand::CopyWordRight => write!(f, "CopyWordRight"),
+            EditCommand::CopyBigWordRight => write!(f, "CopyBigWordRight"),
+            EditCommand::CopyWordRightToNext => write!(f, "CopyWordRightToNext"),
+            EditCommand::CopyBigWordRightToNext => write!(f, "CopyBigWordRightToNext"),
+            EditCommand::CopyLeft => write!(f, "CopyLeft"),
+            EditCommand::CopyRight => write!(f, "CopyRight"),
+            EditCommand::CopyRightUntil(_) => write!(f, "CopyRightUntil Value: <char>"),
+            EditCommand::CopyRightBefore(_) => write!(f, "CopyRightBefore Value: <char>"),
+            EditCommand::CopyLeftUntil(_) => write!(f, "CopyLeftUntil Value: <char>"),
+            EditCommand::CopyLeftBefore(_) => write!(f, "CopyLeftBefore Value: <char>"),
+            EditCommand::CopyTextObject { .. } => write!(f, "CopyTextObject Value: <TextObject>"),
+            EditCommand::UseRegister(_) => write!(f, "UseRegister Value: <char>"),
             EditCommand::Paste => write!(f, "Paste"),
             #[cfg(feature = "system_clipboard")]
             EditCommand::CutSelectionSystem => write!(f, "CutSelectionSystem"),
@@ -386,6 +553,8 @@ impl EditCommand {
             | EditCommand::MoveToLineStart { select, .. }
             | EditCommand::MoveToLineEnd { select, .. }
             | EditCommand::MoveToPosition { select, .. }
+            | EditCommand::MoveLineUp { select, .. }
+            | EditCommand::MoveLineDown { select, .. }
             | EditCommand::MoveLeft { select, .. }
             | EditCommand::MoveRight { select, .. }
             | EditCommand::MoveWordLeft { select, .. }
@@ -402,7 +571,9 @@ impl EditCommand {
                 EditType::MoveCursor { select: *select }
             }
 
-            EditCommand::SelectAll => EditType::MoveCursor { select: true },
+            EditCommand::SelectAll
+            | EditCommand::SelectLines
+            | EditCommand::SelectTextObject { .. } => EditType::MoveCursor { select: true },
             // Text edits
             EditCommand::InsertChar(_)
             | EditCommand::Backspace
@@ -410,6 +581,9 @@ impl EditCommand {
             | EditCommand::CutChar
             | EditCommand::InsertString(_)
             | EditCommand::InsertNewline
+            | EditCommand::IndentLines
+            | EditCommand::DedentLines
+            | EditCommand::JoinLines
             | EditCommand::ReplaceChar(_)
             | EditCommand::ReplaceChars(_, _)
             | EditCommand::BackspaceWord
@@ -441,6 +615,8 @@ impl EditCommand {
             | EditCommand::CutLeftUntil(_)
             | EditCommand::CutLeftBefore(_)
             | EditCommand::CutSelection
+            | EditCommand::ChangeSelection
+            | EditCommand::CutTextObject { .. }
             | EditCommand::Paste => EditType::EditText,
 
             #[cfg(feature = "system_clipboard")] // Sadly cfg attributes in patterns don't work
@@ -448,7 +624,24 @@ impl EditCommand {
 
             EditCommand::Undo | EditCommand::Redo => EditType::UndoRedo,
 
-            EditCommand::CopySelection => EditType::NoOp,
+            EditCommand::CopySelection
+            | EditCommand::CopyCurrentLine
+            | EditCommand::CopyFromLineStart
+            | EditCommand::CopyToLineEnd
+            | EditCommand::CopyWordLeft
+            | EditCommand::CopyBigWordLeft
+            | EditCommand::CopyWordRight
+            | EditCommand::CopyBigWordRight
+            | EditCommand::CopyWordRightToNext
+            | EditCommand::CopyBigWordRightToNext
+            | EditCommand::CopyLeft
+            | EditCommand::CopyRight
+            | EditCommand::CopyRightUntil(_)
+            | EditCommand::CopyRightBefore(_)
+            | EditCommand::CopyLeftUntil(_)
+            | EditCommand::CopyLeftBefore(_)
+            | EditCommand::CopyTextObject { .. }
+            | EditCommand::UseRegister(_) => EditType::NoOp,
             #[cfg(feature = "system_clipboard")]
             EditCommand::CopySelectionSystem => EditType::NoOp,
         }
@@ -572,8 +765,11 @@ pub enum ReedlineEvent {
     /// Esc event
     Esc,
 
-    /// Mouse
-    Mouse, // Fill in details later
+    /// Left click at the given column and row of the screen
+    ///
+    /// Moves the cursor to the clicked position in the buffer or selects the clicked entry of
+    /// the active menu. Only reported with [`crate::Reedline::use_mouse_capture()`]
+    MouseClick(u16, u16),
 
     /// trigger terminal resize
     Resize(u16, u16),
@@ -644,6 +840,35 @@ pub enum ReedlineEvent {
 
     /// Open text editor
     OpenEditor,
+
+    /// Run the command registered under the name with
+    /// [`crate::Reedline::with_custom_command()`] and continue editing
+    CustomCommand(String),
+
+    /// Suspend the process like a shell job, what Ctrl-Z does outside of raw mode
+    ///
+    /// The terminal is restored before `SIGTSTP` is raised and set up again once the process
+    /// is continued, the prompt is then painted anew. Only supported on Unix, ignored
+    /// elsewhere.
+    Suspend,
+
+    /// Start recording the events of a keyboard macro
+    StartMacro,
+
+    /// Stop recording the keyboard macro, it becomes the last macro
+    EndMacro,
+
+    /// Replay the last recorded keyboard macro the given number of times
+    CallLastMacro(usize),
+
+    /// Replay the keyboard macro saved under the name the given number of times
+    CallMacro(String, usize),
+
+    /// Switch the vi edit mode to `"normal"` or `"insert"`
+    ///
+    /// Switching to normal mode works like pressing Esc. The [`crate::Vi`] edit mode applies
+    /// the switch itself, other edit modes ignore it.
+    ViChangeMode(String),
 }
 
 impl Display for ReedlineEvent {
@@ -660,7 +885,7 @@ impl Display for ReedlineEvent {
             ReedlineEvent::Submit => write!(f, "Submit"),
             ReedlineEvent::SubmitOrNewline => write!(f, "SubmitOrNewline"),
             ReedlineEvent::Esc => write!(f, "Esc"),
-            ReedlineEvent::Mouse => write!(f, "Mouse"),
+            ReedlineEvent::MouseClick(_, _) => write!(f, "MouseClick <int> <int>"),
             ReedlineEvent::Resize(_, _) => write!(f, "Resize <int> <int>"),
             ReedlineEvent::Edit(_) => write!(
                 f,
@@ -687,6 +912,13 @@ impl Display for ReedlineEvent {
             ReedlineEvent::MenuPagePrevious => write!(f, "MenuPagePrevious"),
             ReedlineEvent::ExecuteHostCommand(_) => write!(f, "ExecuteHostCommand"),
             ReedlineEvent::OpenEditor => write!(f, "OpenEditor"),
+            ReedlineEvent::CustomCommand(_) => write!(f, "CustomCommand Name: <string>"),
+            ReedlineEvent::Suspend => write!(f, "Suspend"),
+            ReedlineEvent::StartMacro => write!(f, "StartMacro"),
+            ReedlineEvent::EndMacro => write!(f, "EndMacro"),
+            ReedlineEvent::CallLastMacro(_) => write!(f, "CallLastMacro <int>"),
+            ReedlineEvent::CallMacro(_, _) => write!(f, "CallMacro Name: <string> <int>"),
+            ReedlineEvent::ViChangeMode(_) => write!(f, "ViChangeMode Mode: <string>"),
         }
     }
 }
@@ -0,0 +1,137 @@
+//! To send events into a running line editor from other threads
+#[cfg(feature = "external_printer")]
+use {
+    crate::{EditCommand, ReedlineEvent},
+    crossbeam::channel::{bounded, Receiver, SendError, Sender},
+};
+
+#[cfg(feature = "external_printer")]
+pub const EVENT_INJECTOR_DEFAULT_CAPACITY: usize = 20;
+
+/// An EventInjector allows to send [`ReedlineEvent`]s to the active
+/// [`crate::Reedline::read_line()`] or `read_line_async()` while the user is editing a line.
+///
+/// The events are handled like the ones produced by the edit mode, e.g. to insert
+/// text picked in the background, force a repaint or submit the buffer. They are picked
+/// up together with the messages of the [`crate::ExternalPrinter`], also while no key is
+/// pressed.
+///
+/// ## Required feature:
+/// `external_printer`, which is still experimental
+#[cfg(feature = "external_printer")]
+#[derive(Debug, Clone)]
+pub struct EventInjector {
+    sender: Sender<ReedlineEvent>,
+    receiver: Receiver<ReedlineEvent>,
+}
+
+#[cfg(feature = "external_printer")]
+impl EventInjector {
+    /// Creates an EventInjector to queue up to max_cap events
+    pub fn new(max_cap: usize) -> Self {
+        let (sender, receiver) = bounded::<ReedlineEvent>(max_cap);
+        Self { sender, receiver }
+    }
+
+    /// Gets a Sender to use the injector externally by sending events to it
+    pub fn sender(&self) -> Sender<ReedlineEvent> {
+        self.sender.clone()
+    }
+
+    /// Receiver to get the events if any
+    pub fn receiver(&self) -> &Receiver<ReedlineEvent> {
+        &self.receiver
+    }
+
+    /// Convenience method to send an event, blocks if max_cap is reached.
+    pub fn send(&self, event: ReedlineEvent) -> Result<(), SendError<ReedlineEvent>> {
+        self.sender.send(event)
+    }
+
+    /// Convenience method to run edit commands on the buffer, blocks if max_cap is reached.
+    pub fn edit(&self, commands: Vec<EditCommand>) -> Result<(), SendError<ReedlineEvent>> {
+        self.send(ReedlineEvent::Edit(commands))
+    }
+
+    /// Takes all queued events, doesn't block.
+    pub(crate) fn pending_events(&self) -> Vec<ReedlineEvent> {
+        self.receiver.try_iter().collect()
+    }
+}
+
+#[cfg(feature = "external_printer")]
+impl Default for EventInjector {
+    fn default() -> Self {
+        Self::new(EVENT_INJECTOR_DEFAULT_CAPACITY)
+    }
+}
+
+#[cfg(all(test, feature = "external_printer"))]
+mod tests {
+    use super::*;
+    use crate::backend::test_fixtures::{line_editor, prompt};
+    use crate::{Signal, VirtualTerminal};
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn injected_events_edit_and_submit_the_buffer() {
+        let terminal = VirtualTerminal::new(20, 5);
+        let injector = EventInjector::default();
+        let mut line_editor = line_editor(&terminal).with_event_injector(injector.clone());
+
+        injector
+            .edit(vec![EditCommand::InsertString("picked".to_string())])
+            .unwrap();
+        injector.send(ReedlineEvent::Submit).unwrap();
+
+        let signal = line_editor.read_line(&prompt()).unwrap();
+        assert!(matches!(signal, Signal::Success(buffer) if buffer == "picked"));
+        assert_eq!(terminal.screen()[0], "> 〉picked");
+    }
+
+    #[test]
+    fn injected_events_mix_with_terminal_input() {
+        let terminal = VirtualTerminal::new(20, 5);
+        terminal.push_str("ab");
+        let injector = EventInjector::default();
+        let mut line_editor = line_editor(&terminal).with_event_injector(injector.clone());
+
+        injector
+            .edit(vec![EditCommand::InsertString("x".to_string())])
+            .unwrap();
+
+        // The script runs out after the typed characters
+        assert!(line_editor.read_line(&prompt()).is_err());
+        assert_eq!(line_editor.current_buffer_contents(), "xab");
+        assert_eq!(terminal.screen()[0], "> 〉xab");
+    }
+
+    #[cfg(feature = "async")]
+    #[test]
+    fn injected_events_are_handled_by_read_line_async_without_input() {
+        use crate::backend::test_fixtures::{eventually, key};
+        use crossterm::event::KeyCode;
+
+        let terminal = VirtualTerminal::new(20, 5);
+        let injector = EventInjector::default();
+        let mut line_editor = line_editor(&terminal).with_event_injector(injector.clone());
+
+        let watched = terminal.clone();
+        let watcher = std::thread::spawn(move || {
+            assert!(eventually(|| watched.is_raw_mode_enabled()));
+            injector
+                .edit(vec![EditCommand::InsertString("picked".to_string())])
+                .unwrap();
+            injector.send(ReedlineEvent::Submit).unwrap();
+            let submitted = eventually(|| !watched.is_raw_mode_enabled());
+            // Ends the line editor if the injected events were not handled
+            watched.push_str("typed");
+            watched.push_event(key(KeyCode::Enter));
+            submitted
+        });
+        let signal = futures::executor::block_on(line_editor.read_line_async(&prompt())).unwrap();
+
+        assert!(watcher.join().unwrap());
+        assert!(matches!(signal, Signal::Success(buffer) if buffer == "picked"));
+    }
+}
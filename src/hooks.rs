@@ -0,0 +1,241 @@
+use crate::{LineBuffer, PromptEditMode};
+
+/// Callbacks keeping the application informed about the editor while the user types
+///
+/// Register them with [`crate::Reedline::with_editor_hooks()`]. All methods do nothing by
+/// default, implement the ones you are interested in.
+pub trait EditorHooks: Send {
+    /// The buffer or the position of the cursor in it changed
+    fn buffer_changed(&mut self, _line_buffer: &LineBuffer) {}
+
+    /// The edit mode switched to another mode, e.g. from vi insert to vi normal mode
+    fn mode_changed(&mut self, _mode: &PromptEditMode) {}
+
+    /// The user submitted `buffer`, decide whether to accept it as is
+    ///
+    /// Runs before the buffer is added to the history and returned from
+    /// [`crate::Reedline::read_line()`].
+    fn pre_submit(&mut self, _buffer: &str) -> PreSubmit {
+        PreSubmit::Accept
+    }
+
+    /// `buffer` was submitted and added to the history
+    fn post_submit(&mut self, _buffer: &str) {}
+}
+
+/// Decision of [`EditorHooks::pre_submit()`] about the submitted buffer
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PreSubmit {
+    /// Submit the buffer as is
+    Accept,
+
+    /// Replace the buffer with the given text and submit that instead
+    Rewrite(String),
+
+    /// Don't submit the buffer, the user keeps editing it
+    ///
+    /// Without a terminal the line is dropped and the next one is read.
+    Veto,
+}
+
+/// [`EditorHooks`] together with the state they were told about last
+pub(crate) struct HookRunner {
+    hooks: Box<dyn EditorHooks>,
+    line_buffer: LineBuffer,
+    mode: PromptEditMode,
+}
+
+impl HookRunner {
+    pub(crate) fn new(
+        hooks: Box<dyn EditorHooks>,
+        line_buffer: &LineBuffer,
+        mode: PromptEditMode,
+    ) -> Self {
+        HookRunner {
+            hooks,
+            line_buffer: line_buffer.clone(),
+            mode,
+        }
+    }
+
+    /// Runs the change hooks for what differs from the last reported state
+    pub(crate) fn report_changes(&mut self, line_buffer: &LineBuffer, mode: PromptEditMode) {
+        if mode != self.mode {
+            self.hooks.mode_changed(&mode);
+            self.mode = mode;
+        }
+        if *line_buffer != self.line_buffer {
+            self.hooks.buffer_changed(line_buffer);
+            self.line_buffer = line_buffer.clone();
+        }
+    }
+
+    /// Takes `mode` as the last reported mode without running the hooks
+    pub(crate) fn sync_mode(&mut self, mode: PromptEditMode) {
+        self.mode = mode;
+    }
+
+    pub(crate) fn pre_submit(&mut self, buffer: &str) -> PreSubmit {
+        self.hooks.pre_submit(buffer)
+    }
+
+    pub(crate) fn post_submit(&mut self, buffer: &str) {
+        self.hooks.post_submit(buffer);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::test_fixtures::{key, line_editor, prompt};
+    use crate::{
+        default_vi_insert_keybindings, default_vi_normal_keybindings, EditCommand, Signal, Vi,
+        VirtualTerminal,
+    };
+    use crossterm::event::KeyCode;
+    use pretty_assertions::assert_eq;
+    use std::sync::{Arc, Mutex};
+
+    // Hooks writing down every call and rewriting or vetoing the buffers starting with `!`
+    #[derive(Clone, Default)]
+    struct RecordingHooks {
+        calls: Arc<Mutex<Vec<String>>>,
+    }
+
+    impl RecordingHooks {
+        fn calls(&self) -> Vec<String> {
+            self.calls.lock().unwrap().clone()
+        }
+
+        fn record(&self, call: String) {
+            self.calls.lock().unwrap().push(call);
+        }
+    }
+
+    impl EditorHooks for RecordingHooks {
+        fn buffer_changed(&mut self, line_buffer: &LineBuffer) {
+            self.record(format!(
+                "buffer {:?} {}",
+                line_buffer.get_buffer(),
+                line_buffer.insertion_point()
+            ));
+        }
+
+        fn mode_changed(&mut self, mode: &PromptEditMode) {
+            self.record(format!("mode {mode:?}"));
+        }
+
+        fn pre_submit(&mut self, buffer: &str) -> PreSubmit {
+            self.record(format!("pre_submit {buffer:?}"));
+            match buffer {
+                "!veto" => PreSubmit::Veto,
+                buffer if buffer.starts_with('!') => PreSubmit::Rewrite(buffer[1..].to_string()),
+                _ => PreSubmit::Accept,
+            }
+        }
+
+        fn post_submit(&mut self, buffer: &str) {
+            self.record(format!("post_submit {buffer:?}"));
+        }
+    }
+
+    #[test]
+    fn reports_buffer_changes() {
+        let terminal = VirtualTerminal::new(20, 5);
+        terminal.push_str("ab");
+        terminal.push_event(key(KeyCode::Left));
+        // The second End doesn't move the cursor
+        terminal.push_event(key(KeyCode::End));
+        terminal.push_event(key(KeyCode::End));
+        let hooks = RecordingHooks::default();
+
+        let mut line_editor = line_editor(&terminal).with_editor_hooks(Box::new(hooks.clone()));
+        line_editor.run_edit_commands(&[EditCommand::InsertString("x".to_string())]);
+        assert!(line_editor.read_line(&prompt()).is_err());
+
+        assert_eq!(
+            hooks.calls(),
+            [
+                r#"buffer "x" 1"#,
+                // Keys already waiting are handled together
+                r#"buffer "xab" 3"#,
+                r#"buffer "xab" 2"#,
+                r#"buffer "xab" 3"#,
+            ]
+        );
+    }
+
+    #[test]
+    fn reports_mode_changes() {
+        let terminal = VirtualTerminal::new(20, 5);
+        terminal.push_event(key(KeyCode::Esc));
+        let hooks = RecordingHooks::default();
+
+        let mut line_editor = line_editor(&terminal)
+            .with_edit_mode(Box::new(Vi::new(
+                default_vi_insert_keybindings(),
+                default_vi_normal_keybindings(),
+            )))
+            .with_editor_hooks(Box::new(hooks.clone()));
+        assert!(line_editor.read_line(&prompt()).is_err());
+
+        assert_eq!(hooks.calls(), ["mode Vi(Normal)"]);
+    }
+
+    #[rstest::rstest]
+    #[case(false)]
+    #[case(true)]
+    fn initial_edit_mode_is_no_mode_change(#[case] hooks_first: bool) {
+        let terminal = VirtualTerminal::new(20, 5);
+        terminal.push_str("a");
+        let hooks = RecordingHooks::default();
+        let vi = Box::new(Vi::new(
+            default_vi_insert_keybindings(),
+            default_vi_normal_keybindings(),
+        ));
+
+        let mut line_editor = if hooks_first {
+            line_editor(&terminal)
+                .with_editor_hooks(Box::new(hooks.clone()))
+                .with_edit_mode(vi)
+        } else {
+            line_editor(&terminal)
+                .with_edit_mode(vi)
+                .with_editor_hooks(Box::new(hooks.clone()))
+        };
+        assert!(line_editor.read_line(&prompt()).is_err());
+
+        assert_eq!(hooks.calls(), [r#"buffer "a" 1"#]);
+    }
+
+    #[rstest::rstest]
+    #[case("ls", Some("ls"))]
+    #[case("!ls", Some("ls"))]
+    #[case("!veto", None)]
+    fn pre_submit_decides_about_the_buffer(#[case] typed: &str, #[case] expected: Option<&str>) {
+        let terminal = VirtualTerminal::new(20, 5);
+        terminal.push_str(typed);
+        terminal.push_event(key(KeyCode::Enter));
+        let hooks = RecordingHooks::default();
+
+        let mut line_editor = line_editor(&terminal).with_editor_hooks(Box::new(hooks.clone()));
+        let signal = line_editor.read_line(&prompt());
+
+        match expected {
+            Some(expected) => {
+                assert!(matches!(signal, Ok(Signal::Success(buffer)) if buffer == expected));
+                assert!(hooks
+                    .calls()
+                    .ends_with(&[format!("post_submit {expected:?}"), r#"buffer "" 0"#.into()]));
+            }
+            None => {
+                assert!(signal.is_err());
+                assert_eq!(line_editor.current_buffer_contents(), typed);
+                assert_eq!(
+                    hooks.calls().last().unwrap(),
+                    &format!("pre_submit {typed:?}")
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,72 @@
+use crate::{validator::open_brackets, Indenter};
+
+/// An indenter which indents the lines by the depth of the brackets left open before them,
+/// matching the brackets checked by [`crate::DefaultValidator`]
+///
+/// A line starting with the closer of the innermost open bracket is dedented once.
+pub struct DefaultIndenter {
+    unit: String,
+}
+
+impl Default for DefaultIndenter {
+    fn default() -> Self {
+        DefaultIndenter {
+            unit: "    ".to_string(),
+        }
+    }
+}
+
+impl DefaultIndenter {
+    /// A builder that sets the text of one level of indentation, four spaces by default
+    #[must_use]
+    pub fn with_unit(mut self, unit: &str) -> Self {
+        self.unit = unit.to_string();
+        self
+    }
+}
+
+impl Indenter for DefaultIndenter {
+    fn indentation(&self, buffer: &str, line_start: usize) -> String {
+        let open = open_brackets(&buffer[..line_start]);
+        let closes_innermost = buffer[line_start..]
+            .trim_start_matches([' ', '\t'])
+            .chars()
+            .next()
+            .filter(|c| open.last() == Some(c))
+            .is_some();
+        self.unit.repeat(open.len() - usize::from(closes_innermost))
+    }
+
+    fn reindents_on(&self, c: char) -> bool {
+        matches!(c, ')' | ']' | '}')
+    }
+
+    fn unit(&self) -> &str {
+        &self.unit
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case("ls", 2, "")]
+    #[case("if $x {\n", 8, "    ")]
+    #[case("[{\n", 3, "        ")]
+    #[case("[{\n    }", 3, "    ")]
+    #[case("do {\n  }", 5, "")]
+    #[case("(a)\n", 4, "")]
+    fn indentation_follows_the_open_brackets(
+        #[case] buffer: &str,
+        #[case] line_start: usize,
+        #[case] expected: &str,
+    ) {
+        assert_eq!(
+            DefaultIndenter::default().indentation(buffer, line_start),
+            expected
+        );
+    }
+}
@@ -0,0 +1,27 @@
+mod default;
+pub use default::DefaultIndenter;
+
+/// The auto-indentation trait. Implementers of this trait decide how far the lines of a
+/// multi-line buffer are indented while they are typed
+///
+/// Used for the lines started by [`crate::EditCommand::InsertNewline`], e.g. when
+/// [`crate::ValidationResult::Incomplete`] makes Enter insert a newline, and by
+/// [`crate::EditCommand::IndentLines`] and [`crate::EditCommand::DedentLines`].
+pub trait Indenter: Send {
+    /// The indentation of the line starting at `line_start` of the `buffer`
+    ///
+    /// The text of the line after its current indentation is already part of the `buffer`.
+    fn indentation(&self, buffer: &str, line_start: usize) -> String;
+
+    /// Whether typing `c` first on a line indents the line again, e.g. to dedent a closing
+    /// bracket
+    fn reindents_on(&self, _c: char) -> bool {
+        false
+    }
+
+    /// One level of indentation added or removed by [`crate::EditCommand::IndentLines`] and
+    /// [`crate::EditCommand::DedentLines`]
+    fn unit(&self) -> &str {
+        "    "
+    }
+}
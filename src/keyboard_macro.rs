@@ -0,0 +1,234 @@
+use crate::ReedlineEvent;
+use serde::{Deserialize, Serialize};
+
+/// Sequence of [`ReedlineEvent`]s recorded while the user was editing
+///
+/// Macros are recorded between [`ReedlineEvent::StartMacro`] and [`ReedlineEvent::EndMacro`]
+/// and replayed with [`ReedlineEvent::CallLastMacro`]. Store them under a name with
+/// [`crate::Reedline::save_last_macro()`] to replay them with [`ReedlineEvent::CallMacro`].
+/// As they serialize like the contained events, named macros can be persisted next to the
+/// keybindings.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, Debug, Default)]
+#[serde(transparent)]
+pub struct KeyboardMacro {
+    events: Vec<ReedlineEvent>,
+}
+
+impl KeyboardMacro {
+    /// Create a macro replaying the given events
+    pub fn new(events: Vec<ReedlineEvent>) -> Self {
+        KeyboardMacro { events }
+    }
+
+    /// The events replayed by the macro
+    pub fn events(&self) -> &[ReedlineEvent] {
+        &self.events
+    }
+
+    /// Checks if the macro doesn't contain any event
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+
+    /// Append an event produced by the edit mode while recording
+    ///
+    /// Events controlling the macros, terminal resizes and mouse clicks are not recorded. The
+    /// events replayed by calls of other macros are recorded in place of the calls.
+    pub(crate) fn record(&mut self, event: &ReedlineEvent) {
+        match event {
+            ReedlineEvent::StartMacro
+            | ReedlineEvent::EndMacro
+            | ReedlineEvent::CallLastMacro(_)
+            | ReedlineEvent::CallMacro(_, _)
+            | ReedlineEvent::Resize(_, _)
+            | ReedlineEvent::MouseClick(_, _)
+            | ReedlineEvent::None => {}
+            ReedlineEvent::Edit(commands) => match self.events.last_mut() {
+                // Keep typed text as a single edit like the engine fuses them
+                Some(ReedlineEvent::Edit(previous)) => previous.extend(commands.iter().cloned()),
+                _ => self.events.push(event.clone()),
+            },
+            event => self.events.push(event.clone()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::test_fixtures::{self, key, prompt};
+    use crate::{default_emacs_keybindings, EditCommand, Emacs, Reedline, Signal, VirtualTerminal};
+    use crossterm::event::{KeyCode, KeyModifiers};
+    use pretty_assertions::assert_eq;
+    use std::collections::HashMap;
+
+    fn line_editor(terminal: &VirtualTerminal) -> Reedline {
+        let mut keybindings = default_emacs_keybindings();
+        keybindings.add_binding(KeyModifiers::NONE, KeyCode::F(1), ReedlineEvent::StartMacro);
+        keybindings.add_binding(KeyModifiers::NONE, KeyCode::F(2), ReedlineEvent::EndMacro);
+        keybindings.add_binding(
+            KeyModifiers::NONE,
+            KeyCode::F(3),
+            ReedlineEvent::CallLastMacro(2),
+        );
+        keybindings.add_binding(
+            KeyModifiers::NONE,
+            KeyCode::F(4),
+            ReedlineEvent::CallMacro("wrap".to_string(), 1),
+        );
+        test_fixtures::line_editor(terminal).with_edit_mode(Box::new(Emacs::new(keybindings)))
+    }
+
+    #[test]
+    fn recording_skips_macro_events_and_fuses_edits() {
+        let mut keyboard_macro = KeyboardMacro::default();
+        for event in [
+            ReedlineEvent::StartMacro,
+            ReedlineEvent::Edit(vec![EditCommand::InsertChar('a')]),
+            ReedlineEvent::Resize(10, 10),
+            ReedlineEvent::Edit(vec![EditCommand::InsertChar('b')]),
+            ReedlineEvent::Left,
+            ReedlineEvent::CallLastMacro(1),
+        ] {
+            keyboard_macro.record(&event);
+        }
+
+        assert_eq!(
+            keyboard_macro.events(),
+            [
+                ReedlineEvent::Edit(vec![
+                    EditCommand::InsertChar('a'),
+                    EditCommand::InsertChar('b')
+                ]),
+                ReedlineEvent::Left,
+            ]
+        );
+    }
+
+    #[test]
+    fn serializes_as_list_of_events() {
+        let keyboard_macro = KeyboardMacro::new(vec![
+            ReedlineEvent::Edit(vec![EditCommand::MoveToStart { select: false }]),
+            ReedlineEvent::Submit,
+        ]);
+
+        let json = serde_json::to_string(&keyboard_macro).unwrap();
+        assert_eq!(
+            json,
+            r#"[{"Edit":[{"MoveToStart":{"select":false}}]},"Submit"]"#
+        );
+        assert_eq!(
+            serde_json::from_str::<KeyboardMacro>(&json).unwrap(),
+            keyboard_macro
+        );
+    }
+
+    #[test]
+    fn recorded_macro_is_replayed_with_repeat_count() {
+        let terminal = VirtualTerminal::new(40, 5);
+        terminal.push_event(key(KeyCode::F(1)));
+        terminal.push_str("ab");
+        terminal.push_event(key(KeyCode::Left));
+        terminal.push_event(key(KeyCode::F(2)));
+        terminal.push_event(key(KeyCode::F(3)));
+        terminal.push_event(key(KeyCode::Enter));
+
+        let mut line_editor = line_editor(&terminal);
+        let signal = line_editor.read_line(&prompt()).unwrap();
+
+        assert!(matches!(signal, Signal::Success(buffer) if buffer == "aaabbb"));
+        assert!(!line_editor.is_recording_macro());
+        assert_eq!(
+            line_editor.last_macro().map(KeyboardMacro::events),
+            Some(
+                &[
+                    ReedlineEvent::Edit(vec![
+                        EditCommand::InsertChar('a'),
+                        EditCommand::InsertChar('b')
+                    ]),
+                    ReedlineEvent::UntilFound(vec![ReedlineEvent::MenuLeft, ReedlineEvent::Left]),
+                ][..]
+            )
+        );
+    }
+
+    #[test]
+    fn macro_calls_are_recorded_as_their_events() {
+        let terminal = VirtualTerminal::new(40, 5);
+        terminal.push_event(key(KeyCode::F(1)));
+        terminal.push_str("x");
+        terminal.push_event(key(KeyCode::F(2)));
+        terminal.push_event(key(KeyCode::F(1)));
+        terminal.push_event(key(KeyCode::F(3)));
+        terminal.push_event(key(KeyCode::F(4)));
+        terminal.push_str("y");
+        terminal.push_event(key(KeyCode::F(2)));
+        terminal.push_event(key(KeyCode::F(3)));
+        terminal.push_event(key(KeyCode::Enter));
+
+        let macros = HashMap::from([(
+            "wrap".to_string(),
+            KeyboardMacro::new(vec![ReedlineEvent::Edit(vec![EditCommand::InsertChar(
+                '-',
+            )])]),
+        )]);
+        let mut line_editor = line_editor(&terminal).with_macros(macros);
+        let signal = line_editor.read_line(&prompt()).unwrap();
+
+        assert!(matches!(signal, Signal::Success(buffer) if buffer == "xxx-yxx-yxx-y"));
+        assert_eq!(
+            line_editor.last_macro().map(KeyboardMacro::events),
+            Some(
+                &[ReedlineEvent::Edit(vec![
+                    EditCommand::InsertChar('x'),
+                    EditCommand::InsertChar('x'),
+                    EditCommand::InsertChar('-'),
+                    EditCommand::InsertChar('y')
+                ])][..]
+            )
+        );
+    }
+
+    #[test]
+    fn named_macros_are_replayed() {
+        let terminal = VirtualTerminal::new(40, 5);
+        terminal.push_str("x");
+        terminal.push_event(key(KeyCode::F(4)));
+        terminal.push_event(key(KeyCode::Enter));
+
+        let macros = HashMap::from([(
+            "wrap".to_string(),
+            KeyboardMacro::new(vec![
+                ReedlineEvent::Edit(vec![EditCommand::MoveToStart { select: false }]),
+                ReedlineEvent::Edit(vec![EditCommand::InsertChar('(')]),
+                ReedlineEvent::Edit(vec![EditCommand::MoveToEnd { select: false }]),
+                ReedlineEvent::Edit(vec![EditCommand::InsertChar(')')]),
+            ]),
+        )]);
+        let mut line_editor = line_editor(&terminal).with_macros(macros);
+        let signal = line_editor.read_line(&prompt()).unwrap();
+
+        assert!(matches!(signal, Signal::Success(buffer) if buffer == "(x)"));
+    }
+
+    #[test]
+    fn last_macro_can_be_saved_under_a_name() {
+        let terminal = VirtualTerminal::new(40, 5);
+        terminal.push_event(key(KeyCode::F(1)));
+        terminal.push_str("z");
+        terminal.push_event(key(KeyCode::F(2)));
+        terminal.push_event(key(KeyCode::Enter));
+
+        let mut line_editor = line_editor(&terminal);
+        assert!(!line_editor.save_last_macro("z"));
+        line_editor.read_line(&prompt()).unwrap();
+
+        assert!(line_editor.save_last_macro("z"));
+        assert_eq!(
+            line_editor.macros().get("z"),
+            Some(&KeyboardMacro::new(vec![ReedlineEvent::Edit(vec![
+                EditCommand::InsertChar('z')
+            ])]))
+        );
+    }
+}
@@ -177,11 +177,12 @@
 //!
 //! ## Crate features
 //!
-//! - `clipboard`: Enable support to use the `SystemClipboard`. Enabling this feature will return a `SystemClipboard` instead of a local clipboard when calling `get_default_clipboard()`.
+//! - `clipboard`: Enable support to use the `SystemClipboard`. Enabling this feature will return a `SystemClipboard` instead of a local clipboard when calling `get_default_clipboard()`. It also provides the `Osc52Clipboard` to reach the clipboard of the terminal over SSH.
 //! - `bashisms`: Enable support for special text sequences that recall components from the history. e.g. `!!` and `!$`. For use in shells like `bash` or [`nushell`](https://nushell.sh).
 //! - `sqlite`: Provides the `SqliteBackedHistory` to store richer information in the history. Statically links the required sqlite version.
 //! - `sqlite-dynlib`: Alternative to the feature `sqlite`. Will not statically link. Requires `sqlite >= 3.38` to link dynamically!
-//! - `external_printer`: **Experimental:** Thread-safe `ExternalPrinter` handle to print lines from concurrently running threads.
+//! - `external_printer`: **Experimental:** Thread-safe `ExternalPrinter` handle to print lines from concurrently running threads and `EventInjector` handle to send `ReedlineEvent`s to the running line editor.
+//! - `async`: Provides `Reedline::read_line_async()` to read a line without blocking the thread of an async runtime.
 //!
 //! ## Are we prompt yet? (Development status)
 //!
@@ -226,11 +227,24 @@
 #![warn(missing_docs)]
 // #![deny(warnings)]
 mod core_editor;
+pub use core_editor::AutoPairs;
 pub use core_editor::Editor;
 pub use core_editor::LineBuffer;
+#[cfg(feature = "system_clipboard")]
+pub use core_editor::{Osc52Clipboard, Osc52Passthrough};
 
 mod enums;
-pub use enums::{EditCommand, ReedlineEvent, ReedlineRawEvent, Signal, UndoBehavior};
+pub use enums::{
+    EditCommand, ReedlineEvent, ReedlineRawEvent, Signal, TextObject, TextObjectScope,
+    TextObjectType, UndoBehavior,
+};
+
+mod backend;
+#[cfg(feature = "async")]
+pub use backend::InputEventStream;
+pub use backend::{
+    DefaultInputSource, DefaultOutputSink, InputSource, OutputSink, VirtualTerminal,
+};
 
 mod painting;
 pub use painting::{Painter, StyledText};
@@ -238,6 +252,17 @@ pub use painting::{Painter, StyledText};
 mod engine;
 pub use engine::Reedline;
 
+mod keyboard_macro;
+pub use keyboard_macro::KeyboardMacro;
+
+mod hooks;
+pub use hooks::{EditorHooks, PreSubmit};
+
+mod session_snapshot;
+pub use session_snapshot::SessionSnapshot;
+
+mod widgets;
+
 mod result;
 pub use result::{ReedlineError, ReedlineErrorVariants, Result};
 
@@ -253,13 +278,14 @@ pub use history::{
 mod prompt;
 pub use prompt::{
     DefaultPrompt, DefaultPromptSegment, Prompt, PromptEditMode, PromptHistorySearch,
-    PromptHistorySearchStatus, PromptViMode,
+    PromptHistorySearchStatus, PromptInvalidator, PromptViMode,
 };
 
 mod edit_mode;
 pub use edit_mode::{
     default_emacs_keybindings, default_vi_insert_keybindings, default_vi_normal_keybindings,
-    CursorConfig, EditMode, Emacs, Keybindings, Vi,
+    CursorConfig, EditMode, Emacs, Inputrc, InputrcEditingMode, InputrcIssue, KeyCombination,
+    Keybindings, Vi,
 };
 
 mod highlighter;
@@ -272,8 +298,11 @@ mod hinter;
 pub use hinter::CwdAwareHinter;
 pub use hinter::{DefaultHinter, Hinter};
 
+mod indenter;
+pub use indenter::{DefaultIndenter, Indenter};
+
 mod validator;
-pub use validator::{DefaultValidator, ValidationResult, Validator};
+pub use validator::{DefaultValidator, Diagnostic, Severity, ValidationResult, Validator};
 
 mod menu;
 pub use menu::{
@@ -287,6 +316,8 @@ pub use terminal_extensions::kitty_protocol_available;
 mod utils;
 
 mod external_printer;
+
+mod event_injector;
 pub use utils::{
     get_reedline_default_keybindings, get_reedline_edit_commands,
     get_reedline_keybinding_modifiers, get_reedline_keycodes, get_reedline_prompt_edit_modes,
@@ -300,3 +331,6 @@ pub use crossterm::{
 };
 #[cfg(feature = "external_printer")]
 pub use external_printer::ExternalPrinter;
+
+#[cfg(feature = "external_printer")]
+pub use event_injector::EventInjector;
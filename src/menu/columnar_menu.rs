@@ -222,6 +222,34 @@ impl ColumnarMenu {
         }
     }
 
+    /// Move menu cursor down by a page of rows, stopping at the last row
+    fn move_page_down(&mut self, page_rows: u16) {
+        let new_row = self
+            .row_pos
+            .saturating_add(page_rows)
+            .min(self.get_rows().saturating_sub(1));
+        let index = new_row * self.get_cols() + self.col_pos;
+        self.row_pos = if index >= self.values.len() as u16 {
+            new_row.saturating_sub(1)
+        } else {
+            new_row
+        }
+    }
+
+    /// Move menu cursor up by a page of rows, stopping at the first row
+    fn move_page_up(&mut self, page_rows: u16) {
+        self.row_pos = self.row_pos.saturating_sub(page_rows);
+    }
+
+    /// Number of rows that are not printed to keep the selected row visible
+    fn skipped_rows(&self, available_lines: u16) -> u16 {
+        if self.row_pos >= available_lines {
+            self.row_pos.saturating_sub(available_lines) + 1
+        } else {
+            0
+        }
+    }
+
     /// Menu index based on column and row position
     fn index(&self) -> usize {
         let index = self.row_pos * self.get_cols() + self.col_pos;
@@ -544,8 +572,12 @@ impl Menu for ColumnarMenu {
                 MenuEvent::MoveDown => self.move_down(),
                 MenuEvent::MoveLeft => self.move_left(),
                 MenuEvent::MoveRight => self.move_right(),
-                MenuEvent::PreviousPage | MenuEvent::NextPage => {
-                    // The columnar menu doest have the concept of pages, yet
+                // A page is made of the rows that fit in the lines available to the menu
+                MenuEvent::NextPage => {
+                    self.move_page_down(painter.menu_lines().unwrap_or(self.min_rows).max(1));
+                }
+                MenuEvent::PreviousPage => {
+                    self.move_page_up(painter.menu_lines().unwrap_or(self.min_rows).max(1));
                 }
             }
 
@@ -634,12 +666,7 @@ impl Menu for ColumnarMenu {
         } else {
             // The skip values represent the number of lines that should be skipped
             // while printing the menu
-            let skip_values = if self.row_pos >= available_lines {
-                let skip_lines = self.row_pos.saturating_sub(available_lines) + 1;
-                (skip_lines * self.get_cols()) as usize
-            } else {
-                0
-            };
+            let skip_values = (self.skipped_rows(available_lines) * self.get_cols()) as usize;
 
             // It seems that crossterm prefers to have a complete string ready to be printed
             // rather than looping through the values and printing multiple things
@@ -661,6 +688,42 @@ impl Menu for ColumnarMenu {
                 .collect()
         }
     }
+
+    /// Every column of the menu has the same width
+    fn select_at(
+        &mut self,
+        column: u16,
+        row: u16,
+        available_lines: u16,
+        _terminal_columns: u16,
+    ) -> bool {
+        let col = (column as usize / self.get_width().max(1)) as u16;
+        let row = row + self.skipped_rows(available_lines);
+        let index = (row * self.get_cols() + col) as usize;
+
+        if col < self.get_cols() && index < self.get_values().len() {
+            self.col_pos = col;
+            self.row_pos = row;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn selected_index(&self) -> Option<usize> {
+        (self.index() < self.get_values().len()).then(|| self.index())
+    }
+
+    fn select_index(&mut self, index: usize) -> bool {
+        if index < self.get_values().len() {
+            let cols = self.get_cols() as usize;
+            self.row_pos = (index / cols) as u16;
+            self.col_pos = (index % cols) as u16;
+            true
+        } else {
+            false
+        }
+    }
 }
 
 #[cfg(test)]
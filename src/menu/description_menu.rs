@@ -663,4 +663,19 @@ impl Menu for DescriptionMenu {
             )
         }
     }
+
+    fn selected_index(&self) -> Option<usize> {
+        (self.index() < self.get_values().len()).then(|| self.index())
+    }
+
+    fn select_index(&mut self, index: usize) -> bool {
+        if index < self.get_values().len() {
+            let cols = self.get_cols() as usize;
+            self.row_pos = (index / cols) as u16;
+            self.col_pos = (index % cols) as u16;
+            true
+        } else {
+            false
+        }
+    }
 }
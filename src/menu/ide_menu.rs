@@ -302,6 +302,44 @@ impl IdeMenu {
         }
     }
 
+    /// Move the selection down by a page of values, stopping at the last value
+    fn move_page_down(&mut self, page_values: u16) {
+        self.selected = self
+            .selected
+            .saturating_add(page_values)
+            .min(self.values.len().saturating_sub(1) as u16);
+    }
+
+    /// Move the selection up by a page of values, stopping at the first value
+    fn move_page_up(&mut self, page_values: u16) {
+        self.selected = self.selected.saturating_sub(page_values);
+    }
+
+    fn border_width(&self) -> u16 {
+        if self.default_details.border.is_some() {
+            2
+        } else {
+            0
+        }
+    }
+
+    /// Number of values that fit in the available lines next to the border
+    fn visible_values(&self, available_lines: u16) -> u16 {
+        available_lines
+            .min(self.default_details.max_completion_height)
+            .saturating_sub(self.border_width())
+    }
+
+    /// Number of values that are not printed to keep the selected value visible
+    fn skipped_values(&self, available_lines: u16) -> u16 {
+        let visible_values = self.visible_values(available_lines);
+        if self.selected >= visible_values {
+            self.selected.saturating_sub(visible_values) + 1
+        } else {
+            0
+        }
+    }
+
     fn index(&self) -> usize {
         self.selected as usize
     }
@@ -681,10 +719,16 @@ impl Menu for IdeMenu {
                 }
                 MenuEvent::NextElement | MenuEvent::MoveDown => self.move_next(),
                 MenuEvent::PreviousElement | MenuEvent::MoveUp => self.move_previous(),
-                MenuEvent::MoveLeft
-                | MenuEvent::MoveRight
-                | MenuEvent::PreviousPage
-                | MenuEvent::NextPage => {}
+                // A page is made of the values that fit in the lines available to the menu
+                MenuEvent::NextPage => self.move_page_down(
+                    self.visible_values(painter.menu_lines().unwrap_or(u16::MAX))
+                        .max(1),
+                ),
+                MenuEvent::PreviousPage => self.move_page_up(
+                    self.visible_values(painter.menu_lines().unwrap_or(u16::MAX))
+                        .max(1),
+                ),
+                MenuEvent::MoveLeft | MenuEvent::MoveRight => {}
             }
 
             self.longest_suggestion = self.get_values().iter().fold(0, |prev, suggestion| {
@@ -838,17 +882,9 @@ impl Menu for IdeMenu {
             let available_lines = available_lines.min(self.default_details.max_completion_height);
             // The skip values represent the number of lines that should be skipped
             // while printing the menu
-            let skip_values = if self.selected >= available_lines.saturating_sub(border_width) {
-                let skip_lines = self
-                    .selected
-                    .saturating_sub(available_lines.saturating_sub(border_width))
-                    + 1;
-                skip_lines as usize
-            } else {
-                0
-            };
+            let skip_values = self.skipped_values(available_lines) as usize;
 
-            let available_values = available_lines.saturating_sub(border_width) as usize;
+            let available_values = self.visible_values(available_lines) as usize;
 
             let max_padding = self.working_details.completion_width.saturating_sub(
                 self.longest_suggestion.min(u16::MAX as usize) as u16 + border_width,
@@ -989,6 +1025,53 @@ impl Menu for IdeMenu {
     fn set_cursor_pos(&mut self, pos: (u16, u16)) {
         self.working_details.cursor_col = pos.0;
     }
+
+    /// Only the suggestions can be selected, not the border or the description
+    fn select_at(
+        &mut self,
+        column: u16,
+        row: u16,
+        available_lines: u16,
+        _terminal_columns: u16,
+    ) -> bool {
+        let top_border = self.border_width() / 2;
+        let row = match row.checked_sub(top_border) {
+            Some(row) if row < self.visible_values(available_lines) => row,
+            _ => return false,
+        };
+
+        let completion_start = if self.working_details.description_is_right {
+            self.working_details.space_left
+        } else {
+            self.working_details.space_left
+                + self.working_details.description_width
+                + self.working_details.description_offset
+        };
+        let completion_end = completion_start + self.working_details.completion_width;
+
+        let index = row + self.skipped_values(available_lines);
+        if (completion_start..completion_end).contains(&column)
+            && (index as usize) < self.get_values().len()
+        {
+            self.selected = index;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn selected_index(&self) -> Option<usize> {
+        (self.index() < self.get_values().len()).then(|| self.index())
+    }
+
+    fn select_index(&mut self, index: usize) -> bool {
+        if index < self.get_values().len() {
+            self.selected = index as u16;
+            true
+        } else {
+            false
+        }
+    }
 }
 
 /// Split the input into strings that are at most `max_length` (in columns, not in chars) long
@@ -589,6 +589,52 @@ impl Menu for ListMenu {
     fn min_rows(&self) -> u16 {
         self.max_lines + 1
     }
+
+    /// Entries of the current page may span several lines, the banner can't be selected
+    fn select_at(
+        &mut self,
+        _column: u16,
+        row: u16,
+        _available_lines: u16,
+        terminal_columns: u16,
+    ) -> bool {
+        let values_before_page = self.pages.iter().take(self.page).sum::<Page>().size;
+        let page_size = match self.pages.get(self.page) {
+            Some(page) => page.size,
+            None => return false,
+        };
+
+        let mut entry_row = 0;
+        for (index, suggestion) in self.get_values().iter().take(page_size).enumerate() {
+            //  to account for the index and the indicator e.g. 0: XXXX
+            entry_row += self.number_of_lines(
+                &suggestion.value,
+                terminal_columns.saturating_sub(
+                    self.indicator().width() as u16 + count_digits(index + values_before_page),
+                ),
+            );
+
+            if row < entry_row {
+                self.row_position = index as u16;
+                return true;
+            }
+        }
+
+        false
+    }
+
+    fn selected_index(&self) -> Option<usize> {
+        (self.index() < self.get_values().len()).then(|| self.index())
+    }
+
+    fn select_index(&mut self, index: usize) -> bool {
+        if index < self.get_values().len() {
+            self.row_position = index as u16;
+            true
+        } else {
+            false
+        }
+    }
 }
 
 fn number_of_lines(entry: &str, max_lines: usize, terminal_columns: u16) -> u16 {
@@ -43,7 +43,7 @@ impl Default for MenuTextStyle {
 }
 
 /// Defines all possible events that could happen with a menu.
-#[derive(Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum MenuEvent {
     /// Activation event for the menu. When the bool is true it means that the values
     /// have already being updated. This is true when the option `quick_completions` is true
@@ -148,6 +148,35 @@ pub trait Menu: Send {
     fn set_cursor_pos(&mut self, _pos: (u16, u16)) {
         // empty implementation to make it optional
     }
+
+    /// Selects the entry painted at `column` and `row`, the row being relative to the first
+    /// line of the menu. `available_lines` is the value the menu string was created with.
+    ///
+    /// Returns true if an entry was selected
+    fn select_at(
+        &mut self,
+        _column: u16,
+        _row: u16,
+        _available_lines: u16,
+        _terminal_columns: u16,
+    ) -> bool {
+        // empty implementation to make it optional
+        false
+    }
+
+    /// Index in [`Menu::get_values()`] of the selected entry
+    fn selected_index(&self) -> Option<usize> {
+        // empty implementation to make it optional
+        None
+    }
+
+    /// Selects the entry at `index` in [`Menu::get_values()`]
+    ///
+    /// Returns true if the entry was selected
+    fn select_index(&mut self, _index: usize) -> bool {
+        // empty implementation to make it optional
+        false
+    }
 }
 
 pub struct MenuSettings {
@@ -469,4 +498,23 @@ impl Menu for ReedlineMenu {
     fn set_cursor_pos(&mut self, pos: (u16, u16)) {
         self.as_mut().set_cursor_pos(pos);
     }
+
+    fn select_at(
+        &mut self,
+        column: u16,
+        row: u16,
+        available_lines: u16,
+        terminal_columns: u16,
+    ) -> bool {
+        self.as_mut()
+            .select_at(column, row, available_lines, terminal_columns)
+    }
+
+    fn selected_index(&self) -> Option<usize> {
+        self.as_ref().selected_index()
+    }
+
+    fn select_index(&mut self, index: usize) -> bool {
+        self.as_mut().select_index(index)
+    }
 }
@@ -0,0 +1,99 @@
+use super::StyledText;
+use crate::{Diagnostic, Severity};
+use nu_ansi_term::{Color, Style};
+use std::fmt::Write;
+
+fn severity_style(severity: Severity) -> (&'static str, Style) {
+    match severity {
+        Severity::Error => ("error", Color::Red.bold()),
+        Severity::Warning => ("warning", Color::Yellow.bold()),
+        Severity::Info => ("info", Color::Blue.bold()),
+    }
+}
+
+/// Underlines the spans of the `diagnostics` in the highlighted `buffer`
+///
+/// Spans outside of the buffer or not on character boundaries are left out.
+pub(crate) fn underline_diagnostics(
+    styled_text: &mut StyledText,
+    buffer: &str,
+    diagnostics: &[Diagnostic],
+) {
+    for diagnostic in diagnostics {
+        let (start, end) = (diagnostic.span.start, diagnostic.span.end);
+        if end <= buffer.len() && buffer.is_char_boundary(start) && buffer.is_char_boundary(end) {
+            styled_text.update_style_range(start, end, |style| style.underline());
+        }
+    }
+}
+
+/// Lines painted below the buffer, one for every diagnostic, each starting with a newline
+pub(crate) fn render_diagnostics(diagnostics: &[Diagnostic], use_ansi_coloring: bool) -> String {
+    let mut lines = String::new();
+    for diagnostic in diagnostics {
+        let (label, style) = severity_style(diagnostic.severity);
+        let label = if use_ansi_coloring {
+            style.paint(label).to_string()
+        } else {
+            label.to_string()
+        };
+        // Messages spanning multiple lines would be mistaken for the buffer
+        let message = diagnostic.message.replace(['\r', '\n'], " ");
+        let _ = write!(lines, "\n{label}: {message}");
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Span;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn spans_are_underlined_keeping_the_highlighting() {
+        let green = Style::new().fg(Color::Green);
+        let mut styled_text = StyledText {
+            buffer: vec![
+                (green, "select".into()),
+                (Style::new(), " * from usrs".into()),
+            ],
+        };
+        let diagnostics = [
+            Diagnostic::new(Severity::Error, "unknown table", Span::new(14, 18)),
+            Diagnostic::new(Severity::Warning, "outside", Span::new(10, 30)),
+        ];
+
+        underline_diagnostics(&mut styled_text, "select * from usrs", &diagnostics);
+
+        assert_eq!(
+            styled_text.buffer,
+            vec![
+                (green, "select".into()),
+                (Style::new(), " * from ".into()),
+                (Style::new().underline(), "usrs".into()),
+            ]
+        );
+    }
+
+    #[test]
+    fn diagnostics_are_rendered_one_per_line() {
+        let diagnostics = [
+            Diagnostic::new(
+                Severity::Error,
+                "unknown table\nat column 14",
+                Span::new(14, 18),
+            ),
+            Diagnostic::new(Severity::Info, "tables: users", Span::new(0, 0)),
+        ];
+
+        assert_eq!(
+            render_diagnostics(&diagnostics, false),
+            "\nerror: unknown table at column 14\ninfo: tables: users"
+        );
+        assert_eq!(
+            render_diagnostics(&diagnostics[1..], true),
+            format!("\n{}: tables: users", Color::Blue.bold().paint("info"))
+        );
+    }
+}
@@ -0,0 +1,259 @@
+use super::{
+    utils::{coerce_crlf, strip_ansi},
+    StyledText,
+};
+use nu_ansi_term::Style;
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
+// Shown in the gutter instead of a line number on the rows continuing a wrapped line
+const WRAP_MARKER: &str = "↪";
+
+/// Collects the painted text on either side of the cursor, merging neighbours of the same style
+struct Output {
+    before_cursor: String,
+    after_cursor: String,
+    past_cursor: bool,
+    style: Style,
+    text: String,
+}
+
+impl Output {
+    fn push(&mut self, style: Style, text: &str) {
+        if style != self.style {
+            self.flush();
+            self.style = style;
+        }
+        self.text.push_str(text);
+    }
+
+    fn flush(&mut self) {
+        if !self.text.is_empty() {
+            let painted = self.style.paint(&self.text).to_string();
+            if self.past_cursor {
+                self.after_cursor.push_str(&painted);
+            } else {
+                self.before_cursor.push_str(&painted);
+            }
+            self.text.clear();
+        }
+    }
+
+    fn pass_cursor(&mut self) {
+        self.flush();
+        self.past_cursor = true;
+    }
+}
+
+/// Paints the buffer with a gutter of line numbers in front of every line
+///
+/// The lines are wrapped before they reach the edge of the terminal, the rows continuing a
+/// line show a marker instead of a number. The layout is kept between repaints to map clicks
+/// back to the buffer.
+#[derive(Debug, Default)]
+pub(crate) struct LineNumberGutter {
+    // Row below the prompt, column, width and buffer offset of every painted grapheme
+    visible: Vec<(usize, usize, usize, usize)>,
+    // Buffer offset of the end of every painted row
+    row_ends: Vec<usize>,
+}
+
+impl LineNumberGutter {
+    /// Renders `styled_text` split at `insertion_point` with the gutter in front of its lines
+    ///
+    /// The first line continues the prompt, which is `prompt_width` columns wide on its last
+    /// row. The other rows are indented by the same width to keep the gutter in one column. The
+    /// line of the cursor is numbered with `current_line_style`.
+    pub(crate) fn render(
+        &mut self,
+        styled_text: &StyledText,
+        insertion_point: usize,
+        prompt_width: usize,
+        terminal_columns: usize,
+        (gutter_style, current_line_style): (Style, Style),
+        use_ansi_coloring: bool,
+    ) -> (String, String) {
+        let buffer = styled_text.raw_string();
+        let digits = (buffer.matches('\n').count() + 1).to_string().len();
+        let current_line = buffer[..insertion_point.min(buffer.len())]
+            .matches('\n')
+            .count();
+        // The last column stays empty to keep the terminal from wrapping the row
+        let columns = terminal_columns.saturating_sub(1);
+        let text_start = prompt_width + digits + 1;
+        let indentation = " ".repeat(prompt_width);
+
+        let mut output = Output {
+            before_cursor: String::new(),
+            after_cursor: String::new(),
+            past_cursor: false,
+            style: Style::new(),
+            text: String::new(),
+        };
+        let number_style = |line: usize| {
+            if line == current_line {
+                current_line_style
+            } else {
+                gutter_style
+            }
+        };
+
+        self.visible.clear();
+        self.row_ends.clear();
+        let (mut row, mut line, mut column) = (0, 0, text_start);
+        output.push(number_style(line), &format!("{:>digits$} ", line + 1));
+
+        let mut offset = 0;
+        for (style, text) in &styled_text.buffer {
+            for (index, grapheme) in text.grapheme_indices(true) {
+                let grapheme_offset = offset + index;
+                let reaches_cursor = !output.past_cursor && grapheme_offset >= insertion_point;
+                if grapheme == "\n" || grapheme == "\r\n" {
+                    if reaches_cursor {
+                        output.pass_cursor();
+                    }
+                    self.row_ends.push(grapheme_offset);
+                    (row, line, column) = (row + 1, line + 1, text_start);
+                    output.push(Style::new(), "\n");
+                    output.push(Style::new(), &indentation);
+                    output.push(number_style(line), &format!("{:>digits$} ", line + 1));
+                } else {
+                    let width = grapheme.width();
+                    // Every row keeps at least one grapheme, even on the narrowest terminal
+                    if column + width > columns && column > text_start {
+                        self.row_ends.push(grapheme_offset);
+                        (row, column) = (row + 1, text_start);
+                        output.push(Style::new(), "\n");
+                        output.push(Style::new(), &indentation);
+                        output.push(gutter_style, &format!("{WRAP_MARKER:>digits$} "));
+                    }
+                    if reaches_cursor {
+                        output.pass_cursor();
+                    }
+                    self.visible.push((row, column, width, grapheme_offset));
+                    output.push(*style, grapheme);
+                    column += width;
+                }
+            }
+            offset += text.len();
+        }
+        self.row_ends.push(buffer.len());
+        output.flush();
+
+        let (before_cursor, after_cursor) = if use_ansi_coloring {
+            (output.before_cursor, output.after_cursor)
+        } else {
+            (
+                strip_ansi(&output.before_cursor),
+                strip_ansi(&output.after_cursor),
+            )
+        };
+        (
+            coerce_crlf(&before_cursor).into_owned(),
+            coerce_crlf(&after_cursor).into_owned(),
+        )
+    }
+
+    /// Offset in the buffer of the grapheme painted at `column` of the `row` below the last
+    /// row of the prompt
+    ///
+    /// Columns of the gutter map to the start of the row, columns past the painted graphemes
+    /// to its end. Returns `None` for rows below the buffer.
+    pub(crate) fn buffer_offset_at(&self, column: usize, row: usize) -> Option<usize> {
+        let row_end = *self.row_ends.get(row)?;
+        Some(
+            self.visible
+                .iter()
+                .filter(|(visible_row, ..)| *visible_row == row)
+                .find(|(_, start, width, _)| column < start + width)
+                .map_or(row_end, |(.., offset)| *offset),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nu_ansi_term::Color;
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+
+    fn plain(text: &str) -> StyledText {
+        StyledText {
+            buffer: vec![(Style::new(), text.to_string())],
+        }
+    }
+
+    fn render(gutter: &mut LineNumberGutter, text: &str, insertion_point: usize) -> String {
+        let (before_cursor, after_cursor) = gutter.render(
+            &plain(text),
+            insertion_point,
+            2,
+            12,
+            (Style::new(), Style::new()),
+            false,
+        );
+        format!("{before_cursor}|{after_cursor}")
+    }
+
+    #[rstest]
+    #[case("ls", 2, "1 ls|")]
+    #[case("ab\ncd", 1, "1 a|b\r\n  2 cd")]
+    #[case("ab\ncd", 2, "1 ab|\r\n  2 cd")]
+    #[case("abcdefghij", 10, "1 abcdefg\r\n  ↪ hij|")]
+    #[case("abcdefghij", 7, "1 abcdefg\r\n  ↪ |hij")]
+    #[case("a\nb\nc\nd\ne\nf\ng\nh\ni\nj", 0, " 1 |a\r\n   2 b\r\n   3 c\r\n   4 d\r\n   5 e\r\n   6 f\r\n   7 g\r\n   8 h\r\n   9 i\r\n  10 j")]
+    fn lines_are_numbered_and_wrapped(
+        #[case] text: &str,
+        #[case] insertion_point: usize,
+        #[case] expected: &str,
+    ) {
+        let mut gutter = LineNumberGutter::default();
+
+        assert_eq!(render(&mut gutter, text, insertion_point), expected);
+    }
+
+    #[test]
+    fn current_line_is_highlighted() {
+        let mut gutter = LineNumberGutter::default();
+        let (gutter_style, current_line_style) = (Color::DarkGray.normal(), Color::White.bold());
+
+        let (before_cursor, after_cursor) = gutter.render(
+            &plain("a\nb"),
+            3,
+            0,
+            20,
+            (gutter_style, current_line_style),
+            true,
+        );
+
+        assert_eq!(
+            format!("{before_cursor}|{after_cursor}"),
+            format!(
+                "{}a\r\n{}b|",
+                gutter_style.paint("1 "),
+                current_line_style.paint("2 ")
+            )
+        );
+    }
+
+    #[rstest]
+    #[case(0, 0, Some(0))]
+    #[case(5, 0, Some(1))]
+    #[case(11, 0, Some(7))]
+    #[case(0, 1, Some(7))]
+    #[case(6, 1, Some(9))]
+    #[case(9, 1, Some(10))]
+    #[case(2, 2, Some(11))]
+    #[case(0, 3, None)]
+    fn clicks_map_to_the_painted_graphemes(
+        #[case] column: usize,
+        #[case] row: usize,
+        #[case] expected: Option<usize>,
+    ) {
+        let mut gutter = LineNumberGutter::default();
+        render(&mut gutter, "abcdefghij\nk", 0);
+
+        assert_eq!(gutter.buffer_offset_at(column, row), expected);
+    }
+}
@@ -0,0 +1,339 @@
+use super::{utils::line_width, StyledText};
+use nu_ansi_term::Style;
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
+// Shown at the edges of the row when the buffer continues beyond them
+const LEFT_MARKER: &str = "<";
+const RIGHT_MARKER: &str = ">";
+// Shown in place of the newlines of the buffer
+const NEWLINE_MARKER: &str = "↵";
+
+/// Grapheme of the buffer placed on the row
+struct Cell<'text> {
+    style: Style,
+    text: &'text str,
+    width: usize,
+    // Column of the buffer the grapheme starts at
+    column: usize,
+    // Offset of the grapheme in the buffer
+    offset: usize,
+}
+
+fn cells(styled_text: &StyledText) -> Vec<Cell<'_>> {
+    let mut cells = Vec::new();
+    let mut column = 0;
+    let mut offset = 0;
+    for (style, text) in &styled_text.buffer {
+        for (index, grapheme) in text.grapheme_indices(true) {
+            let text = match grapheme {
+                "\n" | "\r\n" => NEWLINE_MARKER,
+                grapheme => grapheme,
+            };
+            let width = text.width();
+            cells.push(Cell {
+                style: *style,
+                text,
+                width,
+                column,
+                offset: offset + index,
+            });
+            column += width;
+        }
+        offset += text.len();
+    }
+    cells
+}
+
+/// Paints the cells, merging neighbours of the same style
+fn paint(cells: &[Cell], output: &mut String) {
+    let mut cells = cells.iter().peekable();
+    while let Some(cell) = cells.next() {
+        let mut text = cell.text.to_string();
+        while let Some(next) = cells.next_if(|next| next.style == cell.style) {
+            text.push_str(next.text);
+        }
+        output.push_str(&cell.style.paint(text).to_string());
+    }
+}
+
+/// Cuts the ANSI styled `text` after `columns`, keeping its escape sequences intact
+fn truncate_styled(text: &str, columns: usize) -> String {
+    let mut output = String::new();
+    let mut width = 0;
+    let mut styled = false;
+    let mut rest = text;
+    while !rest.is_empty() {
+        if rest.starts_with("\x1b[") {
+            let end = rest[2..]
+                .find(|c: char| ('@'..='~').contains(&c))
+                .map_or(rest.len(), |end| end + 3);
+            output.push_str(&rest[..end]);
+            styled = true;
+            rest = &rest[end..];
+        } else {
+            let end = rest
+                .find('\x1b')
+                .filter(|end| *end > 0)
+                .unwrap_or(rest.len());
+            for grapheme in rest[..end].graphemes(true) {
+                width += grapheme.width();
+                if width > columns {
+                    if styled {
+                        output.push_str("\x1b[0m");
+                    }
+                    return output;
+                }
+                output.push_str(grapheme);
+            }
+            rest = &rest[end..];
+        }
+    }
+    output
+}
+
+/// Keeps the buffer on a single row by scrolling it horizontally to follow the cursor
+///
+/// The scroll position is kept between repaints, so the row only moves once the cursor
+/// reaches one of its edges.
+#[derive(Debug, Default)]
+pub(crate) struct HorizontalScroll {
+    // Column of the buffer shown first
+    first_column: usize,
+    // Column on the row, width and buffer offset of every painted grapheme
+    visible: Vec<(usize, usize, usize)>,
+    // Buffer offset after the last painted grapheme
+    visible_end: usize,
+}
+
+impl HorizontalScroll {
+    /// Renders the part of `styled_text` that fits on the row around `insertion_point`
+    ///
+    /// `columns` are the columns left on the row after the prompt. Returns the text before and
+    /// after the cursor, including the continuation markers painted with `marker_style`, and
+    /// the part of the `hint` that still fits on the row.
+    pub(crate) fn render(
+        &mut self,
+        styled_text: &StyledText,
+        insertion_point: usize,
+        hint: &str,
+        columns: usize,
+        marker_style: Style,
+        use_ansi_coloring: bool,
+    ) -> (String, String, String) {
+        // The last column stays empty to keep the terminal from wrapping the row
+        let columns = columns.saturating_sub(1).max(3);
+        let cells = cells(styled_text);
+        let total_width: usize = cells.iter().map(|cell| cell.width).sum();
+        let cursor = cells
+            .iter()
+            .position(|cell| cell.offset >= insertion_point)
+            .unwrap_or(cells.len());
+        let (cursor_column, cursor_width) = cells
+            .get(cursor)
+            .map_or((total_width, 1), |cell| (cell.column, cell.width.max(1)));
+
+        // The end of the buffer needs room for the cursor as well
+        if total_width < columns {
+            self.first_column = 0;
+        } else {
+            self.first_column = self.first_column.min(cursor_column);
+            let left_marker = usize::from(self.first_column > 0);
+            if cursor_column + cursor_width > self.first_column + columns - left_marker - 1 {
+                self.first_column = cursor_column + cursor_width + 2 - columns;
+            }
+            // Don't leave empty columns behind the end of the buffer
+            let left_marker = usize::from(self.first_column > 0);
+            if total_width + 1 < self.first_column + columns - left_marker {
+                self.first_column = total_width + 2 - columns;
+            }
+        }
+
+        // Wide graphemes cut by the left edge are left out
+        let first = cells
+            .iter()
+            .position(|cell| cell.column >= self.first_column)
+            .unwrap_or(cells.len());
+        self.first_column = cells.get(first).map_or(total_width, |cell| cell.column);
+
+        let left_marker = self.first_column > 0;
+        let mut available = columns - usize::from(left_marker);
+        let right_marker = total_width + 1 - self.first_column > available;
+        if right_marker {
+            available -= 1;
+        }
+        let mut used = 0;
+        let last = cells[first..]
+            .iter()
+            .position(|cell| {
+                used += cell.width;
+                used > available
+            })
+            .map_or(cells.len(), |count| first + count);
+        let visible = &cells[first..last];
+        let used: usize = visible.iter().map(|cell| cell.width).sum();
+
+        let start = usize::from(left_marker);
+        self.visible = visible
+            .iter()
+            .map(|cell| {
+                (
+                    start + cell.column - self.first_column,
+                    cell.width,
+                    cell.offset,
+                )
+            })
+            .collect();
+        self.visible_end = cells
+            .get(last)
+            .map_or(styled_text.raw_string().len(), |cell| cell.offset);
+
+        let split = cursor.clamp(first, last) - first;
+        let mut before_cursor = String::new();
+        if left_marker {
+            before_cursor.push_str(&marker_style.paint(LEFT_MARKER).to_string());
+        }
+        paint(&visible[..split], &mut before_cursor);
+
+        let mut after_cursor = String::new();
+        paint(&visible[split..], &mut after_cursor);
+        let hint = if right_marker {
+            // Fill the columns left by a wide grapheme that didn't fit
+            after_cursor.push_str(&" ".repeat(available - used));
+            after_cursor.push_str(&marker_style.paint(RIGHT_MARKER).to_string());
+            String::new()
+        } else {
+            let hint = hint.split(['\r', '\n']).next().unwrap_or_default();
+            truncate_styled(hint, available - used)
+        };
+
+        if use_ansi_coloring {
+            (before_cursor, after_cursor, hint)
+        } else {
+            (
+                super::utils::strip_ansi(&before_cursor),
+                super::utils::strip_ansi(&after_cursor),
+                hint,
+            )
+        }
+    }
+
+    /// Offset in the buffer of the grapheme painted `column` columns after the prompt
+    ///
+    /// Columns past the painted graphemes map to the end of the painted part.
+    pub(crate) fn buffer_offset_at(&self, column: usize) -> usize {
+        self.visible
+            .iter()
+            .find(|(start, width, _)| column < start + width)
+            .map_or(self.visible_end, |(_, _, offset)| *offset)
+    }
+}
+
+/// Columns left on the row after the last line of the prompt
+pub(crate) fn columns_after_prompt(prompt: &str, terminal_columns: u16) -> usize {
+    let prompt_width = line_width(prompt.lines().last().unwrap_or_default());
+    (terminal_columns as usize).saturating_sub(prompt_width % terminal_columns.max(1) as usize)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nu_ansi_term::Color;
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+
+    fn plain(text: &str) -> StyledText {
+        StyledText {
+            buffer: vec![(Style::new(), text.to_string())],
+        }
+    }
+
+    fn render(scroll: &mut HorizontalScroll, text: &str, insertion_point: usize) -> String {
+        let (before_cursor, after_cursor, hint) =
+            scroll.render(&plain(text), insertion_point, "", 11, Style::new(), false);
+        format!("{before_cursor}|{after_cursor}{hint}")
+    }
+
+    #[rstest]
+    #[case("abc", 1, "a|bc")]
+    #[case("abcdefghi", 9, "abcdefghi|")]
+    #[case("abcdefghij", 10, "<cdefghij|")]
+    #[case("abcdefghijklmnop", 0, "|abcdefghi>")]
+    #[case("abcdefghijklmnop", 16, "<ijklmnop|")]
+    #[case("ab\ncd", 3, "ab↵|cd")]
+    fn rendering_around_the_cursor(
+        #[case] text: &str,
+        #[case] insertion_point: usize,
+        #[case] expected: &str,
+    ) {
+        let mut scroll = HorizontalScroll::default();
+
+        assert_eq!(render(&mut scroll, text, insertion_point), expected);
+    }
+
+    #[test]
+    fn scrolling_only_happens_at_the_edges() {
+        let text = "abcdefghijklmnop";
+        let mut scroll = HorizontalScroll::default();
+
+        assert_eq!(render(&mut scroll, text, 16), "<ijklmnop|");
+        assert_eq!(render(&mut scroll, text, 9), "<i|jklmnop");
+        assert_eq!(render(&mut scroll, text, 8), "<|ijklmnop");
+        assert_eq!(render(&mut scroll, text, 7), "<|hijklmno>");
+        assert_eq!(render(&mut scroll, text, 2), "<|cdefghij>");
+        assert_eq!(render(&mut scroll, text, 0), "|abcdefghi>");
+    }
+
+    #[test]
+    fn wide_graphemes_are_never_cut() {
+        let text = "一二三四五六七";
+        let mut scroll = HorizontalScroll::default();
+
+        assert_eq!(render(&mut scroll, text, 0), "|一二三四 >");
+        assert_eq!(render(&mut scroll, text, text.len()), "<四五六七|");
+        assert_eq!(render(&mut scroll, text, 9), "<|四五六七");
+    }
+
+    #[test]
+    fn hint_is_cut_at_the_edge() {
+        let mut scroll = HorizontalScroll::default();
+        let hint = Style::new()
+            .fg(Color::DarkGray)
+            .paint("defghijkl\nmn")
+            .to_string();
+
+        let (before_cursor, after_cursor, hint) =
+            scroll.render(&plain("abc"), 3, &hint, 11, Style::new(), true);
+
+        assert_eq!(before_cursor, "abc");
+        assert_eq!(after_cursor, "");
+        assert_eq!(hint, "\x1b[90mdefghij\x1b[0m");
+    }
+
+    #[test]
+    fn styles_are_kept_in_the_window() {
+        let mut scroll = HorizontalScroll::default();
+        let selected = Style::new().reverse();
+        let mut styled_text = plain("abcdefghijklmnop");
+        styled_text.style_range(9, 11, selected);
+
+        let (before_cursor, after_cursor, _) =
+            scroll.render(&styled_text, 16, "", 11, Style::new(), true);
+
+        assert_eq!(before_cursor, "<i\x1b[7mjk\x1b[0mlmnop");
+        assert_eq!(after_cursor, "");
+    }
+
+    #[rstest]
+    #[case(0, 8)]
+    #[case(1, 8)]
+    #[case(3, 10)]
+    #[case(8, 15)]
+    #[case(9, 16)]
+    fn clicks_map_to_the_painted_graphemes(#[case] column: usize, #[case] expected: usize) {
+        let mut scroll = HorizontalScroll::default();
+        render(&mut scroll, "abcdefghijklmnop", 16);
+
+        assert_eq!(scroll.buffer_offset_at(column), expected);
+    }
+}
@@ -1,8 +1,14 @@
+mod diagnostics;
+mod gutter;
+mod horizontal_scroll;
 mod painter;
 mod prompt_lines;
 mod styled_text;
 mod utils;
 
+pub(crate) use diagnostics::{render_diagnostics, underline_diagnostics};
+pub(crate) use gutter::LineNumberGutter;
+pub(crate) use horizontal_scroll::{columns_after_prompt, HorizontalScroll};
 pub use painter::{Painter, PainterSuspendedState};
 pub(crate) use prompt_lines::PromptLines;
 pub use styled_text::StyledText;
@@ -1,16 +1,16 @@
 use crate::{CursorConfig, PromptEditMode, PromptViMode};
 
 use {
-    super::utils::{coerce_crlf, line_width},
+    super::utils::{coerce_crlf, line_width, strip_ansi},
     crate::{
         menu::{Menu, ReedlineMenu},
         painting::PromptLines,
-        Prompt,
+        OutputSink, Prompt,
     },
     crossterm::{
         cursor::{self, MoveTo, RestorePosition, SavePosition},
         style::{Attribute, Print, ResetColor, SetAttribute, SetForegroundColor},
-        terminal::{self, Clear, ClearType},
+        terminal::{Clear, ClearType},
         QueueableCommand,
     },
     std::io::{Result, Write},
@@ -19,6 +19,11 @@ use {
 #[cfg(feature = "external_printer")]
 use {crate::LineBuffer, crossterm::cursor::MoveUp};
 
+// Semantic prompt marks (FinalTerm / OSC 133) used by shell integration aware terminals
+const PROMPT_START_MARK: &str = "\x1b]133;A\x1b\\";
+const INPUT_START_MARK: &str = "\x1b]133;B\x1b\\";
+const COMMAND_EXECUTED_MARK: &str = "\x1b]133;C\x1b\\";
+
 // Returns a string that skips N number of lines with the next offset of lines
 // An offset of 0 would return only one line after skipping the required lines
 fn skip_buffer_lines(string: &str, skip: usize, offset: Option<usize>) -> &str {
@@ -47,9 +52,6 @@ fn skip_buffer_lines(string: &str, skip: usize, offset: Option<usize>) -> &str {
     string[index..limit].trim_end_matches('\n')
 }
 
-/// the type used by crossterm operations
-pub type W = std::io::BufWriter<std::io::Stderr>;
-
 #[derive(Debug, PartialEq, Eq)]
 pub struct PainterSuspendedState {
     previous_prompt_rows_range: RangeInclusive<u16>,
@@ -89,17 +91,20 @@ fn select_prompt_row(
 /// Implementation of the output to the terminal
 pub struct Painter {
     // Stdout
-    stdout: W,
+    stdout: Box<dyn OutputSink>,
     prompt_start_row: u16,
     terminal_size: (u16, u16),
     last_required_lines: u16,
     large_buffer: bool,
     just_resized: bool,
     after_cursor_lines: Option<String>,
+    // Starting row and number of lines available to the last painted menu
+    menu_area: Option<(u16, u16)>,
+    semantic_prompt_marks: bool,
 }
 
 impl Painter {
-    pub(crate) fn new(stdout: W) -> Self {
+    pub(crate) fn new(stdout: Box<dyn OutputSink>) -> Self {
         Painter {
             stdout,
             prompt_start_row: 0,
@@ -108,7 +113,46 @@ impl Painter {
             large_buffer: false,
             just_resized: false,
             after_cursor_lines: None,
+            menu_area: None,
+            semantic_prompt_marks: false,
+        }
+    }
+
+    /// Replaces the output everything is painted to
+    pub(crate) fn set_output_sink(&mut self, stdout: Box<dyn OutputSink>) {
+        self.stdout = stdout;
+    }
+
+    /// The output everything is painted to, for escape sequences sent outside of painting
+    pub(crate) fn output_sink(&mut self) -> &mut dyn OutputSink {
+        self.stdout.as_mut()
+    }
+
+    /// Toggles the semantic prompt marks around the prompt and the buffer
+    pub(crate) fn set_semantic_prompt_marks(&mut self, enable: bool) {
+        self.semantic_prompt_marks = enable;
+    }
+
+    /// Marks the start of the output of the command that was just entered
+    pub(crate) fn mark_command_executed(&mut self) -> Result<()> {
+        if self.semantic_prompt_marks {
+            self.stdout.queue(Print(COMMAND_EXECUTED_MARK))?;
+            self.stdout.flush()?;
         }
+        Ok(())
+    }
+
+    /// Marks the end of the output of the last command together with its exit code
+    pub(crate) fn mark_command_finished(&mut self, exit_code: Option<i32>) -> Result<()> {
+        if self.semantic_prompt_marks {
+            let mark = match exit_code {
+                Some(exit_code) => format!("\x1b]133;D;{exit_code}\x1b\\"),
+                None => "\x1b]133;D\x1b\\".to_string(),
+            };
+            self.stdout.queue(Print(mark))?;
+            self.stdout.flush()?;
+        }
+        Ok(())
     }
 
     /// Height of the current terminal window
@@ -126,6 +170,35 @@ impl Painter {
         self.screen_height().saturating_sub(self.prompt_start_row)
     }
 
+    /// Converts a row of the screen to a row relative to the start of the prompt
+    ///
+    /// Returns `None` for rows above the prompt or when the buffer is too large to be
+    /// painted completely
+    pub(crate) fn prompt_relative_row(&self, row: u16) -> Option<u16> {
+        if self.large_buffer {
+            None
+        } else {
+            row.checked_sub(self.prompt_start_row)
+        }
+    }
+
+    /// Converts a row of the screen to a row relative to the start of the painted menu
+    ///
+    /// Returns the relative row together with the lines that were available to the menu,
+    /// or `None` if the row isn't covered by the menu
+    pub(crate) fn menu_relative_row(&self, row: u16) -> Option<(u16, u16)> {
+        self.menu_area.and_then(|(starting_row, available_lines)| {
+            row.checked_sub(starting_row)
+                .filter(|menu_row| *menu_row < available_lines)
+                .map(|menu_row| (menu_row, available_lines))
+        })
+    }
+
+    /// Lines that were available to the last painted menu
+    pub(crate) fn menu_lines(&self) -> Option<u16> {
+        self.menu_area.map(|(_, available_lines)| available_lines)
+    }
+
     /// Returns the state necessary before suspending the painter (to run a host command event).
     ///
     /// This state will be used to re-initialize the painter to re-use last prompt if possible.
@@ -148,7 +221,7 @@ impl Painter {
     ) -> Result<()> {
         // Update the terminal size
         self.terminal_size = {
-            let size = terminal::size()?;
+            let size = self.stdout.size()?;
             // if reported size is 0, 0 -
             // use a default size to avoid divide by 0 panics
             if size == (0, 0) {
@@ -157,7 +230,7 @@ impl Painter {
                 size
             }
         };
-        let prompt_selector = select_prompt_row(suspended_state, cursor::position()?);
+        let prompt_selector = select_prompt_row(suspended_state, self.stdout.cursor_position()?);
         self.prompt_start_row = match prompt_selector {
             PromptRowSelector::UseExistingPrompt { start_row } => start_row,
             PromptRowSelector::MakeNewPrompt { new_row } => {
@@ -195,6 +268,7 @@ impl Painter {
         cursor_config: &Option<CursorConfig>,
     ) -> Result<()> {
         self.stdout.queue(cursor::Hide)?;
+        self.menu_area = None;
 
         let screen_width = self.screen_width();
         let screen_height = self.screen_height();
@@ -216,7 +290,7 @@ impl Painter {
         self.large_buffer = required_lines >= screen_height;
 
         // This might not be terribly performant. Testing it out
-        let is_reset = || match cursor::position() {
+        let mut is_reset = || match self.stdout.cursor_position() {
             // when output something without newline, the cursor position is at current line.
             // but the prompt_start_row is next line.
             // in this case we don't want to reset, need to `add 1` to handle for such case.
@@ -261,6 +335,9 @@ impl Painter {
                 PromptEditMode::Emacs => shapes.emacs,
                 PromptEditMode::Vi(PromptViMode::Insert) => shapes.vi_insert,
                 PromptEditMode::Vi(PromptViMode::Normal) => shapes.vi_normal,
+                PromptEditMode::Vi(PromptViMode::VisualLine) => {
+                    shapes.vi_visual_line.or(shapes.vi_normal)
+                }
                 _ => None,
             };
             if let Some(shape) = shape {
@@ -296,6 +373,13 @@ impl Painter {
         Ok(())
     }
 
+    fn queue_mark(&mut self, mark: &str) -> Result<()> {
+        if self.semantic_prompt_marks {
+            self.stdout.queue(Print(mark))?;
+        }
+        Ok(())
+    }
+
     fn print_menu(
         &mut self,
         menu: &dyn Menu,
@@ -305,20 +389,31 @@ impl Painter {
         let screen_width = self.screen_width();
         let screen_height = self.screen_height();
         let cursor_distance = lines.distance_from_prompt(screen_width);
+        let diagnostics_rows = lines.diagnostics_rows(screen_width);
 
         // If there is not enough space to print the menu, then the starting
         // drawing point for the menu will overwrite the last rows in the buffer
-        let starting_row = if cursor_distance >= screen_height.saturating_sub(1) {
-            screen_height.saturating_sub(menu.min_rows())
+        let starting_row = if cursor_distance + diagnostics_rows >= screen_height.saturating_sub(1)
+        {
+            screen_height.saturating_sub(menu.min_rows() + diagnostics_rows)
         } else {
             self.prompt_start_row + cursor_distance + 1
         };
 
-        let remaining_lines = screen_height.saturating_sub(starting_row);
-        let menu_string = menu.menu_string(remaining_lines, use_ansi_coloring);
+        // The diagnostics stay between the buffer and the menu
         self.stdout
             .queue(cursor::MoveTo(0, starting_row))?
             .queue(Clear(ClearType::FromCursorDown))?
+            .queue(Print(&coerce_crlf(
+                lines.diagnostics.trim_start_matches('\n'),
+            )))?;
+
+        let menu_row = (starting_row + diagnostics_rows).min(screen_height);
+        let remaining_lines = screen_height.saturating_sub(menu_row);
+        self.menu_area = Some((menu_row, remaining_lines));
+        let menu_string = menu.menu_string(remaining_lines, use_ansi_coloring);
+        self.stdout
+            .queue(cursor::MoveTo(0, menu_row))?
             .queue(Print(menu_string.trim_end_matches('\n')))?;
 
         Ok(())
@@ -331,6 +426,8 @@ impl Painter {
         menu: Option<&ReedlineMenu>,
         use_ansi_coloring: bool,
     ) -> Result<()> {
+        self.queue_mark(PROMPT_START_MARK)?;
+
         // print our prompt with color
         if use_ansi_coloring {
             self.stdout
@@ -361,6 +458,7 @@ impl Painter {
                 .queue(ResetColor)?;
         }
 
+        self.queue_mark(INPUT_START_MARK)?;
         self.stdout
             .queue(Print(&lines.before_cursor))?
             .queue(SavePosition)?
@@ -369,7 +467,9 @@ impl Painter {
         if let Some(menu) = menu {
             self.print_menu(menu, lines, use_ansi_coloring)?;
         } else {
-            self.stdout.queue(Print(&lines.hint))?;
+            self.stdout
+                .queue(Print(&lines.hint))?
+                .queue(Print(&coerce_crlf(&lines.diagnostics)))?;
         }
 
         Ok(())
@@ -399,6 +499,8 @@ impl Painter {
         // Extra rows represent how many rows are "above" the visible area in the terminal
         let extra_rows = (total_lines_before).saturating_sub(screen_height as usize);
 
+        self.queue_mark(PROMPT_START_MARK)?;
+
         // print our prompt with color
         if use_ansi_coloring {
             self.stdout
@@ -436,14 +538,15 @@ impl Painter {
         // The minimum number of lines from the menu are removed from the buffer if there is no more
         // space to print the menu. This will only happen if the cursor is at the last line and
         // it is a large buffer
+        let diagnostics_rows = lines.diagnostics_rows(screen_width);
         let offset = menu.and_then(|menu| {
-            if cursor_distance >= screen_height.saturating_sub(1) {
+            if cursor_distance + diagnostics_rows >= screen_height.saturating_sub(1) {
                 let rows = lines
                     .before_cursor
                     .lines()
                     .count()
                     .saturating_sub(extra_rows)
-                    .saturating_sub(menu.min_rows() as usize);
+                    .saturating_sub((menu.min_rows() + diagnostics_rows) as usize);
                 Some(rows)
             } else {
                 None
@@ -452,6 +555,7 @@ impl Painter {
 
         // Selecting the lines before the cursor that will be printed
         let before_cursor_skipped = skip_buffer_lines(&lines.before_cursor, extra_rows, offset);
+        self.queue_mark(INPUT_START_MARK)?;
         self.stdout.queue(Print(before_cursor_skipped))?;
         self.stdout.queue(SavePosition)?;
 
@@ -475,7 +579,8 @@ impl Painter {
             let after_cursor_skipped = skip_buffer_lines(&lines.after_cursor, 0, Some(offset));
             self.stdout.queue(Print(after_cursor_skipped))?;
             // Hint lines
-            let hint_skipped = skip_buffer_lines(&lines.hint, 0, Some(offset));
+            let below_cursor = lines.hint.to_string() + &coerce_crlf(&lines.diagnostics);
+            let hint_skipped = skip_buffer_lines(&below_cursor, 0, Some(offset));
             self.stdout.queue(Print(hint_skipped))?;
         }
 
@@ -497,7 +602,7 @@ impl Painter {
         //
         // I assume this is a bug with the position() call but haven't figured that
         // out yet.
-        if let Ok(position) = cursor::position() {
+        if let Ok(position) = self.stdout.cursor_position() {
             self.prompt_start_row = position.1;
             self.just_resized = true;
         }
@@ -510,6 +615,15 @@ impl Painter {
         self.stdout.flush()
     }
 
+    /// Writes `text` without its ANSI styling and without moving to the next line
+    ///
+    /// Used to show the prompt when reading input without a terminal
+    pub(crate) fn paint_plain(&mut self, text: &str) -> Result<()> {
+        self.stdout.queue(Print(strip_ansi(text)))?;
+
+        self.stdout.flush()
+    }
+
     /// Goes to the beginning of the next line
     ///
     /// Also works in raw mode
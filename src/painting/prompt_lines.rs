@@ -1,10 +1,49 @@
-use super::utils::{coerce_crlf, estimate_required_lines, line_width};
+use super::utils::{coerce_crlf, estimate_required_lines, line_width, strip_ansi};
 use crate::{
     menu::{Menu, ReedlineMenu},
     prompt::PromptEditMode,
     Prompt, PromptHistorySearch,
 };
 use std::borrow::Cow;
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
+/// Position of the next cell to paint while following the output of the painter
+struct ScreenCursor {
+    column: usize,
+    row: usize,
+    terminal_columns: usize,
+}
+
+impl ScreenCursor {
+    /// Moves past a grapheme of the given width, returning the row it is painted on
+    fn advance(&mut self, width: usize) -> usize {
+        if self.column + width > self.terminal_columns {
+            self.column = 0;
+            self.row += 1;
+        }
+        self.column += width;
+        self.row
+    }
+
+    fn newline(&mut self) {
+        self.column = 0;
+        self.row += 1;
+    }
+
+    /// Moves past text without keeping track of the graphemes
+    fn skip(&mut self, text: &str) {
+        for grapheme in text.graphemes(true) {
+            match grapheme {
+                "\n" | "\r\n" => self.newline(),
+                "\r" => self.column = 0,
+                grapheme => {
+                    self.advance(grapheme.width());
+                }
+            }
+        }
+    }
+}
 
 /// Aggregate of prompt and input string used by `Painter`
 #[derive(Debug)]
@@ -15,6 +54,8 @@ pub(crate) struct PromptLines<'prompt> {
     pub(crate) before_cursor: Cow<'prompt, str>,
     pub(crate) after_cursor: Cow<'prompt, str>,
     pub(crate) hint: Cow<'prompt, str>,
+    // Validation diagnostics painted below the buffer, set after construction
+    pub(crate) diagnostics: Cow<'prompt, str>,
     pub(crate) right_prompt_on_last_line: bool,
 }
 
@@ -50,6 +91,7 @@ impl<'prompt> PromptLines<'prompt> {
             before_cursor,
             after_cursor,
             hint,
+            diagnostics: Cow::Borrowed(""),
             right_prompt_on_last_line,
         }
     }
@@ -64,11 +106,13 @@ impl<'prompt> PromptLines<'prompt> {
                 + &self.before_cursor
                 + &self.after_cursor
                 + &self.hint
+                + &self.diagnostics
         } else {
             self.prompt_str_left.to_string()
                 + &self.prompt_indicator
                 + &self.before_cursor
                 + &self.after_cursor
+                + &self.diagnostics
         };
 
         let lines = estimate_required_lines(&input, terminal_columns);
@@ -80,6 +124,11 @@ impl<'prompt> PromptLines<'prompt> {
         }
     }
 
+    /// Rows taken by the diagnostics painted below the buffer, considering line wrapping
+    pub(crate) fn diagnostics_rows(&self, terminal_columns: u16) -> u16 {
+        estimate_required_lines(&self.diagnostics, terminal_columns).saturating_sub(1) as u16
+    }
+
     /// Estimated distance of the cursor to the prompt.
     /// This considers line wrapping
     pub(crate) fn distance_from_prompt(&self, terminal_columns: u16) -> u16 {
@@ -116,6 +165,55 @@ impl<'prompt> PromptLines<'prompt> {
         (cursor_x, cursor_y as u16)
     }
 
+    /// Offset in `buffer` of the grapheme painted at `column` and `row`, the row being
+    /// relative to the start of the prompt
+    ///
+    /// The layout follows the painter: the prompt, then the buffer with the
+    /// `multiline_indicator` after every newline. Positions past the end of a line map to the
+    /// end of that line and positions left of the buffer on the last prompt line to its start.
+    /// Returns `None` for rows that only contain the prompt and rows below the buffer.
+    pub(crate) fn buffer_offset_at(
+        &self,
+        column: u16,
+        row: u16,
+        terminal_columns: u16,
+        buffer: &str,
+        multiline_indicator: &str,
+    ) -> Option<usize> {
+        let (column, row) = (column as usize, row as usize);
+        let mut cursor = ScreenCursor {
+            column: 0,
+            row: 0,
+            terminal_columns: terminal_columns.max(1) as usize,
+        };
+
+        cursor.skip(&strip_ansi(
+            &(self.prompt_str_left.to_string() + &self.prompt_indicator),
+        ));
+        if row < cursor.row {
+            return None;
+        }
+
+        let multiline_indicator = strip_ansi(multiline_indicator);
+        for (offset, grapheme) in buffer.grapheme_indices(true) {
+            if grapheme == "\n" || grapheme == "\r\n" {
+                if cursor.row == row {
+                    return Some(offset);
+                }
+                cursor.newline();
+                cursor.skip(&multiline_indicator);
+            } else {
+                let width = grapheme.width();
+                let grapheme_row = cursor.advance(width);
+                if grapheme_row > row || (grapheme_row == row && column < cursor.column) {
+                    return Some(offset);
+                }
+            }
+        }
+
+        (cursor.row == row).then_some(buffer.len())
+    }
+
     /// Total lines that the prompt uses considering that it may wrap the screen
     pub(crate) fn prompt_lines_with_wrap(&self, screen_width: u16) -> u16 {
         let complete_prompt = self.prompt_str_left.to_string() + &self.prompt_indicator;
@@ -245,6 +343,7 @@ mod tests {
             before_cursor: Cow::Borrowed(before_cursor),
             after_cursor: Cow::Borrowed(""),
             hint: Cow::Borrowed(""),
+            diagnostics: Cow::Borrowed(""),
             right_prompt_on_last_line: false,
         };
 
@@ -252,4 +351,40 @@ mod tests {
 
         assert_eq!(pos, expected);
     }
+
+    #[rstest]
+    #[case("> ", "abc", (3, 0), 10, Some(1))]
+    #[case("> ", "abc", (0, 0), 10, Some(0))]
+    #[case("> ", "abc", (8, 0), 10, Some(3))]
+    #[case("> ", "abc", (0, 1), 10, None)]
+    #[case("> ", "abc\ndef", (5, 1), 10, Some(5))]
+    #[case("> ", "abc\ndef", (9, 0), 10, Some(3))]
+    #[case("> ", "abc\n\ndef", (4, 1), 10, Some(4))]
+    #[case("> ", "abcdefghijkl", (1, 1), 10, Some(9))]
+    #[case("> ", "abcdefgh一二", (1, 1), 9, Some(8))]
+    #[case("path\n> ", "abc", (2, 0), 10, None)]
+    #[case("path\n> ", "abc", (2, 1), 10, Some(0))]
+    fn test_buffer_offset_at(
+        #[case] prompt_indicator: &str,
+        #[case] buffer: &str,
+        #[case] position: (u16, u16),
+        #[case] terminal_columns: u16,
+        #[case] expected: Option<usize>,
+    ) {
+        let prompt_lines = PromptLines {
+            prompt_str_left: Cow::Borrowed(""),
+            prompt_str_right: Cow::Borrowed(""),
+            prompt_indicator: Cow::Borrowed(prompt_indicator),
+            before_cursor: Cow::Borrowed(""),
+            after_cursor: Cow::Borrowed(""),
+            hint: Cow::Borrowed(""),
+            diagnostics: Cow::Borrowed(""),
+            right_prompt_on_last_line: false,
+        };
+
+        let offset =
+            prompt_lines.buffer_offset_at(position.0, position.1, terminal_columns, buffer, "::: ");
+
+        assert_eq!(offset, expected);
+    }
 }
@@ -30,6 +30,18 @@ impl StyledText {
 
     /// Style range with the provided style
     pub fn style_range(&mut self, from: usize, to: usize, new_style: Style) {
+        self.update_style_range(from, to, |_| new_style);
+    }
+
+    /// Changes the style of the given range with `update`, which receives the current style
+    ///
+    /// Unlike [`StyledText::style_range()`] this can keep the highlighting, e.g. to underline it.
+    pub(crate) fn update_style_range(
+        &mut self,
+        from: usize,
+        to: usize,
+        update: impl Fn(Style) -> Style,
+    ) {
         let (from, to) = if from > to { (to, from) } else { (from, to) };
         let mut current_idx = 0;
         let mut pair_idx = 0;
@@ -59,27 +71,27 @@ impl StyledText {
                 (Position::Before, Position::After) => {
                     let mut in_range = pair.1.split_off(from - current_idx);
                     let after_range = in_range.split_off(to - from);
-                    let in_range = (new_style, in_range);
+                    let in_range = (update(pair.0), in_range);
                     let after_range = (pair.0, after_range);
                     self.buffer.insert(pair_idx + 1, in_range);
                     self.buffer.insert(pair_idx + 2, after_range);
                     break;
                 }
                 (Position::Before, Position::In) => {
-                    let in_range = pair.1.split_off(from - current_idx);
+                    let in_range = (update(pair.0), pair.1.split_off(from - current_idx));
                     pair_idx += 1; // Additional increment for the split pair, since the new insertion is already correctly styled and can be skipped next iteration
-                    self.buffer.insert(pair_idx, (new_style, in_range));
+                    self.buffer.insert(pair_idx, in_range);
                 }
                 (Position::In, Position::After) => {
                     let after_range = pair.1.split_off(to - current_idx);
                     let old_style = pair.0;
-                    pair.0 = new_style;
+                    pair.0 = update(old_style);
                     if !after_range.is_empty() {
                         self.buffer.insert(pair_idx + 1, (old_style, after_range));
                     }
                     break;
                 }
-                (Position::In, Position::In) => pair.0 = new_style,
+                (Position::In, Position::In) => pair.0 = update(pair.0),
 
                 (Position::After, _) => break,
                 _ => (),
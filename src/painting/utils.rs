@@ -26,7 +26,8 @@ pub(crate) fn coerce_crlf(input: &str) -> Cow<str> {
             cursor = idx + 1;
         }
     }
-    if let Cow::Owned(_) = result {
+    // A leading newline leaves `result` borrowed, `AddAssign` skips copying into an empty string
+    if cursor > 0 {
         result += &input[cursor..input.len()];
     }
     result
@@ -83,6 +84,7 @@ mod test {
     #[case("😇\nsentence", "😇\r\nsentence")]
     #[case("sentence\n😇", "sentence\r\n😇")]
     #[case("\n", "\r\n")]
+    #[case("\nsentence", "\r\nsentence")]
     #[case("", "")]
     fn test_coerce_crlf(#[case] input: &str, #[case] expected: &str) {
         let result = coerce_crlf(input);
@@ -43,7 +43,7 @@ impl PromptHistorySearch {
 }
 
 /// Modes that the prompt can be in
-#[derive(Serialize, Deserialize, Clone, Debug, EnumIter)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, EnumIter)]
 pub enum PromptEditMode {
     /// The default mode
     Default,
@@ -59,7 +59,7 @@ pub enum PromptEditMode {
 }
 
 /// The vi-specific modes that the prompt can be in
-#[derive(Serialize, Deserialize, Clone, Debug, EnumIter, Default)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, EnumIter, Default)]
 pub enum PromptViMode {
     /// The default mode
     #[default]
@@ -67,6 +67,9 @@ pub enum PromptViMode {
 
     /// Insertion mode
     Insert,
+
+    /// Visual mode selecting whole lines
+    VisualLine,
 }
 
 impl Display for PromptEditMode {
@@ -74,7 +77,7 @@ impl Display for PromptEditMode {
         match self {
             PromptEditMode::Default => write!(f, "Default"),
             PromptEditMode::Emacs => write!(f, "Emacs"),
-            PromptEditMode::Vi(_) => write!(f, "Vi_Normal\nVi_Insert"),
+            PromptEditMode::Vi(_) => write!(f, "Vi_Normal\nVi_Insert\nVi_VisualLine"),
             PromptEditMode::Custom(s) => write!(f, "Custom_{s}"),
         }
     }
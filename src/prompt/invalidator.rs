@@ -0,0 +1,51 @@
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
+
+/// Handle to request a repaint of the prompt of the running line editor
+///
+/// Obtained from [`crate::Reedline::prompt_invalidator()`]. Call
+/// [`PromptInvalidator::invalidate()`] e.g. from a thread that finished computing the git
+/// status shown in the prompt. While a handle exists, [`crate::Reedline::read_line()`] checks
+/// periodically for invalidations and repaints the prompt even if the user is idle.
+#[derive(Debug, Clone, Default)]
+pub struct PromptInvalidator {
+    invalidated: Arc<AtomicBool>,
+}
+
+impl PromptInvalidator {
+    /// Mark the prompt as outdated so that it gets repainted
+    pub fn invalidate(&self) {
+        self.invalidated.store(true, Ordering::Release);
+    }
+
+    /// Checks and resets if the prompt was invalidated
+    pub(crate) fn take_invalidated(&self) -> bool {
+        self.invalidated.swap(false, Ordering::AcqRel)
+    }
+
+    /// Checks if handles were given out, only then invalidations have to be awaited
+    pub(crate) fn is_shared(&self) -> bool {
+        Arc::strong_count(&self.invalidated) > 1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn invalidation_is_taken_once() {
+        let invalidator = PromptInvalidator::default();
+        assert!(!invalidator.is_shared());
+
+        let handle = invalidator.clone();
+        assert!(invalidator.is_shared());
+        assert!(!invalidator.take_invalidated());
+
+        handle.invalidate();
+        assert!(invalidator.take_invalidated());
+        assert!(!invalidator.take_invalidated());
+    }
+}
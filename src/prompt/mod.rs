@@ -1,8 +1,10 @@
 mod base;
 mod default;
+mod invalidator;
 
 pub use base::{
     Prompt, PromptEditMode, PromptHistorySearch, PromptHistorySearchStatus, PromptViMode,
 };
 
 pub use default::{DefaultPrompt, DefaultPromptSegment};
+pub use invalidator::PromptInvalidator;
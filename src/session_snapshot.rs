@@ -0,0 +1,106 @@
+use crate::core_editor::EditorState;
+use serde::{Deserialize, Serialize};
+
+/// Editing state of a [`crate::Reedline`] line editor that can be persisted
+///
+/// Covers the buffer with its insertion point, the undo and redo history, the selection and
+/// the internal mode of the edit mode, like the vi normal and insert modes. Take it with
+/// [`crate::Reedline::session_snapshot()`] before the process exits and hand it to
+/// [`crate::Reedline::restore_session_snapshot()`] after a restart to continue editing.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, Debug)]
+pub struct SessionSnapshot {
+    editor: EditorState,
+    edit_mode: Option<String>,
+}
+
+impl SessionSnapshot {
+    pub(crate) fn new(editor: EditorState, edit_mode: Option<String>) -> Self {
+        SessionSnapshot { editor, edit_mode }
+    }
+
+    pub(crate) fn into_parts(self) -> (EditorState, Option<String>) {
+        (self.editor, self.edit_mode)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        default_vi_insert_keybindings, default_vi_normal_keybindings, EditCommand, EditMode,
+        PromptEditMode, PromptViMode, Reedline, Vi,
+    };
+    use pretty_assertions::assert_eq;
+
+    fn vi_line_editor(mode: &str) -> Reedline {
+        let mut vi = Vi::new(
+            default_vi_insert_keybindings(),
+            default_vi_normal_keybindings(),
+        );
+        vi.restore_mode_state(mode);
+        Reedline::create().with_edit_mode(Box::new(vi))
+    }
+
+    #[test]
+    fn restores_the_buffer_and_its_undo_history() {
+        let mut line_editor = Reedline::create();
+        line_editor.run_edit_commands(&[
+            EditCommand::InsertString("for x in 1..3 {".to_string()),
+            EditCommand::InsertNewline,
+            EditCommand::InsertString("echo $x".to_string()),
+            EditCommand::MoveToLineStart { select: false },
+            EditCommand::MoveToLineEnd { select: true },
+        ]);
+
+        let json = serde_json::to_string(&line_editor.session_snapshot()).unwrap();
+        let mut restored = Reedline::create();
+        restored.restore_session_snapshot(serde_json::from_str(&json).unwrap());
+
+        assert_eq!(restored.session_snapshot(), line_editor.session_snapshot());
+        assert_eq!(
+            restored.current_buffer_contents(),
+            "for x in 1..3 {\necho $x"
+        );
+        assert_eq!(restored.current_insertion_point(), 23);
+
+        for line_editor in [&mut line_editor, &mut restored] {
+            line_editor.run_edit_commands(&[EditCommand::Undo, EditCommand::Undo]);
+        }
+        assert_eq!(restored.current_buffer_contents(), "for x in 1..3 {");
+        assert_eq!(
+            restored.current_buffer_contents(),
+            line_editor.current_buffer_contents()
+        );
+    }
+
+    #[test]
+    fn restores_the_vi_mode() {
+        let line_editor = vi_line_editor("normal");
+
+        let snapshot = line_editor.session_snapshot();
+        let mut restored = vi_line_editor("insert");
+        restored.restore_session_snapshot(snapshot);
+
+        assert!(matches!(
+            restored.prompt_edit_mode(),
+            PromptEditMode::Vi(PromptViMode::Normal)
+        ));
+    }
+
+    #[test]
+    fn repairs_positions_outside_of_the_buffer() {
+        let json = r#"{
+            "editor": {
+                "line_buffer": { "lines": "äb", "insertion_point": 1 },
+                "edit_stack": { "internal_list": [], "index": 4 },
+                "selection_anchor": 7
+            },
+            "edit_mode": null
+        }"#;
+
+        let mut line_editor = Reedline::create();
+        line_editor.restore_session_snapshot(serde_json::from_str(json).unwrap());
+        line_editor.run_edit_commands(&[EditCommand::InsertChar('c')]);
+
+        assert_eq!(line_editor.current_buffer_contents(), "äbc");
+    }
+}
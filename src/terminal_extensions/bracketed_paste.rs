@@ -1,8 +1,12 @@
 use crossterm::{event, execute};
+use std::io::Write;
 
 /// Helper managing proper setup and teardown of bracketed paste mode
 ///
 /// <https://en.wikipedia.org/wiki/Bracketed-paste>
+///
+/// The escape sequences go to the output passed in, the owner has to call
+/// [`BracketedPasteGuard::exit()`] before dropping it.
 #[derive(Default)]
 pub(crate) struct BracketedPasteGuard {
     enabled: bool,
@@ -13,24 +17,16 @@ impl BracketedPasteGuard {
     pub fn set(&mut self, enable: bool) {
         self.enabled = enable;
     }
-    pub fn enter(&mut self) {
+    pub fn enter(&mut self, mut out: impl Write) {
         if self.enabled && !self.active {
-            let _ = execute!(std::io::stdout(), event::EnableBracketedPaste);
+            let _ = execute!(out, event::EnableBracketedPaste);
             self.active = true;
         }
     }
-    pub fn exit(&mut self) {
+    pub fn exit(&mut self, mut out: impl Write) {
         if self.active {
-            let _ = execute!(std::io::stdout(), event::DisableBracketedPaste);
+            let _ = execute!(out, event::DisableBracketedPaste);
             self.active = false;
         }
     }
 }
-
-impl Drop for BracketedPasteGuard {
-    fn drop(&mut self) {
-        if self.active {
-            let _ = execute!(std::io::stdout(), event::DisableBracketedPaste);
-        }
-    }
-}
@@ -1,4 +1,5 @@
 use crossterm::{event, execute};
+use std::io::Write;
 
 /// Helper managing proper setup and teardown of the kitty keyboard enhancement protocol
 ///
@@ -12,6 +13,9 @@ use crossterm::{event, execute};
 /// * [dte text editor](https://gitlab.com/craigbarnes/dte/-/issues/138)
 ///
 /// Refer to <https://sw.kovidgoyal.net/kitty/keyboard-protocol/> if you're curious.
+///
+/// The escape sequences go to the output passed in, the owner has to call
+/// [`KittyProtocolGuard::exit()`] before dropping it.
 #[derive(Default)]
 pub(crate) struct KittyProtocolGuard {
     enabled: bool,
@@ -22,10 +26,10 @@ impl KittyProtocolGuard {
     pub fn set(&mut self, enable: bool) {
         self.enabled = enable && super::kitty_protocol_available();
     }
-    pub fn enter(&mut self) {
+    pub fn enter(&mut self, mut out: impl Write) {
         if self.enabled && !self.active {
             let _ = execute!(
-                std::io::stdout(),
+                out,
                 event::PushKeyboardEnhancementFlags(
                     event::KeyboardEnhancementFlags::DISAMBIGUATE_ESCAPE_CODES
                 )
@@ -34,18 +38,10 @@ impl KittyProtocolGuard {
             self.active = true;
         }
     }
-    pub fn exit(&mut self) {
+    pub fn exit(&mut self, mut out: impl Write) {
         if self.active {
-            let _ = execute!(std::io::stdout(), event::PopKeyboardEnhancementFlags);
+            let _ = execute!(out, event::PopKeyboardEnhancementFlags);
             self.active = false;
         }
     }
 }
-
-impl Drop for KittyProtocolGuard {
-    fn drop(&mut self) {
-        if self.active {
-            let _ = execute!(std::io::stdout(), event::PopKeyboardEnhancementFlags);
-        }
-    }
-}
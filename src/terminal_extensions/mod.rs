@@ -1,5 +1,6 @@
 pub(crate) mod bracketed_paste;
 pub(crate) mod kitty;
+pub(crate) mod mouse_capture;
 
 /// Return if the terminal supports the kitty keyboard enhancement protocol
 ///
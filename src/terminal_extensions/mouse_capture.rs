@@ -0,0 +1,33 @@
+use crossterm::{event, execute};
+use std::io::Write;
+
+/// Helper managing proper setup and teardown of the mouse capture
+///
+/// While active the terminal reports clicks and wheel scrolls instead of handling them itself
+/// (e.g. selecting text)
+///
+/// The escape sequences go to the output passed in, the owner has to call
+/// [`MouseCaptureGuard::exit()`] before dropping it.
+#[derive(Default)]
+pub(crate) struct MouseCaptureGuard {
+    enabled: bool,
+    active: bool,
+}
+
+impl MouseCaptureGuard {
+    pub fn set(&mut self, enable: bool) {
+        self.enabled = enable;
+    }
+    pub fn enter(&mut self, mut out: impl Write) {
+        if self.enabled && !self.active {
+            let _ = execute!(out, event::EnableMouseCapture);
+            self.active = true;
+        }
+    }
+    pub fn exit(&mut self, mut out: impl Write) {
+        if self.active {
+            let _ = execute!(out, event::DisableMouseCapture);
+            self.active = false;
+        }
+    }
+}
@@ -114,6 +114,8 @@ pub fn get_reedline_edit_commands() -> Vec<String> {
 /// Get the default keybindings and return a `Vec<(String, String, String, String)>`
 /// where String 1 is `mode`, String 2 is `key_modifiers`, String 3 is `key_code`, and
 /// Sting 4 is `event`
+///
+/// The modifiers and key codes of the keys of a sequence binding are separated by spaces.
 pub fn get_reedline_default_keybindings() -> Vec<(String, String, String, String)> {
     let options = vec![
         ("emacs", default_emacs_keybindings()),
@@ -142,6 +144,27 @@ fn get_keybinding_strings(
                 format!("{event:?}"),
             )
         })
+        .chain(
+            keybindings
+                .get_sequence_bindings()
+                .iter()
+                .map(|(sequence, event)| {
+                    let modifiers: Vec<String> = sequence
+                        .iter()
+                        .map(|combination| format!("{:?}", combination.modifier))
+                        .collect();
+                    let key_codes: Vec<String> = sequence
+                        .iter()
+                        .map(|combination| format!("{:?}", combination.key_code))
+                        .collect();
+                    (
+                        mode.to_string(),
+                        modifiers.join(" "),
+                        key_codes.join(" "),
+                        format!("{event:?}"),
+                    )
+                }),
+        )
         .collect();
 
     data.sort();
@@ -14,9 +14,14 @@ impl Validator for DefaultValidator {
 }
 
 fn incomplete_brackets(line: &str) -> bool {
+    !open_brackets(line).is_empty()
+}
+
+/// Closers of the brackets left open in `text`, the innermost one last
+pub(crate) fn open_brackets(text: &str) -> Vec<char> {
     let mut balance: Vec<char> = Vec::new();
 
-    for c in line.chars() {
+    for c in text.chars() {
         if c == '{' {
             balance.push('}');
         } else if c == '[' {
@@ -32,7 +37,7 @@ fn incomplete_brackets(line: &str) -> bool {
         }
     }
 
-    !balance.is_empty()
+    balance
 }
 
 #[cfg(test)]
@@ -1,6 +1,9 @@
 mod default;
+pub(crate) use default::open_brackets;
 pub use default::DefaultValidator;
 
+use crate::Span;
+
 /// The syntax validation trait. Implementers of this trait will check to see if the current input
 /// is incomplete and spans multiple lines
 pub trait Validator: Send {
@@ -8,7 +11,7 @@ pub trait Validator: Send {
     fn validate(&self, line: &str) -> ValidationResult;
 }
 
-#[derive(Clone, Copy)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 /// Whether or not the validation shows the input was complete
 pub enum ValidationResult {
     /// An incomplete input which may need to span multiple lines to be complete
@@ -16,4 +19,47 @@ pub enum ValidationResult {
 
     /// An input that is complete as-is
     Complete,
+
+    /// An input that can't be submitted as long as the [`Diagnostic`]s apply to it
+    ///
+    /// The diagnostics are shown below the buffer with their spans underlined. They are updated
+    /// while the user edits the buffer and disappear once the validator accepts it.
+    Invalid(Vec<Diagnostic>),
+}
+
+/// Problem of the input found by a [`Validator`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    /// How serious the problem is, decides the color it is shown in
+    pub severity: Severity,
+
+    /// Explanation shown below the buffer
+    pub message: String,
+
+    /// Part of the buffer the problem is about, in bytes
+    pub span: Span,
+}
+
+impl Diagnostic {
+    /// Creates a new [`Diagnostic`] about the `span` of the buffer
+    pub fn new(severity: Severity, message: impl Into<String>, span: Span) -> Self {
+        Diagnostic {
+            severity,
+            message: message.into(),
+            span,
+        }
+    }
+}
+
+/// Severity of a [`Diagnostic`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    /// The input is wrong
+    Error,
+
+    /// The input is likely to be wrong
+    Warning,
+
+    /// Additional information about the input
+    Info,
 }
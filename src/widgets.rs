@@ -0,0 +1,289 @@
+use crate::{Completer, EditCommand, Menu, MenuEvent, ReedlineEvent, Span, Suggestion};
+use crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers};
+
+// Painted in front of the items of a multi-select
+const CHECKED: &str = "[x] ";
+const UNCHECKED: &str = "[ ] ";
+
+/// What an event does in [`crate::Reedline::select()`] and [`crate::Reedline::multi_select()`]
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) enum SelectAction {
+    /// Finish with the selected or checked items
+    Accept,
+    /// Finish without an answer
+    Cancel,
+    /// Move the selection in the menu
+    Menu(MenuEvent),
+    /// Edit the text filtering the items
+    Edit(Vec<EditCommand>),
+    /// Check or uncheck the selected item of a multi-select
+    Toggle,
+    /// Resize the painted area
+    Resize(u16, u16),
+    /// Nothing to do
+    Ignore,
+}
+
+/// What a key pressed in [`crate::Reedline::confirm()`] does
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) enum ConfirmKey {
+    /// Answer the question
+    Answer(bool),
+    /// Leave the question unanswered
+    Cancel,
+    /// Keep waiting for an answer
+    Ignore,
+}
+
+/// Reads the answer of [`crate::Reedline::confirm()`] from a pressed key
+///
+/// Only Enter answers with the `default`, without one a `y` or `n` is required.
+pub(crate) fn confirm_key(event: &Event, default: Option<bool>) -> ConfirmKey {
+    let (code, modifiers) = match event {
+        Event::Key(KeyEvent {
+            code, modifiers, ..
+        }) => (code, *modifiers),
+        _ => return ConfirmKey::Ignore,
+    };
+    match (code, modifiers) {
+        (KeyCode::Char('y' | 'Y'), KeyModifiers::NONE | KeyModifiers::SHIFT) => {
+            ConfirmKey::Answer(true)
+        }
+        (KeyCode::Char('n' | 'N'), KeyModifiers::NONE | KeyModifiers::SHIFT) => {
+            ConfirmKey::Answer(false)
+        }
+        (KeyCode::Enter, KeyModifiers::NONE) => {
+            default.map_or(ConfirmKey::Ignore, ConfirmKey::Answer)
+        }
+        (KeyCode::Esc, KeyModifiers::NONE) | (KeyCode::Char('c' | 'd'), KeyModifiers::CONTROL) => {
+            ConfirmKey::Cancel
+        }
+        _ => ConfirmKey::Ignore,
+    }
+}
+
+/// Choices offered next to the question of [`crate::Reedline::confirm()`]
+pub(crate) fn confirm_choices(default: Option<bool>) -> &'static str {
+    match default {
+        Some(true) => "[Y/n]",
+        Some(false) => "[y/N]",
+        None => "[y/n]",
+    }
+}
+
+/// Action of a select widget for the event the edit mode parsed from a key
+///
+/// Esc only cancels when it didn't switch the edit mode, e.g. from vi insert to vi normal mode.
+pub(crate) fn select_action(
+    event: ReedlineEvent,
+    multiple: bool,
+    mode_changed: bool,
+) -> SelectAction {
+    match event {
+        ReedlineEvent::Enter | ReedlineEvent::Submit | ReedlineEvent::SubmitOrNewline => {
+            SelectAction::Accept
+        }
+        ReedlineEvent::CtrlC | ReedlineEvent::CtrlD => SelectAction::Cancel,
+        ReedlineEvent::Esc if !mode_changed => SelectAction::Cancel,
+        ReedlineEvent::MenuUp | ReedlineEvent::Up | ReedlineEvent::PreviousHistory => {
+            SelectAction::Menu(MenuEvent::MoveUp)
+        }
+        ReedlineEvent::MenuDown | ReedlineEvent::Down | ReedlineEvent::NextHistory => {
+            SelectAction::Menu(MenuEvent::MoveDown)
+        }
+        ReedlineEvent::MenuLeft | ReedlineEvent::Left => SelectAction::Menu(MenuEvent::MoveLeft),
+        ReedlineEvent::MenuRight | ReedlineEvent::Right => SelectAction::Menu(MenuEvent::MoveRight),
+        ReedlineEvent::MenuNext => SelectAction::Menu(MenuEvent::NextElement),
+        ReedlineEvent::MenuPrevious => SelectAction::Menu(MenuEvent::PreviousElement),
+        ReedlineEvent::MenuPageNext => SelectAction::Menu(MenuEvent::NextPage),
+        ReedlineEvent::MenuPagePrevious => SelectAction::Menu(MenuEvent::PreviousPage),
+        ReedlineEvent::Edit(commands) if multiple && commands == [EditCommand::InsertChar(' ')] => {
+            SelectAction::Toggle
+        }
+        ReedlineEvent::Edit(commands) => SelectAction::Edit(commands),
+        ReedlineEvent::Resize(width, height) => SelectAction::Resize(width, height),
+        // The first event of the list that does something, like the engine would
+        ReedlineEvent::UntilFound(events) | ReedlineEvent::Multiple(events) => events
+            .into_iter()
+            .map(|event| select_action(event, multiple, mode_changed))
+            .find(|action| *action != SelectAction::Ignore)
+            .unwrap_or(SelectAction::Ignore),
+        _ => SelectAction::Ignore,
+    }
+}
+
+/// Items of [`crate::Reedline::select()`] and [`crate::Reedline::multi_select()`]
+///
+/// Completes the typed filter with the items containing it, ignoring case. Items of a
+/// multi-select are shown with a checkbox. The index of the item is kept as the only
+/// [`Suggestion::extra`] value.
+pub(crate) struct SelectItems {
+    items: Vec<String>,
+    checked: Option<Vec<bool>>,
+}
+
+impl SelectItems {
+    pub(crate) fn new(items: &[impl AsRef<str>], multiple: bool) -> Self {
+        SelectItems {
+            items: items.iter().map(|item| item.as_ref().to_string()).collect(),
+            checked: multiple.then(|| vec![false; items.len()]),
+        }
+    }
+
+    pub(crate) fn is_multiple(&self) -> bool {
+        self.checked.is_some()
+    }
+
+    /// Index of the item selected in `menu`
+    pub(crate) fn selected(&self, menu: &dyn Menu) -> Option<usize> {
+        let suggestion = menu.get_values().get(menu.selected_index()?)?;
+        suggestion.extra.as_ref()?.first()?.parse().ok()
+    }
+
+    pub(crate) fn toggle(&mut self, index: usize) {
+        if let Some(checked) = self
+            .checked
+            .as_mut()
+            .and_then(|checked| checked.get_mut(index))
+        {
+            *checked = !*checked;
+        }
+    }
+
+    /// Indices of the checked items, in the order of the items
+    pub(crate) fn checked(&self) -> Vec<usize> {
+        self.checked
+            .iter()
+            .flatten()
+            .enumerate()
+            .filter(|(_, checked)| **checked)
+            .map(|(index, _)| index)
+            .collect()
+    }
+}
+
+impl Completer for SelectItems {
+    fn complete(&mut self, line: &str, _pos: usize) -> Vec<Suggestion> {
+        let filter = line.to_lowercase();
+        self.items
+            .iter()
+            .enumerate()
+            .filter(|(_, item)| item.to_lowercase().contains(&filter))
+            .map(|(index, item)| {
+                let value = match &self.checked {
+                    Some(checked) if checked[index] => format!("{CHECKED}{item}"),
+                    Some(_) => format!("{UNCHECKED}{item}"),
+                    None => item.clone(),
+                };
+                Suggestion {
+                    value,
+                    extra: Some(vec![index.to_string()]),
+                    // The filter is not a prefix of the items, nothing is highlighted as match
+                    span: Span::new(0, 0),
+                    ..Suggestion::default()
+                }
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+
+    fn key(code: KeyCode, modifiers: KeyModifiers) -> Event {
+        Event::Key(KeyEvent::new(code, modifiers))
+    }
+
+    #[rstest]
+    #[case(KeyCode::Char('y'), KeyModifiers::NONE, None, ConfirmKey::Answer(true))]
+    #[case(
+        KeyCode::Char('N'),
+        KeyModifiers::SHIFT,
+        Some(true),
+        ConfirmKey::Answer(false)
+    )]
+    #[case(
+        KeyCode::Enter,
+        KeyModifiers::NONE,
+        Some(true),
+        ConfirmKey::Answer(true)
+    )]
+    #[case(KeyCode::Enter, KeyModifiers::NONE, None, ConfirmKey::Ignore)]
+    #[case(
+        KeyCode::Char('x'),
+        KeyModifiers::NONE,
+        Some(false),
+        ConfirmKey::Ignore
+    )]
+    #[case(KeyCode::Char('y'), KeyModifiers::CONTROL, None, ConfirmKey::Ignore)]
+    #[case(
+        KeyCode::Char('d'),
+        KeyModifiers::CONTROL,
+        Some(false),
+        ConfirmKey::Cancel
+    )]
+    #[case(KeyCode::Esc, KeyModifiers::NONE, None, ConfirmKey::Cancel)]
+    fn confirm_keys(
+        #[case] code: KeyCode,
+        #[case] modifiers: KeyModifiers,
+        #[case] default: Option<bool>,
+        #[case] expected: ConfirmKey,
+    ) {
+        assert_eq!(confirm_key(&key(code, modifiers), default), expected);
+    }
+
+    #[rstest]
+    #[case(
+        ReedlineEvent::UntilFound(vec![ReedlineEvent::MenuUp, ReedlineEvent::Up]),
+        false,
+        SelectAction::Menu(MenuEvent::MoveUp)
+    )]
+    #[case(
+        ReedlineEvent::Edit(vec![EditCommand::InsertChar(' ')]),
+        true,
+        SelectAction::Toggle
+    )]
+    #[case(
+        ReedlineEvent::Edit(vec![EditCommand::InsertChar(' ')]),
+        false,
+        SelectAction::Edit(vec![EditCommand::InsertChar(' ')])
+    )]
+    #[case(
+        ReedlineEvent::Multiple(vec![ReedlineEvent::Esc, ReedlineEvent::Repaint]),
+        false,
+        SelectAction::Cancel
+    )]
+    #[case(ReedlineEvent::ClearScreen, false, SelectAction::Ignore)]
+    fn select_actions(
+        #[case] event: ReedlineEvent,
+        #[case] multiple: bool,
+        #[case] expected: SelectAction,
+    ) {
+        assert_eq!(select_action(event, multiple, false), expected);
+    }
+
+    #[test]
+    fn esc_switching_the_edit_mode_does_not_cancel() {
+        let esc = ReedlineEvent::Multiple(vec![ReedlineEvent::Esc, ReedlineEvent::Repaint]);
+
+        assert_eq!(select_action(esc, false, true), SelectAction::Ignore);
+    }
+
+    #[test]
+    fn items_containing_the_filter_are_completed() {
+        let mut items = SelectItems::new(&["Apple", "banana", "pineapple"], true);
+        items.toggle(2);
+
+        let values: Vec<_> = items
+            .complete("APP", 3)
+            .into_iter()
+            .map(|suggestion| suggestion.value)
+            .collect();
+
+        assert_eq!(values, vec!["[ ] Apple", "[x] pineapple"]);
+        assert_eq!(items.checked(), vec![2]);
+    }
+}